@@ -1,11 +1,19 @@
+mod plugin_api;
+mod time;
 mod world;
 
 use parking_lot::Mutex;
+pub use plugin_api::{Plugin, Requirement, Requirements};
+pub use time::{Stopwatch, Time, Timer, TimerMode};
 pub use world::World;
 
 use crossbeam::thread::scope;
+use ly_events::registry::{Event, Events};
+use ly_events::schedule;
 use ly_log::core_prelude::*;
 use std::process::exit;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
 
 pub type AppRunner = dyn FnOnce(App);
 //pub type AppSubProcess = dyn FnOnce(&'static World) -> () + Send;
@@ -19,6 +27,7 @@ pub struct App
 	runner: Option<Box<AppRunner>>,
 	processes: Option<Vec<AppSubProcess>>,
 	systems: Vec<AppSubProcess>,
+	requirements: Vec<Requirements>,
 }
 
 /// The state of the application
@@ -35,6 +44,7 @@ pub enum AppState
 pub struct AppInfo
 {
 	state: Mutex<AppState>,
+	exit_requested: AtomicBool,
 }
 
 impl AppInfo
@@ -43,12 +53,22 @@ impl AppInfo
 	{
 		AppInfo {
 			state: Mutex::new(AppState::Initialized),
+			exit_requested: AtomicBool::new(false),
 		}
 	}
 
 	/// Get the current state of the application
 	pub fn state(&self) -> AppState { *self.state.lock() }
 
+	/// Asks the runner to stop the application
+	///
+	/// It is up to the runner to check
+	/// [`exit_requested`](AppInfo::exit_requested) and stop when it suits it.
+	pub fn request_exit(&self) { self.exit_requested.store(true, Ordering::Relaxed); }
+
+	/// Checks if someone has asked the application to stop
+	pub fn exit_requested(&self) -> bool { self.exit_requested.load(Ordering::Relaxed) }
+
 	/// Sets new state for application
 	fn set_state(&self, state: AppState) { *self.state.lock() = state; }
 }
@@ -62,16 +82,30 @@ impl App
 		if let Err(e) = app.world.set_resource(AppInfo::new_initialized()) {
 			core_error!("Could not initialize AppInfo correctly due to {}", e)
 		}
+		if let Err(e) = app.world.create_resource::<Time>() {
+			core_error!("Could not initialize Time correctly due to {}", e)
+		}
+		if let Err(e) = app.world.create_resource::<Events>() {
+			core_error!("Could not initialize Events correctly due to {}", e)
+		}
 		app
 	}
 
 	/// Runs the application.
 	/// This will use the runner set by [`set_runner`](App::set_runner)
 	/// and hijack the running the thread.
+	///
+	/// Before starting, all declared [`Requirements`] are validated. If any
+	/// resource is missing, a report of everything missing is logged and the
+	/// app stops without running.
 	pub fn run(mut self) -> !
 	{
 		let mut exit_code = 0;
-		if let Some(runner) = self.runner.take() {
+		if let Err(report) = self.validate() {
+			core_error!("{}", report);
+			exit_code = 1;
+		}
+		else if let Some(runner) = self.runner.take() {
 			let world = self.get_world_handle();
 			world
 				.get_resource::<AppInfo>()
@@ -103,8 +137,27 @@ impl App
 	}
 
 	/// Update tick for application
-	pub fn update(&mut self)
+	///
+	/// Ticks the frame clock in [`Time`] and injects scheduled events that are
+	/// due. Then the event stage flushes the channels in [`Events`], before
+	/// running the systems.
+	pub fn update(&mut self) { self.tick(None); }
+
+	/// Like [`update`](App::update), but advances the frame clock by `delta`
+	///
+	/// Given the same events and deltas, the updates are the same, which is
+	/// what replays rely on.
+	pub fn update_with_delta(&mut self, delta: Duration) { self.tick(Some(delta)); }
+
+	fn tick(&mut self, delta: Option<Duration>)
 	{
+		if let Ok(time) = self.world.get_resource::<Time>() {
+			let frame = time.tick(delta);
+			schedule::scheduler().advance(frame, time.now());
+		}
+		if let Ok(events) = self.world.get_resource::<Events>() {
+			events.flush();
+		}
 		for system in self.systems.iter() {
 			system(&self.world);
 		}
@@ -113,6 +166,14 @@ impl App
 	/// Used to set a run function for this app.
 	pub fn set_runner(&mut self, runner: Box<AppRunner>) { self.runner = Some(runner); }
 
+	/// Like [`set_runner`](App::set_runner), declaring the resources the
+	/// runner requires
+	pub fn set_runner_requiring(&mut self, runner: Box<AppRunner>, requirements: Requirements)
+	{
+		self.set_runner(runner);
+		self.require(requirements);
+	}
+
 	/// Add a subprocess to the app.
 	/// The provided fn will we run in a separate thread and joined upon
 	/// application exit, so if the function never returns, the application
@@ -127,10 +188,102 @@ impl App
 		}
 	}
 
+	/// Like [`add_process`](App::add_process), declaring the resources the
+	/// process requires
+	pub fn add_process_requiring(&mut self, func: AppSubProcess, requirements: Requirements)
+	{
+		self.add_process(func);
+		self.require(requirements);
+	}
+
 	/// Adds a system to the application.
 	/// The provided fn will we run every app update in the main thread.
 	pub fn add_system(&mut self, func: AppSubProcess) { self.systems.push(func); }
 
+	/// Like [`add_system`](App::add_system), declaring the resources the
+	/// system requires
+	pub fn add_system_requiring(&mut self, func: AppSubProcess, requirements: Requirements)
+	{
+		self.add_system(func);
+		self.require(requirements);
+	}
+
+	/// Adds a channel for events of type `T` to the [`Events`] resource
+	///
+	/// The channel is flushed every update, before the systems are run.
+	pub fn add_event<T>(&mut self)
+	where
+		T: Send + Sync + 'static,
+	{
+		match self.world.get_resource::<Events>() {
+			Ok(events) => {
+				events.add::<T>();
+			}
+			Err(e) => core_error!("Could not add event due to {}", e),
+		}
+	}
+
+	/// Adds the channel of an [`Event`] to the [`Events`] resource,
+	/// configured as the event declares
+	///
+	/// Like [`add_event`](App::add_event), for events deriving `Event`.
+	pub fn add_configured_event<T>(&mut self)
+	where
+		T: Event,
+	{
+		match self.world.get_resource::<Events>() {
+			Ok(events) => {
+				events.add_event::<T>();
+			}
+			Err(e) => core_error!("Could not add event due to {}", e),
+		}
+	}
+
+	/// Builds the plugin and records its requirements
+	pub fn add_plugin<P>(&mut self, plugin: P)
+	where
+		P: Plugin,
+	{
+		plugin.build(self);
+		self.require(plugin.requirements());
+	}
+
+	/// Declares resources that must be present when the app is run
+	///
+	/// The requirements are checked by [`run`](App::run), so resources may be
+	/// added in any order before that.
+	pub fn require(&mut self, requirements: Requirements) { self.requirements.push(requirements); }
+
+	/// Checks that all declared requirements are met by the world
+	///
+	/// Returns a report of every missing resource, and who required it.
+	pub fn validate(&self) -> Result<(), String>
+	{
+		let mut missing: Vec<(&str, Vec<&str>)> = Vec::new();
+		for requirements in self.requirements.iter() {
+			for requirement in requirements.missing(&self.world) {
+				let owner = requirements.owner();
+				match missing
+					.iter_mut()
+					.find(|(type_name, _)| *type_name == requirement.type_name())
+				{
+					Some((_, owners)) => owners.push(owner),
+					None => missing.push((requirement.type_name(), vec![owner])),
+				}
+			}
+		}
+
+		if missing.is_empty() {
+			return Ok(());
+		}
+
+		let mut report = format!("App is missing {} required resource(s):", missing.len());
+		for (type_name, owners) in missing {
+			report += &format!("\n\t{} required by {}", type_name, owners.join(", "));
+		}
+		Err(report)
+	}
+
 	/// Gets a world handle to be passed to subprocess
 	/// TODO: create system to pass resources to subprocess instead of the world
 	fn get_world_handle(&self) -> &'static World
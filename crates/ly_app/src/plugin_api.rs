@@ -0,0 +1,157 @@
+use std::any::type_name;
+
+use crate::{App, World};
+
+/// A bundle of setup for the [`App`]
+///
+/// Plugins add their systems, processes and resources in
+/// [`build`](Plugin::build), and declare the resources they expect someone
+/// else to provide in [`requirements`](Plugin::requirements).
+pub trait Plugin
+{
+	/// Sets up the app, called once by [`App::add_plugin`]
+	fn build(&self, app: &mut App);
+
+	/// Name used when reporting missing requirements
+	fn name(&self) -> &str { type_name::<Self>() }
+
+	/// Resources that must be in the [`World`] before the app runs
+	fn requirements(&self) -> Requirements { Requirements::new(self.name()) }
+}
+
+/// A single resource that has to be present in the [`World`]
+#[derive(Clone, Copy)]
+pub struct Requirement
+{
+	type_name: &'static str,
+	is_present: fn(&World) -> bool,
+}
+
+impl Requirement
+{
+	/// Requires a resource of type `T`
+	pub fn resource<T>() -> Self
+	where
+		T: Send + Sync + 'static,
+	{
+		Requirement {
+			type_name: type_name::<T>(),
+			is_present: has_resource::<T>,
+		}
+	}
+
+	/// The name of the required type
+	pub fn type_name(&self) -> &'static str { self.type_name }
+
+	/// Checks if the requirement is met by `world`
+	pub fn is_met(&self, world: &World) -> bool { (self.is_present)(world) }
+}
+
+fn has_resource<T>(world: &World) -> bool
+where
+	T: Send + Sync + 'static,
+{
+	world.get_resource::<T>().is_ok()
+}
+
+/// The requirements of some named part of the app
+///
+/// The owner is the plugin, system, process or runner requiring the
+/// resources, and is only used for reporting.
+#[derive(Clone)]
+pub struct Requirements
+{
+	owner: String,
+	requirements: Vec<Requirement>,
+}
+
+impl Requirements
+{
+	pub fn new(owner: &str) -> Self
+	{
+		Requirements {
+			owner: owner.to_string(),
+			requirements: Vec::new(),
+		}
+	}
+
+	/// Adds a required resource of type `T`
+	pub fn resource<T>(self) -> Self
+	where
+		T: Send + Sync + 'static,
+	{
+		self.with(Requirement::resource::<T>())
+	}
+
+	/// Adds a requirement
+	pub fn with(mut self, requirement: Requirement) -> Self
+	{
+		self.requirements.push(requirement);
+		self
+	}
+
+	pub fn owner(&self) -> &str { &self.owner }
+
+	pub fn iter(&self) -> impl Iterator<Item = &Requirement> { self.requirements.iter() }
+
+	/// Gets the requirements not met by `world`
+	pub fn missing<'a>(&'a self, world: &'a World) -> impl Iterator<Item = &'a Requirement>
+	{
+		self.requirements.iter().filter(move |r| !r.is_met(world))
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+
+	struct NeedsCounter;
+
+	impl Plugin for NeedsCounter
+	{
+		fn build(&self, _app: &mut App) {}
+
+		fn name(&self) -> &str { "NeedsCounter" }
+
+		fn requirements(&self) -> Requirements { Requirements::new(self.name()).resource::<u32>() }
+	}
+
+	#[test]
+	fn requirements_001()
+	{
+		let world = World::new();
+		world.set_resource(0_u64).unwrap();
+		let requirements = Requirements::new("system")
+			.resource::<u32>()
+			.resource::<u64>();
+		assert_eq!(requirements.owner(), "system");
+		assert_eq!(requirements.iter().count(), 2);
+		let missing: Vec<_> = requirements
+			.missing(&world)
+			.map(|r| r.type_name())
+			.collect();
+		assert_eq!(missing, [type_name::<u32>()]);
+	}
+
+	#[test]
+	fn validate_001()
+	{
+		let mut app = App::default();
+		app.add_plugin(NeedsCounter);
+		app.add_system_requiring(|_| (), Requirements::new("counting").resource::<u32>());
+		app.add_process_requiring(|_| (), Requirements::new("logging").resource::<String>());
+
+		let report = app.validate().unwrap_err();
+		assert!(report.starts_with("App is missing 2 required resource(s):"));
+		assert!(report.contains(&format!(
+			"{} required by NeedsCounter, counting",
+			type_name::<u32>()
+		)));
+		assert!(report.contains(&format!("{} required by logging", type_name::<String>())));
+
+		app.world.set_resource(0_u32).unwrap();
+		app.world.create_resource::<String>().unwrap();
+		assert_eq!(app.validate(), Ok(()));
+	}
+}
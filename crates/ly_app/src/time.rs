@@ -0,0 +1,407 @@
+use parking_lot::Mutex;
+use std::sync::{Arc, Weak};
+use std::time::{Duration, Instant};
+
+/// The frame clock of the application
+///
+/// Kept as a resource in the [`World`](crate::World) and ticked by
+/// [`App::update`](crate::App::update). Timers and stopwatches added to the
+/// clock are ticked with it.
+pub struct Time
+{
+	clock: Mutex<Clock>,
+	timers: Mutex<Vec<Weak<Mutex<TimerState>>>>,
+	stopwatches: Mutex<Vec<Weak<Mutex<StopwatchState>>>>,
+}
+
+struct Clock
+{
+	startup: Instant,
+	last_tick: Option<Instant>,
+	delta: Duration,
+	elapsed: Duration,
+	frame: u64,
+	fixed_delta: Option<Duration>,
+}
+
+impl Default for Time
+{
+	fn default() -> Self
+	{
+		Time {
+			clock: Mutex::new(Clock {
+				startup: Instant::now(),
+				last_tick: None,
+				delta: Duration::ZERO,
+				elapsed: Duration::ZERO,
+				frame: 0,
+				fixed_delta: None,
+			}),
+			timers: Mutex::new(Vec::new()),
+			stopwatches: Mutex::new(Vec::new()),
+		}
+	}
+}
+
+impl Time
+{
+	/// Time between the two last ticks
+	pub fn delta(&self) -> Duration { self.clock.lock().delta }
+
+	/// Sum of all deltas since the first tick
+	pub fn elapsed(&self) -> Duration { self.clock.lock().elapsed }
+
+	/// Number of ticks so far
+	pub fn frame(&self) -> u64 { self.clock.lock().frame }
+
+	/// The instant the clock was created
+	pub fn startup(&self) -> Instant { self.clock.lock().startup }
+
+	/// Add a timer to be ticked with the clock
+	///
+	/// The clock only keeps a weak handle, the timer stops being ticked once
+	/// all clones of it are dropped.
+	pub fn add_timer(&self, timer: &Timer)
+	{
+		self.timers.lock().push(Arc::downgrade(&timer.state));
+	}
+
+	/// Add a stopwatch to be ticked with the clock
+	///
+	/// Like for timers, the clock only keeps a weak handle.
+	pub fn add_stopwatch(&self, stopwatch: &Stopwatch)
+	{
+		self.stopwatches
+			.lock()
+			.push(Arc::downgrade(&stopwatch.state));
+	}
+
+	/// Makes every tick advance the clock by `delta`, instead of the time
+	/// passed since the last tick
+	///
+	/// Used for deterministic runs, such as replays and automated tests.
+	/// `None` goes back to measuring time.
+	pub fn set_fixed_delta(&self, delta: Option<Duration>)
+	{
+		self.clock.lock().fixed_delta = delta;
+	}
+
+	/// The delta used by every tick, if set
+	pub fn fixed_delta(&self) -> Option<Duration> { self.clock.lock().fixed_delta }
+
+	/// The time of the last tick in the frame clock
+	///
+	/// This is the startup time plus [`elapsed`](Time::elapsed), which is not
+	/// the wall clock time if the clock has been ticked with given deltas.
+	pub fn now(&self) -> Instant
+	{
+		let clock = self.clock.lock();
+		clock.startup + clock.elapsed
+	}
+
+	/// Advances the clock, and ticks all added timers and stopwatches
+	///
+	/// The clock advances by `delta` if given, otherwise by the fixed delta
+	/// or the time since the last tick. Returns the new frame number.
+	pub(crate) fn tick(&self, delta: Option<Duration>) -> u64
+	{
+		let now = Instant::now();
+		let (delta, frame) = {
+			let mut clock = self.clock.lock();
+			let measured = match clock.last_tick {
+				Some(last) => now.saturating_duration_since(last),
+				None => Duration::ZERO,
+			};
+			let delta = delta.or(clock.fixed_delta).unwrap_or(measured);
+			clock.last_tick = Some(now);
+			clock.delta = delta;
+			clock.elapsed += delta;
+			clock.frame += 1;
+			(delta, clock.frame)
+		};
+
+		self.timers.lock().retain(|timer| match timer.upgrade() {
+			Some(state) => {
+				state.lock().tick(delta);
+				true
+			}
+			None => false,
+		});
+		self.stopwatches
+			.lock()
+			.retain(|stopwatch| match stopwatch.upgrade() {
+				Some(state) => {
+					state.lock().tick(delta);
+					true
+				}
+				None => false,
+			});
+		frame
+	}
+}
+
+/// Whether a [`Timer`] stops or starts over when finished
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimerMode
+{
+	Once,
+	Repeating,
+}
+
+/// Counts down a duration
+///
+/// The timer is a handle, clones share the same state. It is ticked either
+/// manually with [`tick`](Timer::tick) or by the frame clock after
+/// [`Time::add_timer`].
+#[derive(Clone)]
+pub struct Timer
+{
+	state: Arc<Mutex<TimerState>>,
+}
+
+struct TimerState
+{
+	duration: Duration,
+	elapsed: Duration,
+	mode: TimerMode,
+	paused: bool,
+	finished: bool,
+	times_finished_this_tick: u32,
+}
+
+impl TimerState
+{
+	fn tick(&mut self, delta: Duration)
+	{
+		self.times_finished_this_tick = 0;
+		if self.paused || (self.finished && self.mode == TimerMode::Once) {
+			return;
+		}
+
+		self.elapsed += delta;
+		if self.elapsed < self.duration {
+			self.finished = false;
+			return;
+		}
+
+		self.finished = true;
+		match self.mode {
+			TimerMode::Once => {
+				self.elapsed = self.duration;
+				self.times_finished_this_tick = 1;
+			}
+			TimerMode::Repeating if self.duration.is_zero() => {
+				self.elapsed = Duration::ZERO;
+				self.times_finished_this_tick = 1;
+			}
+			TimerMode::Repeating => {
+				let duration = self.duration.as_nanos();
+				let elapsed = self.elapsed.as_nanos();
+				self.times_finished_this_tick = (elapsed / duration) as u32;
+				self.elapsed = Duration::from_nanos((elapsed % duration) as u64);
+			}
+		}
+	}
+}
+
+impl Timer
+{
+	pub fn new(duration: Duration, mode: TimerMode) -> Self
+	{
+		Timer {
+			state: Arc::new(Mutex::new(TimerState {
+				duration,
+				elapsed: Duration::ZERO,
+				mode,
+				paused: false,
+				finished: false,
+				times_finished_this_tick: 0,
+			})),
+		}
+	}
+
+	/// Advances the timer by `delta`
+	///
+	/// Don't call this for timers added to the frame clock, they are already
+	/// ticked by it.
+	pub fn tick(&self, delta: Duration) { self.state.lock().tick(delta); }
+
+	/// Whether the timer has reached its duration
+	///
+	/// A once-timer stays finished until reset, a repeating timer is only
+	/// finished in the tick it wrapped around.
+	pub fn finished(&self) -> bool { self.state.lock().finished }
+
+	/// Whether the timer finished during the last tick
+	pub fn just_finished(&self) -> bool { self.state.lock().times_finished_this_tick > 0 }
+
+	/// Number of times the timer finished during the last tick
+	///
+	/// Can be larger than one for repeating timers with short durations.
+	pub fn times_finished_this_tick(&self) -> u32 { self.state.lock().times_finished_this_tick }
+
+	pub fn elapsed(&self) -> Duration { self.state.lock().elapsed }
+
+	pub fn remaining(&self) -> Duration
+	{
+		let state = self.state.lock();
+		state.duration.saturating_sub(state.elapsed)
+	}
+
+	pub fn duration(&self) -> Duration { self.state.lock().duration }
+
+	/// Sets a new duration, without resetting the elapsed time
+	pub fn set_duration(&self, duration: Duration) { self.state.lock().duration = duration; }
+
+	pub fn mode(&self) -> TimerMode { self.state.lock().mode }
+
+	pub fn pause(&self) { self.state.lock().paused = true; }
+
+	pub fn unpause(&self) { self.state.lock().paused = false; }
+
+	pub fn paused(&self) -> bool { self.state.lock().paused }
+
+	/// Starts the timer over, does not unpause it
+	pub fn reset(&self)
+	{
+		let mut state = self.state.lock();
+		state.elapsed = Duration::ZERO;
+		state.finished = false;
+		state.times_finished_this_tick = 0;
+	}
+}
+
+/// Measures elapsed time
+///
+/// Like the [`Timer`], the stopwatch is a handle, and is ticked either
+/// manually or by the frame clock after [`Time::add_stopwatch`].
+#[derive(Clone, Default)]
+pub struct Stopwatch
+{
+	state: Arc<Mutex<StopwatchState>>,
+}
+
+#[derive(Default)]
+struct StopwatchState
+{
+	elapsed: Duration,
+	paused: bool,
+}
+
+impl StopwatchState
+{
+	fn tick(&mut self, delta: Duration)
+	{
+		if !self.paused {
+			self.elapsed += delta;
+		}
+	}
+}
+
+impl Stopwatch
+{
+	pub fn new() -> Self { Stopwatch::default() }
+
+	/// Advances the stopwatch by `delta`
+	///
+	/// Don't call this for stopwatches added to the frame clock.
+	pub fn tick(&self, delta: Duration) { self.state.lock().tick(delta); }
+
+	pub fn elapsed(&self) -> Duration { self.state.lock().elapsed }
+
+	pub fn pause(&self) { self.state.lock().paused = true; }
+
+	pub fn unpause(&self) { self.state.lock().paused = false; }
+
+	pub fn paused(&self) -> bool { self.state.lock().paused }
+
+	/// Sets elapsed time to zero, does not unpause it
+	pub fn reset(&self) { self.state.lock().elapsed = Duration::ZERO; }
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+
+	const MS: Duration = Duration::from_millis(1);
+
+	#[test]
+	fn timer_001()
+	{
+		let once = Timer::new(10 * MS, TimerMode::Once);
+		once.tick(4 * MS);
+		assert!(!once.finished());
+		assert_eq!(once.remaining(), 6 * MS);
+		once.tick(20 * MS);
+		assert!(once.finished() && once.just_finished());
+		assert_eq!(once.elapsed(), 10 * MS, "clamped to the duration");
+		once.tick(MS);
+		assert!(once.finished(), "stays finished until reset");
+		assert!(!once.just_finished());
+
+		let repeating = Timer::new(10 * MS, TimerMode::Repeating);
+		repeating.tick(25 * MS);
+		assert!(repeating.finished());
+		assert_eq!(repeating.times_finished_this_tick(), 2);
+		assert_eq!(repeating.elapsed(), 5 * MS);
+		repeating.tick(2 * MS);
+		assert!(!repeating.finished() && !repeating.just_finished());
+		repeating.tick(3 * MS);
+		assert_eq!(repeating.times_finished_this_tick(), 1);
+		assert_eq!(repeating.elapsed(), Duration::ZERO);
+	}
+
+	#[test]
+	fn timer_002()
+	{
+		let timer = Timer::new(10 * MS, TimerMode::Once);
+		timer.pause();
+		timer.tick(20 * MS);
+		assert!(timer.paused() && !timer.finished());
+		assert_eq!(timer.elapsed(), Duration::ZERO);
+		timer.unpause();
+		timer.tick(20 * MS);
+		assert!(timer.finished());
+
+		timer.pause();
+		timer.reset();
+		assert!(timer.paused(), "reset does not unpause");
+		assert!(!timer.finished() && !timer.just_finished());
+		assert_eq!(timer.elapsed(), Duration::ZERO);
+
+		let stopwatch = Stopwatch::new();
+		stopwatch.tick(3 * MS);
+		stopwatch.pause();
+		stopwatch.tick(3 * MS);
+		assert_eq!(stopwatch.elapsed(), 3 * MS);
+		stopwatch.reset();
+		assert!(stopwatch.paused());
+		assert_eq!(stopwatch.elapsed(), Duration::ZERO);
+	}
+
+	#[test]
+	/// test ticking timers and stopwatches by the frame clock
+	fn time_001()
+	{
+		let time = Time::default();
+		let timer = Timer::new(10 * MS, TimerMode::Repeating);
+		let stopwatch = Stopwatch::new();
+		time.add_timer(&timer);
+		time.add_stopwatch(&stopwatch);
+
+		time.set_fixed_delta(Some(4 * MS));
+		assert_eq!(time.tick(None), 1);
+		assert_eq!(time.tick(Some(7 * MS)), 2, "given delta over fixed delta");
+		assert_eq!(time.delta(), 7 * MS);
+		assert_eq!(time.elapsed(), 11 * MS);
+		assert_eq!(time.now(), time.startup() + 11 * MS);
+		assert!(timer.just_finished());
+		assert_eq!(stopwatch.elapsed(), 11 * MS);
+
+		drop(timer);
+		time.tick(None);
+		assert!(time.timers.lock().is_empty(), "dropped timers are removed");
+		assert_eq!(stopwatch.elapsed(), 15 * MS);
+	}
+}
@@ -0,0 +1,280 @@
+use parking_lot::RwLock;
+use std::collections::BTreeMap;
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+pub use toml::Value;
+
+/// Default file loaded by [`Config::load_default`]
+pub const DEFAULT_CONFIG_FILE: &str = "ly.toml";
+
+/// Prefix of environment variables overriding config values
+pub const ENV_PREFIX: &str = "LY_";
+
+/// Engine configuration
+///
+/// Values are stored by their dotted key, so the TOML
+/// ```toml
+/// [window]
+/// title = "My Game"
+/// ```
+/// is read with `config.get::<String>("window.title")`.
+///
+/// The config uses interior mutability, so it can be kept as a resource in
+/// the `World` and changed at runtime.
+#[derive(Default)]
+pub struct Config
+{
+	values: RwLock<BTreeMap<String, Value>>,
+}
+
+/// Conversion from a config [`Value`]
+pub trait FromConfig: Sized
+{
+	fn from_config(value: &Value) -> Option<Self>;
+}
+
+impl FromConfig for bool
+{
+	fn from_config(value: &Value) -> Option<Self> { value.as_bool() }
+}
+
+impl FromConfig for i64
+{
+	fn from_config(value: &Value) -> Option<Self> { value.as_integer() }
+}
+
+impl FromConfig for f64
+{
+	fn from_config(value: &Value) -> Option<Self>
+	{
+		match value {
+			Value::Float(f) => Some(*f),
+			Value::Integer(i) => Some(*i as f64),
+			_ => None,
+		}
+	}
+}
+
+impl FromConfig for String
+{
+	fn from_config(value: &Value) -> Option<Self>
+	{
+		match value {
+			Value::String(s) => Some(s.clone()),
+			_ => None,
+		}
+	}
+}
+
+impl FromConfig for Value
+{
+	fn from_config(value: &Value) -> Option<Self> { Some(value.clone()) }
+}
+
+impl Config
+{
+	/// Parses a config from TOML
+	pub fn from_toml_str(toml: &str) -> Result<Self, Box<dyn Error>>
+	{
+		let table = toml.parse::<Value>()?;
+		let config = Config::default();
+		{
+			let mut values = config.values.write();
+			flatten("", table, &mut values);
+		}
+		Ok(config)
+	}
+
+	/// Loads a config from a TOML file
+	pub fn load<P>(path: P) -> Result<Self, Box<dyn Error>>
+	where
+		P: AsRef<Path>,
+	{
+		let path = path.as_ref();
+		let toml = fs::read_to_string(path)
+			.map_err(|e| format!("could not read config {}: {}", path.display(), e))?;
+		Config::from_toml_str(&toml)
+	}
+
+	/// Loads the config the way the engine does by default
+	///
+	/// In increasing precedence, values come from [`DEFAULT_CONFIG_FILE`] if
+	/// it exists, environment variables, and the command line arguments.
+	/// See [`apply_env`](Config::apply_env) and
+	/// [`apply_args`](Config::apply_args).
+	pub fn load_default() -> Result<Self, Box<dyn Error>>
+	{
+		let config = if Path::new(DEFAULT_CONFIG_FILE).exists() {
+			Config::load(DEFAULT_CONFIG_FILE)?
+		}
+		else {
+			Config::default()
+		};
+		config.apply_env(env::vars());
+		config.apply_args(env::args().skip(1))?;
+		Ok(config)
+	}
+
+	/// Overrides values from environment variables
+	///
+	/// Variables starting with [`ENV_PREFIX`] are used, with double
+	/// underscores separating the key parts. `LY_WINDOW__TITLE` sets
+	/// `window.title`.
+	pub fn apply_env<I>(&self, vars: I)
+	where
+		I: IntoIterator<Item = (String, String)>,
+	{
+		let mut values = self.values.write();
+		for (name, value) in vars {
+			if let Some(key) = name.strip_prefix(ENV_PREFIX) {
+				let key = key.to_lowercase().replace("__", ".");
+				values.insert(key, parse_override(&value));
+			}
+		}
+	}
+
+	/// Overrides values from command line arguments
+	///
+	/// Values are set with `--set key=value`, or the shorter `-s key=value`.
+	/// Returns the arguments that were not used, in order.
+	pub fn apply_args<I>(&self, args: I) -> Result<Vec<String>, Box<dyn Error>>
+	where
+		I: IntoIterator<Item = String>,
+	{
+		let mut unused = Vec::new();
+		let mut args = args.into_iter();
+		while let Some(arg) = args.next() {
+			if arg != "--set" && arg != "-s" {
+				unused.push(arg);
+				continue;
+			}
+
+			let assignment = args
+				.next()
+				.ok_or_else(|| format!("{} expects key=value", arg))?;
+			let (key, value) = assignment
+				.split_once('=')
+				.ok_or_else(|| format!("{} expects key=value, got {}", arg, assignment))?;
+			self.values
+				.write()
+				.insert(key.trim().to_string(), parse_override(value.trim()));
+		}
+		Ok(unused)
+	}
+
+	/// Gets the value of `key`, if it exists and has the type `T`
+	pub fn get<T>(&self, key: &str) -> Option<T>
+	where
+		T: FromConfig,
+	{
+		self.values.read().get(key).and_then(T::from_config)
+	}
+
+	/// Gets the value of `key`, or `default` if it is missing
+	pub fn get_or<T>(&self, key: &str, default: T) -> T
+	where
+		T: FromConfig,
+	{
+		self.get(key).unwrap_or(default)
+	}
+
+	/// Sets the value of `key`
+	pub fn set<V>(&self, key: &str, value: V)
+	where
+		V: Into<Value>,
+	{
+		self.values.write().insert(key.to_string(), value.into());
+	}
+
+	/// Checks if `key` has a value
+	pub fn contains(&self, key: &str) -> bool { self.values.read().contains_key(key) }
+
+	/// Gets all keys, sorted
+	pub fn keys(&self) -> Vec<String> { self.values.read().keys().cloned().collect() }
+}
+
+fn flatten(prefix: &str, value: Value, values: &mut BTreeMap<String, Value>)
+{
+	match value {
+		Value::Table(table) => {
+			for (key, value) in table {
+				let key = match prefix {
+					"" => key,
+					_ => format!("{}.{}", prefix, key),
+				};
+				flatten(&key, value, values);
+			}
+		}
+		value => {
+			values.insert(prefix.to_string(), value);
+		}
+	}
+}
+
+/// Overrides are typed like TOML values when possible, `true` is a boolean
+/// and `3` an integer, otherwise they are strings
+fn parse_override(value: &str) -> Value
+{
+	match format!("v = {}", value).parse::<Value>() {
+		Ok(Value::Table(mut table)) => table
+			.remove("v")
+			.unwrap_or_else(|| Value::String(value.to_string())),
+		_ => Value::String(value.to_string()),
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+
+	#[test]
+	fn config_001()
+	{
+		let config = Config::from_toml_str(
+			r#"
+			[window]
+			title = "From file"
+			width = 800
+
+			[log]
+			level = "info"
+			"#,
+		)
+		.unwrap();
+		assert_eq!(config.get::<String>("window.title").unwrap(), "From file");
+		assert_eq!(config.get::<i64>("window.width"), Some(800));
+		assert_eq!(config.get::<bool>("window.width"), None, "wrong type");
+
+		config.apply_env(vec![
+			("LY_WINDOW__WIDTH".to_string(), "1024".to_string()),
+			("HOME".to_string(), "/root".to_string()),
+		]);
+		assert_eq!(config.get::<i64>("window.width"), Some(1024));
+		assert!(!config.contains("home"));
+
+		let unused = config
+			.apply_args(
+				[
+					"game",
+					"--set",
+					"window.title=From args",
+					"-s",
+					"log.level=debug",
+				]
+				.iter()
+				.map(|s| s.to_string()),
+			)
+			.unwrap();
+		assert_eq!(unused, ["game"]);
+		assert_eq!(config.get::<String>("window.title").unwrap(), "From args");
+		assert_eq!(config.get::<String>("log.level").unwrap(), "debug");
+
+		assert!(config
+			.apply_args(["--set".to_string(), "novalue".to_string()])
+			.is_err());
+	}
+}
@@ -0,0 +1,407 @@
+use ly_events::channel::{OwnedSyncEventWriter, SyncEventChannel};
+use ly_events::registry::Events;
+use parking_lot::RwLock;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use crate::Config;
+
+/// The value of a console variable
+#[derive(Debug, Clone, PartialEq)]
+pub enum CVarValue
+{
+	Bool(bool),
+	Int(i64),
+	Float(f64),
+	Str(String),
+}
+
+impl CVarValue
+{
+	/// Name of the type of the value
+	pub fn type_name(&self) -> &'static str
+	{
+		match self {
+			CVarValue::Bool(_) => "bool",
+			CVarValue::Int(_) => "int",
+			CVarValue::Float(_) => "float",
+			CVarValue::Str(_) => "string",
+		}
+	}
+
+	/// Parses `s` as a value of the same type as `self`
+	pub fn parse_same(&self, s: &str) -> Result<CVarValue, Box<dyn Error>>
+	{
+		let value = match self {
+			CVarValue::Bool(_) => CVarValue::Bool(s.parse()?),
+			CVarValue::Int(_) => CVarValue::Int(s.parse()?),
+			CVarValue::Float(_) => CVarValue::Float(s.parse()?),
+			CVarValue::Str(_) => CVarValue::Str(s.to_string()),
+		};
+		Ok(value)
+	}
+
+	fn coerce_config(&self, value: &toml::Value) -> Option<CVarValue>
+	{
+		match (self, value) {
+			(CVarValue::Bool(_), toml::Value::Boolean(b)) => Some(CVarValue::Bool(*b)),
+			(CVarValue::Int(_), toml::Value::Integer(i)) => Some(CVarValue::Int(*i)),
+			(CVarValue::Float(_), toml::Value::Float(f)) => Some(CVarValue::Float(*f)),
+			(CVarValue::Float(_), toml::Value::Integer(i)) => Some(CVarValue::Float(*i as f64)),
+			(CVarValue::Str(_), toml::Value::String(s)) => Some(CVarValue::Str(s.clone())),
+			_ => None,
+		}
+	}
+}
+
+impl fmt::Display for CVarValue
+{
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+	{
+		match self {
+			CVarValue::Bool(b) => write!(f, "{}", b),
+			CVarValue::Int(i) => write!(f, "{}", i),
+			CVarValue::Float(v) => write!(f, "{}", v),
+			CVarValue::Str(s) => write!(f, "{}", s),
+		}
+	}
+}
+
+/// Types that can be stored in a console variable
+pub trait CVarType: Sized + Send + Sync + 'static
+{
+	fn into_value(self) -> CVarValue;
+	fn from_value(value: &CVarValue) -> Option<Self>;
+}
+
+impl CVarType for bool
+{
+	fn into_value(self) -> CVarValue { CVarValue::Bool(self) }
+
+	fn from_value(value: &CVarValue) -> Option<Self>
+	{
+		match value {
+			CVarValue::Bool(b) => Some(*b),
+			_ => None,
+		}
+	}
+}
+
+impl CVarType for i64
+{
+	fn into_value(self) -> CVarValue { CVarValue::Int(self) }
+
+	fn from_value(value: &CVarValue) -> Option<Self>
+	{
+		match value {
+			CVarValue::Int(i) => Some(*i),
+			_ => None,
+		}
+	}
+}
+
+impl CVarType for f64
+{
+	fn into_value(self) -> CVarValue { CVarValue::Float(self) }
+
+	fn from_value(value: &CVarValue) -> Option<Self>
+	{
+		match value {
+			CVarValue::Float(f) => Some(*f),
+			_ => None,
+		}
+	}
+}
+
+impl CVarType for String
+{
+	fn into_value(self) -> CVarValue { CVarValue::Str(self) }
+
+	fn from_value(value: &CVarValue) -> Option<Self>
+	{
+		match value {
+			CVarValue::Str(s) => Some(s.clone()),
+			_ => None,
+		}
+	}
+}
+
+/// Event sent by the [`CVarRegistry`] when a variable is changed
+#[derive(Debug, Clone)]
+pub struct CVarChanged
+{
+	pub name: String,
+	pub old: CVarValue,
+	pub new: CVarValue,
+}
+
+/// Description of a registered variable
+#[derive(Debug, Clone)]
+pub struct CVarInfo
+{
+	pub name: String,
+	pub doc: String,
+	pub default: CVarValue,
+	pub value: CVarValue,
+}
+
+struct Entry
+{
+	doc: String,
+	default: CVarValue,
+	value: RwLock<CVarValue>,
+}
+
+/// Typed handle to a registered variable
+///
+/// Reads the value without going through the registry, set it with
+/// [`CVarRegistry::set`] so that watchers are notified.
+pub struct CVar<T>
+{
+	name: String,
+	entry: Arc<Entry>,
+	_type: PhantomData<fn() -> T>,
+}
+
+impl<T> CVar<T>
+where
+	T: CVarType,
+{
+	/// Gets the current value
+	pub fn get(&self) -> T
+	{
+		T::from_value(&self.entry.value.read()).expect("the type of a cvar never changes")
+	}
+
+	pub fn name(&self) -> &str { &self.name }
+
+	pub fn doc(&self) -> &str { &self.entry.doc }
+}
+
+/// Registry of console variables
+///
+/// Any crate can register typed and documented variables, which can then be
+/// read and set by name at runtime. Changes are sent as [`CVarChanged`]
+/// events on the channel given by [`changes`](CVarRegistry::changes), which
+/// is registered in [`Events`] when created by
+/// [`from_config_in`](CVarRegistry::from_config_in).
+///
+/// ### Example
+/// ```
+/// # use ly_config::{Config, CVarRegistry};
+/// let config = Config::from_toml_str("[player]\nspeed = 2.5").unwrap();
+/// let registry = CVarRegistry::from_config(&config);
+/// let reader = registry.changes().get_reader().unwrap();
+///
+/// let speed = registry.register("player.speed", 1.0, "Walk speed").unwrap();
+/// assert_eq!(speed.get(), 2.5, "config overrides the default");
+///
+/// registry.set_str("player.speed", "4").unwrap();
+/// assert_eq!(speed.get(), 4.0);
+///
+/// reader.flush_channel();
+/// assert_eq!(reader.read().count(), 1);
+/// ```
+pub struct CVarRegistry
+{
+	vars: RwLock<BTreeMap<String, Arc<Entry>>>,
+	overrides: BTreeMap<String, toml::Value>,
+	changes: Arc<SyncEventChannel<CVarChanged>>,
+	writer: OwnedSyncEventWriter<CVarChanged>,
+}
+
+impl Default for CVarRegistry
+{
+	fn default() -> Self { CVarRegistry::new(BTreeMap::new(), Arc::default()) }
+}
+
+impl CVarRegistry
+{
+	/// Creates a registry where values in `config` override the defaults
+	/// of registered variables
+	pub fn from_config(config: &Config) -> Self
+	{
+		CVarRegistry::new(overrides(config), Arc::default())
+	}
+
+	/// Like [`from_config`](CVarRegistry::from_config), sending the changes to
+	/// the channel of [`CVarChanged`] in `events`
+	///
+	/// The channel is then flushed with the other channels in `events`.
+	pub fn from_config_in(config: &Config, events: &Events) -> Self
+	{
+		CVarRegistry::new(overrides(config), events.add::<CVarChanged>())
+	}
+
+	fn new(
+		overrides: BTreeMap<String, toml::Value>,
+		changes: Arc<SyncEventChannel<CVarChanged>>,
+	) -> Self
+	{
+		CVarRegistry {
+			vars: RwLock::default(),
+			overrides,
+			writer: changes.get_owned_writer(),
+			changes,
+		}
+	}
+
+	/// Registers a variable
+	///
+	/// Errors if a variable of that name already exists.
+	pub fn register<T>(&self, name: &str, default: T, doc: &str) -> Result<CVar<T>, Box<dyn Error>>
+	where
+		T: CVarType,
+	{
+		let mut vars = self.vars.write();
+		if vars.contains_key(name) {
+			return Err(format!("cvar {} already registered", name).into());
+		}
+
+		let default = default.into_value();
+		let value = match self.overrides.get(name) {
+			Some(config_value) => match default.coerce_config(config_value) {
+				Some(value) => value,
+				None => {
+					return Err(format!(
+						"config value of {} is not a {}",
+						name,
+						default.type_name()
+					)
+					.into())
+				}
+			},
+			None => default.clone(),
+		};
+
+		let entry = Arc::new(Entry {
+			doc: doc.to_string(),
+			default,
+			value: RwLock::new(value),
+		});
+		vars.insert(name.to_string(), Arc::clone(&entry));
+		Ok(CVar {
+			name: name.to_string(),
+			entry,
+			_type: PhantomData,
+		})
+	}
+
+	/// Gets the value of a variable, if it exists and has the type `T`
+	pub fn get<T>(&self, name: &str) -> Option<T>
+	where
+		T: CVarType,
+	{
+		self.get_value(name).and_then(|v| T::from_value(&v))
+	}
+
+	/// Gets the untyped value of a variable
+	pub fn get_value(&self, name: &str) -> Option<CVarValue>
+	{
+		self.vars
+			.read()
+			.get(name)
+			.map(|entry| entry.value.read().clone())
+	}
+
+	/// Sets a variable
+	///
+	/// Errors if it does not exist or has another type.
+	pub fn set<T>(&self, name: &str, value: T) -> Result<(), Box<dyn Error>>
+	where
+		T: CVarType,
+	{
+		self.set_value(name, value.into_value())
+	}
+
+	/// Sets a variable from a string, parsed as the type of the variable
+	pub fn set_str(&self, name: &str, value: &str) -> Result<(), Box<dyn Error>>
+	{
+		let current = self
+			.get_value(name)
+			.ok_or_else(|| format!("no cvar named {}", name))?;
+		let value = current
+			.parse_same(value)
+			.map_err(|e| format!("{} expects a {}: {}", name, current.type_name(), e))?;
+		self.set_value(name, value)
+	}
+
+	/// Sets a variable to its default value
+	pub fn reset(&self, name: &str) -> Result<(), Box<dyn Error>>
+	{
+		let default = self
+			.vars
+			.read()
+			.get(name)
+			.map(|entry| entry.default.clone())
+			.ok_or_else(|| format!("no cvar named {}", name))?;
+		self.set_value(name, default)
+	}
+
+	/// Describes all registered variables, sorted by name
+	pub fn list(&self) -> Vec<CVarInfo>
+	{
+		self.vars
+			.read()
+			.iter()
+			.map(|(name, entry)| CVarInfo {
+				name: name.clone(),
+				doc: entry.doc.clone(),
+				default: entry.default.clone(),
+				value: entry.value.read().clone(),
+			})
+			.collect()
+	}
+
+	/// The channel [`CVarChanged`] events are sent to
+	///
+	/// The registry never flushes the channel. That is up to the readers,
+	/// unless the channel is registered in [`Events`].
+	pub fn changes(&self) -> &Arc<SyncEventChannel<CVarChanged>> { &self.changes }
+
+	fn set_value(&self, name: &str, value: CVarValue) -> Result<(), Box<dyn Error>>
+	{
+		let entry = self
+			.vars
+			.read()
+			.get(name)
+			.cloned()
+			.ok_or_else(|| format!("no cvar named {}", name))?;
+
+		let old = {
+			let mut current = entry.value.write();
+			if current.type_name() != value.type_name() {
+				return Err(format!(
+					"{} is a {}, not a {}",
+					name,
+					current.type_name(),
+					value.type_name()
+				)
+				.into());
+			}
+			std::mem::replace(&mut *current, value.clone())
+		};
+
+		if old != value {
+			// the change is only given back if the channel is closed
+			let _ = self.writer.send(CVarChanged {
+				name: name.to_string(),
+				old,
+				new: value,
+			});
+		}
+		Ok(())
+	}
+}
+
+fn overrides(config: &Config) -> BTreeMap<String, toml::Value>
+{
+	config
+		.keys()
+		.into_iter()
+		.filter_map(|key| config.get::<toml::Value>(&key).map(|v| (key, v)))
+		.collect()
+}
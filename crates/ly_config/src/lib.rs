@@ -0,0 +1,63 @@
+//! Configuration of the LY engine
+//!
+//! The crate provides the [`Config`], loaded from a TOML file with overrides
+//! from environment variables and command line arguments, and the
+//! [`CVarRegistry`] of typed variables that can be changed at runtime.
+//!
+//! Both are intended to be resources in the `World`, which is what the
+//! [`ConfigPlugin`] sets up.
+
+mod config;
+mod cvar;
+
+pub use config::*;
+pub use cvar::*;
+
+use ly_app::{App, Plugin};
+use ly_events::registry::Events;
+use ly_log::core_prelude::*;
+use ly_log::LogLevel;
+
+/// Loads the default [`Config`] and creates the [`CVarRegistry`] from it
+///
+/// See [`Config::load_default`] for where the values are loaded from. The
+/// channel of [`CVarChanged`] events is registered in the `Events` resource,
+/// to be flushed every update.
+/// The log level is set from `log.level` if present.
+#[derive(Default)]
+pub struct ConfigPlugin;
+
+impl Plugin for ConfigPlugin
+{
+	fn build(&self, app: &mut App)
+	{
+		let config = match Config::load_default() {
+			Ok(config) => config,
+			Err(e) => {
+				core_error!("Could not load config, using defaults: {}", e);
+				Config::default()
+			}
+		};
+
+		if let Some(level) = config.get::<String>("log.level") {
+			match level.parse::<LogLevel>() {
+				Ok(level) => set_log_level(level),
+				Err(e) => core_warning!("Invalid log.level in config: {}", e),
+			}
+		}
+
+		let registry = match app.world.get_resource::<Events>() {
+			Ok(events) => CVarRegistry::from_config_in(&config, events),
+			Err(e) => {
+				core_error!("Could not register CVarChanged events: {}", e);
+				CVarRegistry::from_config(&config)
+			}
+		};
+		if let Err(e) = app.world.set_resource(config) {
+			core_error!("Could not set Config resource: {}", e);
+		}
+		if let Err(e) = app.world.set_resource(registry) {
+			core_error!("Could not set CVarRegistry resource: {}", e);
+		}
+	}
+}
@@ -0,0 +1,172 @@
+use ly_app::World;
+use std::any::type_name;
+use std::str::FromStr;
+
+use crate::Console;
+
+/// What a command gets to work with when run
+pub struct CommandContext<'a>
+{
+	pub world: &'a World,
+	pub console: &'a Console,
+}
+
+/// Result of running a command, the output or an error message
+pub type CommandResult = Result<String, String>;
+
+type CommandFn = dyn Fn(&CommandContext, &CommandArgs) -> CommandResult + Send + Sync;
+
+/// A command that can be run by the [`Console`]
+pub struct Command
+{
+	pub(crate) name: String,
+	pub(crate) usage: String,
+	pub(crate) help: String,
+	pub(crate) run: Box<CommandFn>,
+}
+
+impl Command
+{
+	/// Creates a command
+	///
+	/// `usage` describes the arguments, e.g. `<name> [value]`, and `help`
+	/// what the command does.
+	pub fn new<F>(name: &str, usage: &str, help: &str, run: F) -> Self
+	where
+		F: Fn(&CommandContext, &CommandArgs) -> CommandResult + Send + Sync + 'static,
+	{
+		Command {
+			name: name.to_string(),
+			usage: usage.to_string(),
+			help: help.to_string(),
+			run: Box::new(run),
+		}
+	}
+
+	pub fn name(&self) -> &str { &self.name }
+
+	pub fn usage(&self) -> &str { &self.usage }
+
+	pub fn help(&self) -> &str { &self.help }
+}
+
+/// The arguments given to a command, not including the command name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandArgs
+{
+	args: Vec<String>,
+}
+
+impl CommandArgs
+{
+	pub fn new(args: Vec<String>) -> Self { CommandArgs { args } }
+
+	pub fn len(&self) -> usize { self.args.len() }
+
+	pub fn is_empty(&self) -> bool { self.args.is_empty() }
+
+	/// Gets argument `index` as a string
+	pub fn str(&self, index: usize) -> Result<&str, String>
+	{
+		self.args
+			.get(index)
+			.map(|s| s.as_str())
+			.ok_or_else(|| format!("missing argument {}", index + 1))
+	}
+
+	/// Parses argument `index` as `T`
+	pub fn get<T>(&self, index: usize) -> Result<T, String>
+	where
+		T: FromStr,
+	{
+		let arg = self.str(index)?;
+		arg.parse().map_err(|_| {
+			format!(
+				"argument {} ({}) is not a {}",
+				index + 1,
+				arg,
+				type_name::<T>()
+			)
+		})
+	}
+
+	/// Parses argument `index` as `T` if it is present
+	pub fn get_opt<T>(&self, index: usize) -> Result<Option<T>, String>
+	where
+		T: FromStr,
+	{
+		match index < self.args.len() {
+			true => self.get(index).map(Some),
+			false => Ok(None),
+		}
+	}
+
+	/// Joins the arguments from `index` and out with spaces
+	pub fn rest(&self, index: usize) -> String { self.args.get(index..).unwrap_or(&[]).join(" ") }
+}
+
+/// Splits a command line into words
+///
+/// Words are separated by whitespace, unless quoted with `"`. Inside quotes,
+/// `\"` is a quote.
+pub fn tokenize(line: &str) -> Result<Vec<String>, String>
+{
+	let mut words = Vec::new();
+	let mut word = String::new();
+	let mut in_word = false;
+	let mut quoted = false;
+	let mut chars = line.chars();
+
+	while let Some(c) = chars.next() {
+		match c {
+			'"' => {
+				quoted = !quoted;
+				in_word = true;
+			}
+			'\\' if quoted => match chars.next() {
+				Some(escaped) => word.push(escaped),
+				None => return Err("line ends with an escape".to_string()),
+			},
+			c if c.is_whitespace() && !quoted => {
+				if in_word {
+					words.push(std::mem::take(&mut word));
+					in_word = false;
+				}
+			}
+			c => {
+				word.push(c);
+				in_word = true;
+			}
+		}
+	}
+
+	if quoted {
+		return Err("unterminated quote".to_string());
+	}
+	if in_word {
+		words.push(word);
+	}
+	Ok(words)
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+
+	#[test]
+	fn tokenize_001()
+	{
+		assert_eq!(tokenize("set  speed 4 ").unwrap(), ["set", "speed", "4"]);
+		assert_eq!(
+			tokenize(r#"set title "My \"Game\"" """#).unwrap(),
+			["set", "title", "My \"Game\"", ""]
+		);
+		assert!(tokenize("set title \"My").is_err());
+
+		let args = CommandArgs::new(tokenize("title My Game").unwrap());
+		assert_eq!(args.rest(1), "My Game");
+		assert_eq!(args.get_opt::<i64>(3), Ok(None));
+		assert!(args.get::<i64>(1).is_err());
+	}
+}
@@ -0,0 +1,360 @@
+use ly_app::{AppInfo, World};
+use ly_config::CVarRegistry;
+use ly_events::channel::{OwnedSyncEventWriter, SyncEventChannel};
+use ly_events::registry::Events;
+use ly_log::{log_level, set_log_level, LogLevel};
+use parking_lot::{Mutex, RwLock};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use crate::{tokenize, Command, CommandArgs, CommandContext, CommandResult};
+
+/// Maximum number of lines kept in the history
+pub const HISTORY_SIZE: usize = 100;
+
+/// Events sent by the [`Console`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConsoleEvent
+{
+	/// A command line was run
+	Command(String),
+	/// Output of a successful command
+	Output(String),
+	/// Error from a failed command
+	Error(String),
+	/// A log printed by the logger
+	Log(LogLevel, String),
+}
+
+/// A resource that can be read and written as text from the console
+///
+/// Resources in the [`World`] can't be mutated, so this needs interior
+/// mutability.
+pub trait ConsoleResource: Send + Sync + 'static
+{
+	fn console_get(&self) -> String;
+	fn console_set(&self, value: &str) -> Result<(), String>;
+}
+
+macro_rules! impl_console_resource_atomic
+{
+	($($atomic:ty),*) => {$(
+		impl ConsoleResource for $atomic
+		{
+			fn console_get(&self) -> String { self.load(Ordering::Relaxed).to_string() }
+
+			fn console_set(&self, value: &str) -> Result<(), String>
+			{
+				let value = value.parse().map_err(|e| format!("{}", e))?;
+				self.store(value, Ordering::Relaxed);
+				Ok(())
+			}
+		}
+	)*};
+}
+
+impl_console_resource_atomic!(AtomicBool, AtomicI64, AtomicUsize);
+
+struct ResourceAccess
+{
+	get: fn(&World) -> Result<String, String>,
+	set: fn(&World, &str) -> Result<(), String>,
+}
+
+fn get_resource<T>(world: &World) -> Result<String, String>
+where
+	T: ConsoleResource,
+{
+	world
+		.get_resource::<T>()
+		.map(|r| r.console_get())
+		.map_err(|e| e.to_string())
+}
+
+fn set_resource<T>(world: &World, value: &str) -> Result<(), String>
+where
+	T: ConsoleResource,
+{
+	world
+		.get_resource::<T>()
+		.map_err(|e| e.to_string())?
+		.console_set(value)
+}
+
+/// The developer console
+///
+/// Holds the registered commands, the resources reachable by `get`/`set`,
+/// and the history of run lines. Everything that happens is sent as
+/// [`ConsoleEvent`]s on the [`events`](Console::events) channel, which is
+/// what a rendered console would read. The channel is registered in
+/// [`Events`] when created [`with_events`](Console::with_events).
+///
+/// Builtin commands are `help`, `list`, `get`, `set`, `log_level` and `quit`.
+/// `get` and `set` work on registered resources and on the cvars of the
+/// `CVarRegistry`, if there is one in the world.
+pub struct Console
+{
+	commands: RwLock<BTreeMap<String, Arc<Command>>>,
+	resources: RwLock<BTreeMap<String, ResourceAccess>>,
+	history: Mutex<Vec<String>>,
+	events: Arc<SyncEventChannel<ConsoleEvent>>,
+	/// Kept for the life of the console, as sending is done by the log
+	/// listener, which must not log, like creating writers does
+	writer: OwnedSyncEventWriter<ConsoleEvent>,
+}
+
+impl Default for Console
+{
+	fn default() -> Self { Console::new(Arc::default()) }
+}
+
+impl Console
+{
+	/// Creates a console sending its events to the channel of
+	/// [`ConsoleEvent`] in `events`
+	///
+	/// The channel is then flushed with the other channels in `events`.
+	pub fn with_events(events: &Events) -> Self { Console::new(events.add::<ConsoleEvent>()) }
+
+	fn new(events: Arc<SyncEventChannel<ConsoleEvent>>) -> Self
+	{
+		let console = Console {
+			commands: RwLock::new(BTreeMap::new()),
+			resources: RwLock::new(BTreeMap::new()),
+			history: Mutex::new(Vec::new()),
+			writer: events.get_owned_writer(),
+			events,
+		};
+		for command in builtin_commands() {
+			console.register_command(command);
+		}
+		console
+	}
+
+	/// Adds a command, replacing any command with the same name
+	pub fn register_command(&self, command: Command)
+	{
+		self.commands
+			.write()
+			.insert(command.name.clone(), Arc::new(command));
+	}
+
+	/// Makes the resource `T` readable and writable by `get`/`set` as `name`
+	pub fn register_resource<T>(&self, name: &str)
+	where
+		T: ConsoleResource,
+	{
+		self.resources.write().insert(
+			name.to_string(),
+			ResourceAccess {
+				get: get_resource::<T>,
+				set: set_resource::<T>,
+			},
+		);
+	}
+
+	/// Parses and runs a command line
+	///
+	/// The line is added to the history, and the line and result are sent
+	/// as events.
+	pub fn execute(&self, world: &World, line: &str) -> CommandResult
+	{
+		let line = line.trim();
+		if line.is_empty() {
+			return Ok(String::new());
+		}
+		self.push_history(line);
+		self.send(ConsoleEvent::Command(line.to_string()));
+
+		let result = self.run(world, line);
+		match &result {
+			Ok(output) => self.send(ConsoleEvent::Output(output.clone())),
+			Err(error) => self.send(ConsoleEvent::Error(error.clone())),
+		}
+		result
+	}
+
+	/// Completes the last word of `partial`
+	///
+	/// The first word completes to command names, the argument of `get` and
+	/// `set` to resource and cvar names. Returns the sorted candidates.
+	pub fn complete(&self, world: &World, partial: &str) -> Vec<String>
+	{
+		let words = match tokenize(partial) {
+			Ok(words) => words,
+			Err(_) => return Vec::new(),
+		};
+		let new_word = partial.is_empty() || partial.ends_with(char::is_whitespace);
+		let prefix = match new_word {
+			true => "",
+			false => words.last().map(|w| w.as_str()).unwrap_or(""),
+		};
+		let index = match new_word {
+			true => words.len(),
+			false => words.len() - 1,
+		};
+
+		let candidates = match index {
+			0 => self.commands.read().keys().cloned().collect(),
+			1 if words[0] == "get" || words[0] == "set" => self.variable_names(world),
+			_ => Vec::new(),
+		};
+		candidates
+			.into_iter()
+			.filter(|c| c.starts_with(prefix))
+			.collect()
+	}
+
+	/// Gets the previously run lines, oldest first
+	pub fn history(&self) -> Vec<String> { self.history.lock().clone() }
+
+	/// The channel the [`ConsoleEvent`]s are sent to
+	pub fn events(&self) -> &Arc<SyncEventChannel<ConsoleEvent>> { &self.events }
+
+	/// Sends the event, without logging
+	///
+	/// The event is dropped if the channel gives it back.
+	pub(crate) fn send(&self, event: ConsoleEvent) { let _ = self.writer.send(event); }
+
+	fn push_history(&self, line: &str)
+	{
+		let mut history = self.history.lock();
+		if history.last().map(|l| l.as_str()) != Some(line) {
+			history.push(line.to_string());
+		}
+		if history.len() > HISTORY_SIZE {
+			history.remove(0);
+		}
+	}
+
+	fn run(&self, world: &World, line: &str) -> CommandResult
+	{
+		let mut words = tokenize(line)?;
+		let name = words.remove(0);
+		let args = CommandArgs::new(words);
+		// don't hold the lock while running, commands may register commands
+		let command = self
+			.commands
+			.read()
+			.get(&name)
+			.cloned()
+			.ok_or_else(|| format!("unknown command {}, try help", name))?;
+		let context = CommandContext {
+			world,
+			console: self,
+		};
+		(command.run)(&context, &args)
+	}
+
+	fn variable_names(&self, world: &World) -> Vec<String>
+	{
+		let mut names: Vec<String> = self.resources.read().keys().cloned().collect();
+		if let Ok(cvars) = world.get_resource::<CVarRegistry>() {
+			names.extend(cvars.list().into_iter().map(|info| info.name));
+		}
+		names.sort();
+		names
+	}
+
+	fn get_variable(&self, world: &World, name: &str) -> CommandResult
+	{
+		if let Some(access) = self.resources.read().get(name) {
+			return (access.get)(world);
+		}
+		world
+			.get_resource::<CVarRegistry>()
+			.ok()
+			.and_then(|cvars| cvars.get_value(name))
+			.map(|value| value.to_string())
+			.ok_or_else(|| format!("no resource or cvar named {}", name))
+	}
+
+	fn set_variable(&self, world: &World, name: &str, value: &str) -> CommandResult
+	{
+		if let Some(access) = self.resources.read().get(name) {
+			(access.set)(world, value)?;
+			return Ok(format!("{} = {}", name, value));
+		}
+		let cvars = world
+			.get_resource::<CVarRegistry>()
+			.map_err(|_| format!("no resource or cvar named {}", name))?;
+		cvars.set_str(name, value).map_err(|e| e.to_string())?;
+		Ok(format!("{} = {}", name, value))
+	}
+}
+
+fn builtin_commands() -> Vec<Command>
+{
+	vec![
+		Command::new("help", "[command]", "Describes commands", |ctx, args| {
+			let commands = ctx.console.commands.read();
+			match args.get_opt::<String>(0)? {
+				Some(name) => commands
+					.get(&name)
+					.map(|c| format!("{} {}\n\t{}", c.name, c.usage, c.help))
+					.ok_or_else(|| format!("unknown command {}", name)),
+				None => Ok(commands
+					.values()
+					.map(|c| format!("{} {}\n\t{}", c.name, c.usage, c.help))
+					.collect::<Vec<_>>()
+					.join("\n")),
+			}
+		}),
+		Command::new(
+			"list",
+			"[commands|vars]",
+			"Lists commands, or resources and cvars for get/set",
+			|ctx, args| {
+				let what = args.get_opt::<String>(0)?;
+				let mut lines = Vec::new();
+				if what.as_deref() != Some("vars") {
+					lines.extend(ctx.console.commands.read().keys().cloned());
+				}
+				if what.as_deref() != Some("commands") {
+					lines.extend(ctx.console.resources.read().keys().cloned());
+					if let Ok(cvars) = ctx.world.get_resource::<CVarRegistry>() {
+						lines.extend(
+							cvars.list().into_iter().map(|info| {
+								format!("{} = {} - {}", info.name, info.value, info.doc)
+							}),
+						);
+					}
+				}
+				Ok(lines.join("\n"))
+			},
+		),
+		Command::new("get", "<name>", "Prints a resource or cvar", |ctx, args| {
+			ctx.console.get_variable(ctx.world, args.str(0)?)
+		}),
+		Command::new(
+			"set",
+			"<name> <value>",
+			"Sets a resource or cvar",
+			|ctx, args| {
+				args.str(1)?;
+				ctx.console
+					.set_variable(ctx.world, args.str(0)?, &args.rest(1))
+			},
+		),
+		Command::new(
+			"log_level",
+			"[error|warning|info|debug|trace]",
+			"Prints or sets the log level",
+			|_, args| match args.get_opt::<LogLevel>(0)? {
+				Some(level) => {
+					set_log_level(level);
+					Ok(format!("log level {}", level))
+				}
+				None => Ok(format!("log level {}", log_level())),
+			},
+		),
+		Command::new("quit", "", "Stops the application", |ctx, _| {
+			ctx.world
+				.get_resource::<AppInfo>()
+				.map_err(|e| e.to_string())?
+				.request_exit();
+			Ok("quitting".to_string())
+		}),
+	]
+}
@@ -0,0 +1,125 @@
+//! Developer console of the LY engine
+//!
+//! The [`Console`] runs text commands from a registry, with typed argument
+//! parsing through [`CommandArgs`], history and autocomplete. It is meant to
+//! tune the game while it runs, e.g. `set player.speed 4`.
+//!
+//! The console does not draw anything, everything it does is sent as
+//! [`ConsoleEvent`]s, including the output of the logger. Without a renderer,
+//! the [`stdin_process`] reads commands from the terminal.
+//!
+//! ### Example
+//! ```
+//! # use ly_app::World;
+//! # use ly_console::{Command, Console};
+//! let world = World::new();
+//! let console = Console::default();
+//! console.register_command(Command::new("add", "<a> <b>", "Adds numbers", |_, args| {
+//!     Ok((args.get::<i64>(0)? + args.get::<i64>(1)?).to_string())
+//! }));
+//!
+//! assert_eq!(console.execute(&world, "add 2 3"), Ok("5".to_string()));
+//! assert!(console.execute(&world, "add 2 three").is_err());
+//! assert_eq!(console.complete(&world, "ad"), ["add"]);
+//! assert_eq!(console.history(), ["add 2 3", "add 2 three"]);
+//! ```
+
+mod command;
+mod console;
+
+pub use command::*;
+pub use console::*;
+
+use crossbeam::channel;
+use ly_app::{App, AppInfo, AppState, Plugin, World};
+use ly_events::registry::Events;
+use ly_log::core_prelude::*;
+use std::io::{self, BufRead};
+use std::thread;
+use std::time::Duration;
+
+/// Adds the [`Console`] resource and forwards logs to it
+///
+/// The channel of [`ConsoleEvent`]s is registered in the `Events` resource,
+/// to be flushed every update. With `stdin` set, the [`stdin_process`] is
+/// added as well.
+#[derive(Default)]
+pub struct ConsolePlugin
+{
+	pub stdin: bool,
+}
+
+impl Plugin for ConsolePlugin
+{
+	fn build(&self, app: &mut App)
+	{
+		let console = match app.world.get_resource::<Events>() {
+			Ok(events) => Console::with_events(events),
+			Err(e) => {
+				core_error!("Could not register ConsoleEvent events: {}", e);
+				Console::default()
+			}
+		};
+		if let Err(e) = app.world.set_resource(console) {
+			core_error!("Could not create Console resource: {}", e);
+			return;
+		}
+
+		let console = app.world.get_resource::<Console>().unwrap();
+		add_log_listener(move |record| {
+			console.send(ConsoleEvent::Log(record.level, record.message.to_string()));
+		});
+
+		if self.stdin {
+			app.add_process(stdin_process);
+		}
+	}
+}
+
+/// Runs console commands read from stdin, printing the results
+///
+/// Intended as an app process, see [`App::add_process`]. Stops when the app
+/// stops or stdin is closed.
+pub fn stdin_process(world: &World)
+{
+	let console = match world.get_resource::<Console>() {
+		Ok(console) => console,
+		Err(e) => {
+			core_error!("stdin console has no console: {}", e);
+			return;
+		}
+	};
+
+	// reading stdin blocks, so it's done in a detached thread to not keep
+	// the app from stopping
+	let (tx, rx) = channel::unbounded();
+	thread::spawn(move || {
+		for line in io::stdin().lock().lines() {
+			let sent = match line {
+				Ok(line) => tx.send(line).is_ok(),
+				Err(_) => false,
+			};
+			if !sent {
+				break;
+			}
+		}
+	});
+
+	loop {
+		match rx.recv_timeout(Duration::from_millis(100)) {
+			Ok(line) => match console.execute(world, &line) {
+				Ok(output) if output.is_empty() => (),
+				Ok(output) => println!("{}", output),
+				Err(error) => println!("error: {}", error),
+			},
+			Err(channel::RecvTimeoutError::Timeout) => (),
+			Err(channel::RecvTimeoutError::Disconnected) => break,
+		}
+
+		if let Ok(info) = world.get_resource::<AppInfo>() {
+			if let AppState::Stopped = info.state() {
+				break;
+			}
+		}
+	}
+}
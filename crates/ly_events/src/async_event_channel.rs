@@ -0,0 +1,278 @@
+use futures_core::Stream;
+use std::collections::VecDeque;
+use std::future::{self, Future};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use crate::event_close::Disconnected;
+use crate::owned_event_channel::OwnedSyncEventReader;
+use crate::sync_event_channel::{
+	Cursor, ReadCursor, SyncEventChannel, SyncEventIterator, SyncEventReader,
+};
+
+/// Future waiting for un-flushed events
+///
+/// Created by [`SyncEventReader::wait_new_async`]. Resolves to the number of
+/// active writers, or [`Disconnected`], like [`SyncEventReader::wait_new`].
+///
+/// Sends do not synchronize with the waiters, so an event sent while the
+/// future is first polled may only wake it on the next send.
+pub struct WaitNew<'a, T>
+{
+	channel: &'a SyncEventChannel<T>,
+}
+
+/// Future waiting for the channel to be flushed
+///
+/// Created by [`SyncEventReader::wait_flushed_async`]. Resolves to
+/// [`Disconnected`] like [`SyncEventReader::wait_flushed`].
+pub struct WaitFlushed<'a, T>
+{
+	channel: &'a SyncEventChannel<T>,
+	read_generation: u64,
+}
+
+/// Stream of the events read by a [`SyncEventReader`]
+///
+/// Created by [`SyncEventReader::stream`].
+pub struct EventStream<'a, T>
+{
+	channel: &'a SyncEventChannel<T>,
+	cursor: &'a Cursor,
+	pending: VecDeque<T>,
+}
+
+/// Stream of the events read by an [`OwnedSyncEventReader`]
+///
+/// Created by [`OwnedSyncEventReader::into_stream`].
+pub struct OwnedEventStream<T>
+{
+	reader: OwnedSyncEventReader<T>,
+	pending: VecDeque<T>,
+}
+
+impl<'a, T> Future for WaitNew<'a, T>
+{
+	type Output = Result<usize, Disconnected>;
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output>
+	{
+		let channel = self.channel;
+		if channel.add_waker_new(cx.waker()).is_err() {
+			return Poll::Ready(Ok(channel.get_num_writers()));
+		}
+		// closing and a dropped last writer wake the waiters after they are
+		// seen
+		if channel.is_disconnected() {
+			return Poll::Ready(Err(Disconnected));
+		}
+		Poll::Pending
+	}
+}
+
+impl<'a, T> Future for WaitFlushed<'a, T>
+{
+	type Output = Result<(), Disconnected>;
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output>
+	{
+		let channel = self.channel;
+		// closing and a dropped last writer wake the waiters after they are
+		// seen
+		if channel
+			.add_waker_flushed(cx.waker(), self.read_generation)
+			.is_err() || channel.is_disconnected()
+		{
+			return Poll::Ready(channel.flushed_status(self.read_generation));
+		}
+		Poll::Pending
+	}
+}
+
+impl<T> SyncEventChannel<T>
+{
+	/// Polls for the next non-empty batch of events unread by `cursor`
+	///
+	/// Only reads flushed events, flushing is left to the owner of the
+	/// channel, as it drops the batch other readers may not have read yet.
+	/// Gives `None` once everything is read, including the un-flushed events,
+	/// and the channel is disconnected.
+	pub(crate) fn poll_batch(
+		&self,
+		cursor: &Cursor,
+		cx: &Context<'_>,
+	) -> Poll<Option<SyncEventIterator<'_, T>>>
+	{
+		loop {
+			if self.has_unread(cursor) {
+				let events = self.read_from(cursor);
+				if events.len() != 0 {
+					return Poll::Ready(Some(events));
+				}
+				continue;
+			}
+			if self.is_disconnected() {
+				return Poll::Ready(None);
+			}
+
+			// closing and a dropped last writer wake the flush waiters
+			if self.add_waker_batch(cx.waker(), cursor.get()).is_ok() {
+				return Poll::Pending;
+			}
+		}
+	}
+}
+
+/// Takes the next event of `pending`, reading a new batch when it is empty
+fn poll_next_event<T>(
+	channel: &SyncEventChannel<T>,
+	cursor: &Cursor,
+	pending: &mut VecDeque<T>,
+	cx: &Context<'_>,
+) -> Poll<Option<T>>
+where
+	T: Clone,
+{
+	if pending.is_empty() {
+		match channel.poll_batch(cursor, cx) {
+			Poll::Ready(Some(events)) => pending.extend(events.cloned()),
+			Poll::Ready(None) => return Poll::Ready(None),
+			Poll::Pending => return Poll::Pending,
+		}
+	}
+	Poll::Ready(pending.pop_front())
+}
+
+// the pending events are never pinned
+impl<'a, T> Unpin for EventStream<'a, T> {}
+impl<T> Unpin for OwnedEventStream<T> {}
+
+impl<'a, T> Stream for EventStream<'a, T>
+where
+	T: Clone,
+{
+	type Item = T;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>>
+	{
+		let this = self.get_mut();
+		poll_next_event(this.channel, this.cursor, &mut this.pending, cx)
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) { (self.pending.len(), None) }
+}
+
+impl<T> Stream for OwnedEventStream<T>
+where
+	T: Clone,
+{
+	type Item = T;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>>
+	{
+		let this = self.get_mut();
+		let reader = &this.reader;
+		poll_next_event(&reader.channel, &reader.cursor, &mut this.pending, cx)
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) { (self.pending.len(), None) }
+}
+
+impl<'a, T> SyncEventReader<'a, T>
+{
+	/// Waits for the next batch of unread events
+	///
+	/// Gives the unread flushed events, if there are any. Otherwise waits for
+	/// the channel to be flushed, the reader does not flush it. Gives `None`
+	/// once all events are read and the channel is closed, or the last writer
+	/// is dropped.
+	///
+	/// The batch holds a read lock on the channel, like
+	/// [`read`](SyncEventReader::read), so drop it before awaiting something
+	/// else.
+	pub async fn next_batch(&self) -> Option<impl Iterator<Item = &T>>
+	{
+		future::poll_fn(|cx| self.channel.poll_batch(&self.cursor, cx)).await
+	}
+
+	/// Waits for un-flushed events to be present, returns number of active
+	/// writers
+	///
+	/// Async version of [`wait_new`](SyncEventReader::wait_new), resolving
+	/// directly if the channel is disconnected.
+	pub fn wait_new_async(&self) -> WaitNew<'a, T>
+	{
+		WaitNew {
+			channel: self.channel,
+		}
+	}
+
+	/// Waits for the channel to be flushed
+	///
+	/// Async version of [`wait_flushed`](SyncEventReader::wait_flushed),
+	/// waiting for a flush after what the reader has read when called.
+	/// Resolves directly if the channel is disconnected, a channel that never
+	/// had a writer waits for its first one.
+	pub fn wait_flushed_async(&self) -> WaitFlushed<'a, T>
+	{
+		WaitFlushed {
+			channel: self.channel,
+			read_generation: self.cursor.get(),
+		}
+	}
+
+	/// Gets a [`Stream`] of cloned events, read in batches like
+	/// [`next_batch`](SyncEventReader::next_batch)
+	pub fn stream(&self) -> EventStream<'_, T>
+	{
+		EventStream {
+			channel: self.channel,
+			cursor: &self.cursor,
+			pending: VecDeque::new(),
+		}
+	}
+}
+
+impl<T> OwnedSyncEventReader<T>
+{
+	/// Waits for the next batch of unread events
+	///
+	/// See [`SyncEventReader::next_batch`].
+	pub async fn next_batch(&self) -> Option<impl Iterator<Item = &T>>
+	{
+		future::poll_fn(|cx| self.channel.poll_batch(&self.cursor, cx)).await
+	}
+
+	/// Waits for un-flushed events to be present, returns number of active
+	/// writers
+	///
+	/// See [`SyncEventReader::wait_new_async`].
+	pub fn wait_new_async(&self) -> WaitNew<'_, T>
+	{
+		WaitNew {
+			channel: &self.channel,
+		}
+	}
+
+	/// Waits for the channel to be flushed
+	///
+	/// See [`SyncEventReader::wait_flushed_async`].
+	pub fn wait_flushed_async(&self) -> WaitFlushed<'_, T>
+	{
+		WaitFlushed {
+			channel: &self.channel,
+			read_generation: self.cursor.get(),
+		}
+	}
+
+	/// Turns the reader into a [`Stream`] of cloned events
+	///
+	/// The stream is `Send + 'static`, so it can be moved into async tasks.
+	pub fn into_stream(self) -> OwnedEventStream<T>
+	{
+		OwnedEventStream {
+			reader: self,
+			pending: VecDeque::new(),
+		}
+	}
+}
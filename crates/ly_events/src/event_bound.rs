@@ -0,0 +1,103 @@
+use ly_log::core_prelude::*;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::sync_event_channel::Waiters;
+
+/// What a bounded channel does with events sent when it is full
+///
+/// See [`SyncEventChannel::bounded`](crate::channel::SyncEventChannel::bounded).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overflow
+{
+	/// The writer waits for a flush to make room
+	///
+	/// Deadlocks if the writing thread is the one flushing the channel.
+	Block,
+	/// The oldest un-flushed event is dropped to make room
+	DropOldest,
+	/// The sent event is dropped
+	DropNewest,
+	/// The event is given back by
+	/// [`send`](crate::channel::SyncEventWriter::send) in
+	/// [`SendError::Full`](crate::channel::SendError::Full)
+	Error,
+}
+
+/// Least time between warnings about dropped events, per channel
+const WARNING_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Capacity of a bounded channel, and its un-flushed events
+pub(crate) struct Bound
+{
+	pub(crate) capacity: usize,
+	pub(crate) overflow: Overflow,
+	/// Un-flushed events, and slots reserved by writers about to push
+	pending: AtomicUsize,
+	/// Writers waiting for room, woken on flush
+	pub(crate) space_waiters: Waiters,
+	created: Instant,
+	/// Time of the last warning, in ms since `created`, plus one
+	warned_at: AtomicU64,
+	/// Drops since the last warning
+	unreported: AtomicU64,
+}
+
+impl Bound
+{
+	pub(crate) fn new(capacity: usize, overflow: Overflow) -> Self
+	{
+		Bound {
+			capacity,
+			overflow,
+			pending: AtomicUsize::new(0),
+			space_waiters: Waiters::default(),
+			created: Instant::now(),
+			warned_at: AtomicU64::new(0),
+			unreported: AtomicU64::new(0),
+		}
+	}
+
+	/// Reserves room for an event, unless the channel is full
+	pub(crate) fn try_reserve(&self) -> bool
+	{
+		self.pending
+			.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |pending| {
+				(pending < self.capacity).then_some(pending + 1)
+			})
+			.is_ok()
+	}
+
+	/// Counts an event not sent by a writer, which is not bounded
+	pub(crate) fn add_unbounded(&self) { self.pending.fetch_add(1, Ordering::Relaxed); }
+
+	/// Frees the room of flushed or dropped events
+	pub(crate) fn release(&self, n: usize) { self.pending.fetch_sub(n, Ordering::Relaxed); }
+
+	pub(crate) fn has_space(&self) -> bool { self.pending.load(Ordering::Relaxed) < self.capacity }
+
+	/// Logs a warning about a dropped event, at most once per
+	/// [`WARNING_INTERVAL`]
+	pub(crate) fn warn_dropped(&self, channel_id: usize)
+	{
+		self.unreported.fetch_add(1, Ordering::Relaxed);
+		let now = self.created.elapsed().as_millis() as u64 + 1;
+		let warned_at = self.warned_at.load(Ordering::Relaxed);
+		if warned_at != 0 && now.saturating_sub(warned_at) < WARNING_INTERVAL.as_millis() as u64 {
+			return;
+		}
+		if self
+			.warned_at
+			.compare_exchange(warned_at, now, Ordering::Relaxed, Ordering::Relaxed)
+			.is_ok()
+		{
+			core_warning!(
+				"channel {} is full, dropped {} events (capacity {}, {:?})",
+				channel_id,
+				self.unreported.swap(0, Ordering::Relaxed),
+				self.capacity,
+				self.overflow
+			);
+		}
+	}
+}
@@ -0,0 +1,451 @@
+use ly_log::core_prelude::*;
+use parking_lot::Mutex;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::any::type_name;
+use std::error::Error;
+use std::fs;
+use std::io::{Read, Write};
+use std::marker::PhantomData;
+use std::net::Shutdown;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::owned_event_channel::{OwnedSyncEventReader, OwnedSyncEventWriter};
+use crate::sync_event_channel::SyncEventChannel;
+
+const MAGIC: [u8; 4] = *b"LYEV";
+/// Version of the framing and handshake, both sides must have the same
+pub const PROTOCOL_VERSION: u16 = 1;
+/// Largest accepted frame, a larger length is taken as a broken connection
+const MAX_FRAME: u32 = 1 << 24;
+/// Time between the connection attempts of a client
+const RETRY_INTERVAL: Duration = Duration::from_millis(200);
+/// Time a peer may take to shake hands, or to take sent events, before it is
+/// disconnected
+const PEER_TIMEOUT: Duration = Duration::from_secs(1);
+/// How often the forwarding thread checks if the bridge is stopped, in ms
+const STOP_POLL_MS: u64 = 100;
+
+/// First frame sent by both sides of a connection
+#[derive(Debug, Serialize, Deserialize)]
+struct Hello
+{
+	magic: [u8; 4],
+	version: u16,
+	event_type: String,
+}
+
+/// Mirrors a channel to other processes over a Unix domain socket
+///
+/// Listens for [`BridgeClient`]s on a socket, and sends every event flushed
+/// in the channel to all connected clients. Events sent by the clients are
+/// sent to the channel, and are then mirrored like any other event.
+///
+/// The bridge does not flush the channel, that is left to its other readers,
+/// like the `App` for channels in the [`Events`](crate::registry::Events)
+/// registry. Dropping the bridge disconnects the clients and removes the
+/// socket.
+pub struct EventBridge<T>
+{
+	server: Arc<Server>,
+	threads: Vec<JoinHandle<()>>,
+	_event: PhantomData<fn(T) -> T>,
+}
+
+struct Server
+{
+	path: PathBuf,
+	stop: AtomicBool,
+	/// Write halves of the connected clients, by connection id
+	peers: Mutex<Vec<(u64, UnixStream)>>,
+	next_id: AtomicU64,
+	/// Threads receiving events from the clients
+	receivers: Mutex<Vec<JoinHandle<()>>>,
+}
+
+/// Connection to an [`EventBridge`] in another process
+///
+/// Events mirrored by the bridge are sent to the client's own
+/// [`channel`](BridgeClient::channel), which the client's readers flush.
+/// Events given to [`send`](BridgeClient::send) are sent to the bridged
+/// channel. The client connects in the background, and reconnects whenever
+/// the connection is lost, e.g. when the other process is restarted.
+pub struct BridgeClient<T>
+{
+	client: Arc<Client>,
+	channel: Arc<SyncEventChannel<T>>,
+	thread: Option<JoinHandle<()>>,
+}
+
+struct Client
+{
+	path: PathBuf,
+	stop: AtomicBool,
+	/// Write half of the current connection
+	stream: Mutex<Option<UnixStream>>,
+}
+
+impl<T> EventBridge<T>
+where
+	T: Serialize + DeserializeOwned + Send + Sync + 'static,
+{
+	/// Mirrors the channel to the clients connecting to the socket at `path`
+	///
+	/// A socket file left by a process that is no longer listening is
+	/// replaced. Only events flushed after this are mirrored.
+	pub fn listen<P>(channel: &Arc<SyncEventChannel<T>>, path: P) -> Result<Self, Box<dyn Error>>
+	where
+		P: AsRef<Path>,
+	{
+		let reader = channel.get_owned_reader()?;
+		let path = path.as_ref().to_path_buf();
+		if path.exists() && UnixStream::connect(&path).is_err() {
+			fs::remove_file(&path)?;
+		}
+		let listener = UnixListener::bind(&path)?;
+		core_debug!(
+			"event bridge of {} listening on {:?}",
+			type_name::<T>(),
+			path
+		);
+
+		let server = Arc::new(Server {
+			path,
+			stop: AtomicBool::new(false),
+			peers: Mutex::new(Vec::new()),
+			next_id: AtomicU64::new(0),
+			receivers: Mutex::new(Vec::new()),
+		});
+		reader.read().count();
+		let writer = channel.get_owned_writer();
+		let threads = vec![
+			{
+				let server = Arc::clone(&server);
+				thread::spawn(move || server.accept(listener, writer))
+			},
+			{
+				let server = Arc::clone(&server);
+				thread::spawn(move || server.forward(reader))
+			},
+		];
+		Ok(EventBridge {
+			server,
+			threads,
+			_event: PhantomData,
+		})
+	}
+}
+
+impl<T> EventBridge<T>
+{
+	/// Number of connected clients
+	pub fn connections(&self) -> usize { self.server.peers.lock().len() }
+
+	/// Path of the socket
+	pub fn path(&self) -> &Path { &self.server.path }
+}
+
+impl<T> Drop for EventBridge<T>
+{
+	fn drop(&mut self)
+	{
+		self.server.stop.store(true, Ordering::Relaxed);
+		// wakes the accepting thread
+		let _ = UnixStream::connect(&self.server.path);
+		for thread in self.threads.drain(..) {
+			let _ = thread.join();
+		}
+		for (_, peer) in self.server.peers.lock().drain(..) {
+			let _ = peer.shutdown(Shutdown::Both);
+		}
+		let receivers = std::mem::take(&mut *self.server.receivers.lock());
+		for thread in receivers {
+			let _ = thread.join();
+		}
+		let _ = fs::remove_file(&self.server.path);
+	}
+}
+
+impl Server
+{
+	fn accept<T>(self: &Arc<Self>, listener: UnixListener, writer: OwnedSyncEventWriter<T>)
+	where
+		T: DeserializeOwned + Send + Sync + 'static,
+	{
+		for stream in listener.incoming() {
+			if self.stop.load(Ordering::Relaxed) {
+				break;
+			}
+			let stream = match stream {
+				Ok(stream) => stream,
+				Err(e) => {
+					core_warning!("event bridge failed to accept connection: {}", e);
+					continue;
+				}
+			};
+			let server = Arc::clone(self);
+			let writer = writer.clone();
+			let receiver = thread::spawn(move || server.serve(stream, writer));
+			let mut receivers = self.receivers.lock();
+			receivers.retain(|thread| !thread.is_finished());
+			receivers.push(receiver);
+		}
+	}
+
+	/// Receives the events of a client, until it disconnects
+	fn serve<T>(&self, mut stream: UnixStream, writer: OwnedSyncEventWriter<T>)
+	where
+		T: DeserializeOwned,
+	{
+		if let Err(e) = handshake::<T>(&mut stream) {
+			core_warning!("event bridge rejected connection: {}", e);
+			return;
+		}
+		let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+		{
+			let Ok(peer) = stream.try_clone()
+			else {
+				return;
+			};
+			let mut peers = self.peers.lock();
+			// checked while locked, so the peer is shut down when stopping
+			if self.stop.load(Ordering::Relaxed) {
+				return;
+			}
+			peers.push((id, peer));
+		}
+		receive(&mut stream, &writer);
+		self.peers.lock().retain(|(peer, _)| *peer != id);
+	}
+
+	/// Sends the flushed events to all clients
+	fn forward<T>(&self, reader: OwnedSyncEventReader<T>)
+	where
+		T: Serialize,
+	{
+		while !self.stop.load(Ordering::Relaxed) {
+			if reader.wait_flushed_timeout(STOP_POLL_MS).is_err() {
+				if reader.channel().is_closed() {
+					return;
+				}
+				// writers may still be added by the program or connecting
+				// clients
+				continue;
+			}
+			// encoded first, so the channel is not locked while writing
+			let mut frames = Vec::new();
+			for event in reader.read() {
+				match encode_frame(event) {
+					Ok(frame) => frames.extend(frame),
+					Err(e) => core_error!("event bridge failed to encode event: {}", e),
+				}
+			}
+			if frames.is_empty() {
+				continue;
+			}
+			self.peers.lock().retain_mut(|(_, peer)| {
+				let sent = peer.write_all(&frames);
+				if let Err(e) = &sent {
+					core_debug!("event bridge disconnecting client: {}", e);
+					let _ = peer.shutdown(Shutdown::Both);
+				}
+				sent.is_ok()
+			});
+		}
+	}
+}
+
+impl<T> BridgeClient<T>
+where
+	T: Serialize + DeserializeOwned + Send + Sync + 'static,
+{
+	/// Connects to the [`EventBridge`] listening at `path`
+	///
+	/// Returns directly, the connection is made in the background, and retried
+	/// until it succeeds.
+	pub fn connect<P>(path: P) -> Self
+	where
+		P: AsRef<Path>,
+	{
+		let client = Arc::new(Client {
+			path: path.as_ref().to_path_buf(),
+			stop: AtomicBool::new(false),
+			stream: Mutex::new(None),
+		});
+		let channel = Arc::new(SyncEventChannel::default());
+		let writer = channel.get_owned_writer();
+		let thread = {
+			let client = Arc::clone(&client);
+			thread::spawn(move || client.run(writer))
+		};
+		BridgeClient {
+			client,
+			channel,
+			thread: Some(thread),
+		}
+	}
+
+	/// Sends the event to the bridged channel
+	///
+	/// Fails if not connected, the event is not kept to be sent later.
+	pub fn send(&self, event: &T) -> Result<(), Box<dyn Error>>
+	{
+		let frame = encode_frame(event)?;
+		match &mut *self.client.stream.lock() {
+			Some(stream) => Ok(stream.write_all(&frame)?),
+			None => Err("not connected to event bridge".into()),
+		}
+	}
+}
+
+impl<T> BridgeClient<T>
+{
+	/// Gets the channel that the mirrored events are sent to
+	pub fn channel(&self) -> &Arc<SyncEventChannel<T>> { &self.channel }
+
+	/// Checks if connected to the bridge
+	pub fn is_connected(&self) -> bool { self.client.stream.lock().is_some() }
+}
+
+impl<T> Drop for BridgeClient<T>
+{
+	fn drop(&mut self)
+	{
+		self.client.stop.store(true, Ordering::Relaxed);
+		if let Some(stream) = self.client.stream.lock().take() {
+			let _ = stream.shutdown(Shutdown::Both);
+		}
+		if let Some(thread) = self.thread.take() {
+			let _ = thread.join();
+		}
+	}
+}
+
+impl Client
+{
+	/// Connects and receives events, until stopped
+	fn run<T>(&self, writer: OwnedSyncEventWriter<T>)
+	where
+		T: DeserializeOwned,
+	{
+		while !self.stop.load(Ordering::Relaxed) {
+			if let Some(mut stream) = self.connect::<T>() {
+				receive(&mut stream, &writer);
+				*self.stream.lock() = None;
+			}
+			if !self.stop.load(Ordering::Relaxed) {
+				thread::sleep(RETRY_INTERVAL);
+			}
+		}
+	}
+
+	fn connect<T>(&self) -> Option<UnixStream>
+	{
+		// the bridge not listening is expected, and not worth a log line
+		let mut stream = UnixStream::connect(&self.path).ok()?;
+		if let Err(e) = handshake::<T>(&mut stream) {
+			core_warning!("event bridge at {:?} rejected: {}", self.path, e);
+			return None;
+		}
+		let peer = stream.try_clone().ok()?;
+		let mut current = self.stream.lock();
+		// checked while locked, so the stream is shut down when stopping
+		if self.stop.load(Ordering::Relaxed) {
+			return None;
+		}
+		*current = Some(peer);
+		core_debug!("connected to event bridge at {:?}", self.path);
+		Some(stream)
+	}
+}
+
+/// Exchanges [`Hello`]s, checking that both sides speak the same protocol
+/// and send the same event type
+///
+/// Sets the timeout for writing to the peer, so a stalled peer is
+/// disconnected.
+fn handshake<T>(stream: &mut UnixStream) -> Result<(), Box<dyn Error>>
+{
+	stream.set_read_timeout(Some(PEER_TIMEOUT))?;
+	stream.set_write_timeout(Some(PEER_TIMEOUT))?;
+	let hello = Hello {
+		magic: MAGIC,
+		version: PROTOCOL_VERSION,
+		event_type: type_name::<T>().to_string(),
+	};
+	stream.write_all(&encode_frame(&hello)?)?;
+	let peer: Hello = read_frame(stream)?;
+	stream.set_read_timeout(None)?;
+
+	if peer.magic != MAGIC {
+		return Err("peer is not an event bridge".into());
+	}
+	if peer.version != PROTOCOL_VERSION {
+		return Err(format!(
+			"peer has protocol version {}, expected {}",
+			peer.version, PROTOCOL_VERSION
+		)
+		.into());
+	}
+	if peer.event_type != hello.event_type {
+		return Err(format!(
+			"peer sends {}, expected {}",
+			peer.event_type, hello.event_type
+		)
+		.into());
+	}
+	Ok(())
+}
+
+/// Sends the events received on `stream` to the channel, until disconnected
+fn receive<T>(stream: &mut UnixStream, writer: &OwnedSyncEventWriter<T>)
+where
+	T: DeserializeOwned,
+{
+	loop {
+		match read_frame(stream) {
+			Ok(event) => {
+				// dropped if the channel is full or closed
+				let _ = writer.send(event);
+			}
+			Err(e) => {
+				core_debug!("event bridge connection closed: {}", e);
+				return;
+			}
+		}
+	}
+}
+
+/// Serializes the value as a frame, prefixed by its length as a
+/// little-endian `u32`
+fn encode_frame<S>(value: &S) -> Result<Vec<u8>, Box<dyn Error>>
+where
+	S: Serialize,
+{
+	let payload = bincode::serialize(value)?;
+	let mut frame = Vec::with_capacity(4 + payload.len());
+	frame.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+	frame.extend_from_slice(&payload);
+	Ok(frame)
+}
+
+fn read_frame<D, R>(reader: &mut R) -> Result<D, Box<dyn Error>>
+where
+	D: DeserializeOwned,
+	R: Read,
+{
+	let mut len = [0; 4];
+	reader.read_exact(&mut len)?;
+	let len = u32::from_le_bytes(len);
+	if len > MAX_FRAME {
+		return Err(format!("frame of {} bytes is too large", len).into());
+	}
+	let mut payload = vec![0; len as usize];
+	reader.read_exact(&mut payload)?;
+	Ok(bincode::deserialize(&payload)?)
+}
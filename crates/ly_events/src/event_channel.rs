@@ -1,5 +1,7 @@
 use std::cell::UnsafeCell;
 
+use crate::event_close::{Closed, Disconnected};
+
 #[derive(Debug)]
 pub(crate) enum ReadableEventBuffer
 {
@@ -13,6 +15,11 @@ impl Default for ReadableEventBuffer
 }
 
 /// Single-threaded event channel
+///
+/// Counts its writers and readers. Sending to a channel whose readers are all
+/// dropped, or one that is [closed](EventChannel::close), gives the event back
+/// in [`Closed`]. Events sent before the first reader is created are kept for
+/// it.
 pub struct EventChannel<T>
 {
 	pub(crate) events_a: UnsafeCell<Vec<T>>,
@@ -21,6 +28,12 @@ pub struct EventChannel<T>
 	pub(crate) start_idx_b: UnsafeCell<usize>,
 	pub(crate) readable_buffer: UnsafeCell<ReadableEventBuffer>,
 	writers: UnsafeCell<usize>,
+	readers: UnsafeCell<usize>,
+	/// Set by the first writer and reader, as a channel is only cut off once
+	/// they are all dropped
+	had_writers: UnsafeCell<bool>,
+	had_readers: UnsafeCell<bool>,
+	closed: UnsafeCell<bool>,
 }
 
 impl<T> Default for EventChannel<T>
@@ -34,6 +47,10 @@ impl<T> Default for EventChannel<T>
 			start_idx_b: UnsafeCell::new(0),
 			readable_buffer: UnsafeCell::new(ReadableEventBuffer::A),
 			writers: UnsafeCell::new(0),
+			readers: UnsafeCell::new(0),
+			had_writers: UnsafeCell::new(false),
+			had_readers: UnsafeCell::new(false),
+			closed: UnsafeCell::new(false),
 		}
 	}
 }
@@ -112,6 +129,7 @@ impl<T> EventChannel<T>
 		unsafe {
 			let writers = self.writers.get();
 			*writers += 1;
+			*self.had_writers.get() = true;
 		}
 		EventWriter { channel: self }
 	}
@@ -119,25 +137,76 @@ impl<T> EventChannel<T>
 	/// Creates a reader for this channel
 	pub fn get_reader(&self) -> EventReader<T>
 	{
+		unsafe {
+			let readers = self.readers.get();
+			*readers += 1;
+			*self.had_readers.get() = true;
+		}
 		EventReader {
 			read_events: UnsafeCell::new(0),
 			channel: self,
 		}
 	}
 
-	fn has_writers(&self) -> bool
+	/// Closes the channel
+	///
+	/// Sending gives the event back from then on. The events sent before can
+	/// still be flushed and read, after that the readers are
+	/// [`Disconnected`].
+	pub fn close(&self) { unsafe { *self.closed.get() = true } }
+
+	pub fn is_closed(&self) -> bool { unsafe { *self.closed.get() } }
+
+	/// Gets the number of live writers
+	pub fn get_num_writers(&self) -> usize { unsafe { *self.writers.get() } }
+
+	/// Gets the number of live readers
+	pub fn get_num_readers(&self) -> usize { unsafe { *self.readers.get() } }
+
+	fn has_writers(&self) -> bool { self.get_num_writers() != 0 }
+
+	/// Checks if there are sent un-flushed events
+	fn has_new_events(&self) -> bool
 	{
 		unsafe {
-			let writers = self.writers.get();
-			*writers != 0
+			match *self.readable_buffer.get() {
+				ReadableEventBuffer::A => !(*self.events_b.get()).is_empty(),
+				ReadableEventBuffer::B => !(*self.events_a.get()).is_empty(),
+			}
 		}
 	}
+
+	/// Checks if no more events can be flushed: the channel is closed or its
+	/// writers are all dropped, and has no un-flushed events
+	fn is_disconnected(&self) -> bool
+	{
+		let writers_dropped = !self.has_writers() && unsafe { *self.had_writers.get() };
+		(self.is_closed() || writers_dropped) && !self.has_new_events()
+	}
+
+	/// Checks if events sent now are given back: the channel is closed or its
+	/// readers are all dropped
+	fn refuses_events(&self) -> bool
+	{
+		let readers_dropped = self.get_num_readers() == 0 && unsafe { *self.had_readers.get() };
+		self.is_closed() || readers_dropped
+	}
 }
 
 impl<'a, T> EventWriter<'a, T>
 {
 	/// Sends the event to the channel
-	pub fn send(&self, event: T) { self.channel.send(event); }
+	///
+	/// Gives the event back if the channel is closed, or its readers are all
+	/// dropped.
+	pub fn send(&self, event: T) -> Result<(), Closed<T>>
+	{
+		if self.channel.refuses_events() {
+			return Err(Closed(event));
+		}
+		self.channel.send(event);
+		Ok(())
+	}
 }
 
 impl<'a, T> Drop for EventWriter<'a, T>
@@ -146,7 +215,7 @@ impl<'a, T> Drop for EventWriter<'a, T>
 	{
 		unsafe {
 			let writers = self.channel.writers.get();
-			*writers += 1;
+			*writers -= 1;
 		}
 	}
 }
@@ -189,6 +258,19 @@ impl<'a, T> EventReader<'a, T>
 		}
 	}
 
+	/// Reads all unread events, unless the reader is disconnected
+	///
+	/// Like [`read`](EventReader::read), but gives [`Disconnected`] once all
+	/// events are read and the channel is closed or has no writers, so no
+	/// more events will come.
+	pub fn try_read(&self) -> Result<impl Iterator<Item = &T>, Disconnected>
+	{
+		if !self.has_unread() && self.channel.is_disconnected() {
+			return Err(Disconnected);
+		}
+		Ok(self.read())
+	}
+
 	/// Initiates a flush on the reader's connected channel
 	///
 	/// It is adviced to use this for flushing. Read [`EventChannel::flush`]
@@ -197,4 +279,27 @@ impl<'a, T> EventReader<'a, T>
 
 	/// Checks if there are any writers connected to reading channel
 	pub fn channel_has_writers(&self) -> bool { self.channel.has_writers() }
+
+	/// Checks if the flushed events are unread, like `read` does
+	fn has_unread(&self) -> bool
+	{
+		unsafe {
+			let start_idx = match *self.channel.readable_buffer.get() {
+				ReadableEventBuffer::A => *self.channel.start_idx_a.get(),
+				ReadableEventBuffer::B => *self.channel.start_idx_b.get(),
+			};
+			*self.read_events.get() <= start_idx
+		}
+	}
+}
+
+impl<'a, T> Drop for EventReader<'a, T>
+{
+	fn drop(&mut self)
+	{
+		unsafe {
+			let readers = self.channel.readers.get();
+			*readers -= 1;
+		}
+	}
 }
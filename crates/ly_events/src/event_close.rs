@@ -0,0 +1,67 @@
+use std::fmt;
+
+/// Error giving back an event sent to a closed channel, or a channel without
+/// readers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Closed<T>(pub T);
+
+impl<T> fmt::Display for Closed<T>
+{
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+	{
+		write!(f, "channel is closed, or has no readers")
+	}
+}
+
+impl<T: fmt::Debug> std::error::Error for Closed<T> {}
+
+/// Error giving back an event that
+/// [`send`](crate::channel::SyncEventWriter::send) could not send
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SendError<T>
+{
+	/// The channel is full, with the
+	/// [`Overflow::Error`](crate::channel::Overflow::Error) policy
+	Full(T),
+	/// The channel is closed, or has no readers
+	Closed(T),
+}
+
+impl<T> SendError<T>
+{
+	/// Gets back the event that was not sent
+	pub fn into_inner(self) -> T
+	{
+		match self {
+			SendError::Full(event) | SendError::Closed(event) => event,
+		}
+	}
+}
+
+impl<T> fmt::Display for SendError<T>
+{
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+	{
+		match self {
+			SendError::Full(_) => write!(f, "channel is full"),
+			SendError::Closed(_) => write!(f, "channel is closed, or has no readers"),
+		}
+	}
+}
+
+impl<T: fmt::Debug> std::error::Error for SendError<T> {}
+
+/// Status of a reader that has read all events, of a channel that gets no
+/// more
+///
+/// The channel is closed, or has no writers left, and there are no un-flushed
+/// events. Worker loops can exit on it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Disconnected;
+
+impl fmt::Display for Disconnected
+{
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { write!(f, "channel is disconnected") }
+}
+
+impl std::error::Error for Disconnected {}
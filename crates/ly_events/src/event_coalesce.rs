@@ -0,0 +1,244 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+use crate::event_meta::EventMeta;
+
+/// Rule for coalescing the events of a flush
+///
+/// Set on a channel with
+/// [`set_coalesce`](crate::channel::SyncEventChannel::set_coalesce), and
+/// applied to the events of every flush before they can be read. This cuts
+/// the number of events for high-frequency events, like mouse movement,
+/// where the readers only care about the sum or the latest of them.
+///
+/// Rules are combined with [`then`](Coalesce::then).
+pub trait Coalesce<T>: Send + Sync
+{
+	/// Coalesces the flushed events in place
+	fn coalesce(&self, events: &mut Vec<T>);
+
+	/// Coalesces the flushed events in place, together with their metadata,
+	/// `metas[i]` being the metadata of `events[i]`
+	///
+	/// Used by channels keeping metadata. The default implementation gives
+	/// all events the metadata of the last event of the flush, if any event
+	/// is removed. Override it to keep the metadata of the kept events.
+	fn coalesce_with_meta(&self, events: &mut Vec<T>, metas: &mut Vec<EventMeta>)
+	{
+		let len = events.len();
+		self.coalesce(events);
+		if let (true, Some(&last)) = (events.len() != len, metas.last()) {
+			*metas = vec![last; events.len()];
+		}
+	}
+
+	/// Applies `next` after this rule
+	fn then<C>(self, next: C) -> Then<Self, C>
+	where
+		Self: Sized,
+		C: Coalesce<T>,
+	{
+		Then { first: self, next }
+	}
+}
+
+/// Merges all events with the same key into the first of them
+///
+/// Suits events carrying deltas, which can be summed. Events without a key
+/// are kept as they are.
+pub struct MergeBy<K, F, M>
+{
+	key: F,
+	merge: M,
+	_key: PhantomData<fn() -> K>,
+}
+
+/// Keeps only the latest event of each key
+///
+/// Suits events carrying a state, like a position, where only the last one
+/// matters. The kept event stays where it was sent, and events without a
+/// key are kept as they are.
+pub struct LatestBy<K, F>
+{
+	key: F,
+	_key: PhantomData<fn() -> K>,
+}
+
+/// Removes events equal to the event before them
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Dedupe;
+
+/// Two rules applied after each other
+///
+/// Created by [`Coalesce::then`].
+pub struct Then<A, B>
+{
+	first: A,
+	next: B,
+}
+
+impl<K, F, M> MergeBy<K, F, M>
+{
+	/// Creates the rule, where `key` gives the key to merge by, and `merge`
+	/// merges an event into the first event of the same key
+	pub fn new<T>(key: F, merge: M) -> Self
+	where
+		F: Fn(&T) -> Option<K>,
+		M: Fn(&mut T, &T),
+	{
+		MergeBy {
+			key,
+			merge,
+			_key: PhantomData,
+		}
+	}
+}
+
+impl<T, K, F, M> Coalesce<T> for MergeBy<K, F, M>
+where
+	K: Eq + Hash,
+	F: Fn(&T) -> Option<K> + Send + Sync,
+	M: Fn(&mut T, &T) + Send + Sync,
+{
+	fn coalesce(&self, events: &mut Vec<T>)
+	{
+		let keep = self.merge(events);
+		retain_mask(events, &keep);
+	}
+
+	/// The merged events keep the metadata of the first event
+	fn coalesce_with_meta(&self, events: &mut Vec<T>, metas: &mut Vec<EventMeta>)
+	{
+		let keep = self.merge(events);
+		retain_mask(events, &keep);
+		retain_mask(metas, &keep);
+	}
+}
+
+impl<K, F, M> MergeBy<K, F, M>
+{
+	/// Merges the events in place, giving which events to keep
+	fn merge<T>(&self, events: &mut [T]) -> Vec<bool>
+	where
+		K: Eq + Hash,
+		F: Fn(&T) -> Option<K>,
+		M: Fn(&mut T, &T),
+	{
+		let mut first = HashMap::new();
+		let mut keep = vec![true; events.len()];
+		for i in 0..events.len() {
+			let Some(key) = (self.key)(&events[i])
+			else {
+				continue;
+			};
+			match first.get(&key) {
+				Some(&into) => {
+					let (head, tail) = events.split_at_mut(i);
+					(self.merge)(&mut head[into], &tail[0]);
+					keep[i] = false;
+				}
+				None => {
+					first.insert(key, i);
+				}
+			}
+		}
+		keep
+	}
+}
+
+impl<K, F> LatestBy<K, F>
+{
+	/// Creates the rule, where `key` gives the key to keep the latest of
+	pub fn new<T>(key: F) -> Self
+	where
+		F: Fn(&T) -> Option<K>,
+	{
+		LatestBy {
+			key,
+			_key: PhantomData,
+		}
+	}
+}
+
+impl<T, K, F> Coalesce<T> for LatestBy<K, F>
+where
+	K: Eq + Hash,
+	F: Fn(&T) -> Option<K> + Send + Sync,
+{
+	fn coalesce(&self, events: &mut Vec<T>)
+	{
+		let keep = self.latest(events);
+		retain_mask(events, &keep);
+	}
+
+	fn coalesce_with_meta(&self, events: &mut Vec<T>, metas: &mut Vec<EventMeta>)
+	{
+		let keep = self.latest(events);
+		retain_mask(events, &keep);
+		retain_mask(metas, &keep);
+	}
+}
+
+impl<K, F> LatestBy<K, F>
+{
+	/// Gives which events are the latest of their key, or have no key
+	fn latest<T>(&self, events: &[T]) -> Vec<bool>
+	where
+		K: Eq + Hash,
+		F: Fn(&T) -> Option<K>,
+	{
+		let keys: Vec<_> = events.iter().map(&self.key).collect();
+		let mut latest = HashMap::new();
+		for (i, key) in keys.iter().enumerate() {
+			if let Some(key) = key {
+				latest.insert(key, i);
+			}
+		}
+		keys.iter()
+			.enumerate()
+			.map(|(i, key)| key.as_ref().is_none_or(|key| latest[key] == i))
+			.collect()
+	}
+}
+
+impl<T> Coalesce<T> for Dedupe
+where
+	T: PartialEq,
+{
+	fn coalesce(&self, events: &mut Vec<T>) { events.dedup(); }
+
+	fn coalesce_with_meta(&self, events: &mut Vec<T>, metas: &mut Vec<EventMeta>)
+	{
+		let keep: Vec<_> = (0..events.len())
+			.map(|i| i == 0 || events[i] != events[i - 1])
+			.collect();
+		retain_mask(events, &keep);
+		retain_mask(metas, &keep);
+	}
+}
+
+impl<T, A, B> Coalesce<T> for Then<A, B>
+where
+	A: Coalesce<T>,
+	B: Coalesce<T>,
+{
+	fn coalesce(&self, events: &mut Vec<T>)
+	{
+		self.first.coalesce(events);
+		self.next.coalesce(events);
+	}
+
+	fn coalesce_with_meta(&self, events: &mut Vec<T>, metas: &mut Vec<EventMeta>)
+	{
+		self.first.coalesce_with_meta(events, metas);
+		self.next.coalesce_with_meta(events, metas);
+	}
+}
+
+/// Keeps the items where `keep` is true
+fn retain_mask<U>(items: &mut Vec<U>, keep: &[bool])
+{
+	let mut keep = keep.iter();
+	items.retain(|_| *keep.next().unwrap());
+}
@@ -0,0 +1,172 @@
+use std::fmt;
+use std::sync::Arc;
+
+use crate::event_close::Disconnected;
+use crate::event_meta::EventMeta;
+use crate::sync_event_channel::{Cursor, SyncEventChannel};
+
+/// Error getting a drainer of a channel that has other readers
+///
+/// Holds the number of readers, including a drainer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NotExclusive(pub usize);
+
+impl fmt::Display for NotExclusive
+{
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+	{
+		write!(f, "channel has {} other readers", self.0)
+	}
+}
+
+impl std::error::Error for NotExclusive {}
+
+/// Error getting a reader of a channel that has a drainer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Drained;
+
+impl fmt::Display for Drained
+{
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+	{
+		write!(f, "channel has a drainer, and can have no other readers")
+	}
+}
+
+impl std::error::Error for Drained {}
+
+/// The only reader of a channel, taking ownership of the flushed events
+///
+/// Created by [`SyncEventChannel::get_drainer`]. Events with large payloads
+/// are moved out of the channel instead of cloned. No other readers may be
+/// created while the drainer lives, getting one fails with [`Drained`].
+pub struct SyncEventDrainer<'a, T>
+{
+	cursor: Arc<Cursor>,
+	channel: &'a SyncEventChannel<T>,
+}
+
+/// A [`SyncEventDrainer`] owning a handle to its channel
+///
+/// Created by [`SyncEventChannel::get_owned_drainer`].
+pub struct OwnedSyncEventDrainer<T>
+{
+	cursor: Arc<Cursor>,
+	channel: Arc<SyncEventChannel<T>>,
+}
+
+impl<T> SyncEventChannel<T>
+{
+	/// Creates the drainer of the channel
+	///
+	/// Fails if the channel has readers, or another drainer.
+	pub fn get_drainer(&self) -> Result<SyncEventDrainer<'_, T>, NotExclusive>
+	{
+		Ok(SyncEventDrainer {
+			cursor: self.add_drainer()?,
+			channel: self,
+		})
+	}
+
+	/// Creates the drainer of the channel, owning a handle to it
+	///
+	/// Fails if the channel has readers, or another drainer.
+	pub fn get_owned_drainer(self: &Arc<Self>) -> Result<OwnedSyncEventDrainer<T>, NotExclusive>
+	{
+		Ok(OwnedSyncEventDrainer {
+			cursor: self.add_drainer()?,
+			channel: Arc::clone(self),
+		})
+	}
+}
+
+impl<'a, T> SyncEventDrainer<'a, T>
+{
+	/// Takes all unread events
+	pub fn drain(&self) -> impl Iterator<Item = T>
+	{
+		self.channel.take_from(&self.cursor).0.into_iter()
+	}
+
+	/// Takes all unread events, with their metadata
+	///
+	/// Panics if the channel does not keep metadata.
+	pub fn drain_with_meta(&self) -> impl Iterator<Item = (T, EventMeta)>
+	{
+		drain_with_meta(self.channel, &self.cursor)
+	}
+
+	/// Initiates a flush on the drainer's channel
+	pub fn flush_channel(&self) { self.channel.flush(); }
+
+	/// Waits for flushed un-drained events, see
+	/// [`SyncEventReader::wait_flushed`](crate::channel::SyncEventReader::wait_flushed)
+	pub fn wait_flushed(&self) -> Result<(), Disconnected>
+	{
+		self.channel.wait_flushed(&self.cursor)
+	}
+
+	/// Like [`wait_flushed`](SyncEventDrainer::wait_flushed), with a timeout in
+	/// ms
+	pub fn wait_flushed_timeout(&self, timeout_ms: u64) -> Result<(), Disconnected>
+	{
+		self.channel.wait_flushed_timeout(&self.cursor, timeout_ms)
+	}
+}
+
+impl<T> OwnedSyncEventDrainer<T>
+{
+	/// Takes all unread events
+	pub fn drain(&self) -> impl Iterator<Item = T>
+	{
+		self.channel.take_from(&self.cursor).0.into_iter()
+	}
+
+	/// Takes all unread events, with their metadata
+	///
+	/// Panics if the channel does not keep metadata.
+	pub fn drain_with_meta(&self) -> impl Iterator<Item = (T, EventMeta)>
+	{
+		drain_with_meta(&self.channel, &self.cursor)
+	}
+
+	/// Initiates a flush on the drainer's channel
+	pub fn flush_channel(&self) { self.channel.flush(); }
+
+	/// Waits for flushed un-drained events, see
+	/// [`SyncEventReader::wait_flushed`](crate::channel::SyncEventReader::wait_flushed)
+	pub fn wait_flushed(&self) -> Result<(), Disconnected>
+	{
+		self.channel.wait_flushed(&self.cursor)
+	}
+
+	/// Like [`wait_flushed`](OwnedSyncEventDrainer::wait_flushed), with a
+	/// timeout in ms
+	pub fn wait_flushed_timeout(&self, timeout_ms: u64) -> Result<(), Disconnected>
+	{
+		self.channel.wait_flushed_timeout(&self.cursor, timeout_ms)
+	}
+
+	/// Gets the channel the drainer takes from
+	pub fn channel(&self) -> &Arc<SyncEventChannel<T>> { &self.channel }
+}
+
+impl<'a, T> Drop for SyncEventDrainer<'a, T>
+{
+	fn drop(&mut self) { self.channel.remove_drainer(); }
+}
+
+impl<T> Drop for OwnedSyncEventDrainer<T>
+{
+	fn drop(&mut self) { self.channel.remove_drainer(); }
+}
+
+fn drain_with_meta<T>(
+	channel: &SyncEventChannel<T>,
+	cursor: &Arc<Cursor>,
+) -> impl Iterator<Item = (T, EventMeta)>
+{
+	assert!(channel.has_meta(), "channel does not keep metadata");
+	let (events, metas) = channel.take_from(cursor);
+	events.into_iter().zip(metas)
+}
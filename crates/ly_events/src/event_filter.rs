@@ -0,0 +1,83 @@
+use std::ops::Deref;
+
+use crate::owned_event_channel::OwnedSyncEventReader;
+use crate::sync_event_channel::SyncEventReader;
+
+/// Reader that only reads the events matching a filter
+///
+/// Created by [`SyncEventReader::filter`] and
+/// [`OwnedSyncEventReader::filter`]. The events are filtered while iterating,
+/// without copying them. Derefs to the wrapped reader, for flushing and
+/// waiting, which are not affected by the filter.
+pub struct Filtered<R, F>
+{
+	reader: R,
+	filter: F,
+}
+
+impl<R, F> Filtered<R, F>
+{
+	/// Gets back the unfiltered reader
+	pub fn into_inner(self) -> R { self.reader }
+}
+
+impl<R, F> Deref for Filtered<R, F>
+{
+	type Target = R;
+
+	fn deref(&self) -> &R { &self.reader }
+}
+
+impl<'a, T, F> Filtered<SyncEventReader<'a, T>, F>
+where
+	F: Fn(&T) -> bool,
+{
+	/// Reads all unread events matching the filter
+	///
+	/// The other events are read too, but skipped.
+	pub fn read(&self) -> impl Iterator<Item = &T>
+	{
+		self.reader.read().filter(|e| (self.filter)(e))
+	}
+}
+
+impl<T, F> Filtered<OwnedSyncEventReader<T>, F>
+where
+	F: Fn(&T) -> bool,
+{
+	/// Reads all unread events matching the filter
+	///
+	/// The other events are read too, but skipped.
+	pub fn read(&self) -> impl Iterator<Item = &T>
+	{
+		self.reader.read().filter(|e| (self.filter)(e))
+	}
+}
+
+impl<'a, T> SyncEventReader<'a, T>
+{
+	/// Turns the reader into one only reading the events matching `filter`
+	pub fn filter<F>(self, filter: F) -> Filtered<Self, F>
+	where
+		F: Fn(&T) -> bool,
+	{
+		Filtered {
+			reader: self,
+			filter,
+		}
+	}
+}
+
+impl<T> OwnedSyncEventReader<T>
+{
+	/// Turns the reader into one only reading the events matching `filter`
+	pub fn filter<F>(self, filter: F) -> Filtered<Self, F>
+	where
+		F: Fn(&T) -> bool,
+	{
+		Filtered {
+			reader: self,
+			filter,
+		}
+	}
+}
@@ -0,0 +1,115 @@
+use crate::event_meta::EventMeta;
+use crate::owned_event_channel::OwnedSyncEventReader;
+
+/// Reads several channels as one, merged in the order the events were sent
+///
+/// Each channel is read by its own reader, and its events are mapped to the
+/// common event type `E`, e.g. an enum with a variant per channel. Only the
+/// added channels are read, so a reader of key presses does not pay for
+/// mouse movement.
+///
+/// The order is the [`order`](EventMeta::order) of the events, so all
+/// channels must keep metadata, see
+/// [`with_meta`](crate::channel::SyncEventChannel::with_meta). Events are
+/// merged over what is flushed in the channels when reading.
+pub struct GroupReader<E>
+{
+	members: Vec<Box<dyn Member<E>>>,
+}
+
+/// A reader of one of the channels of a [`GroupReader`]
+trait Member<E>: Send + Sync
+{
+	fn read_into(&self, events: &mut Vec<(E, EventMeta)>);
+
+	fn flush(&self);
+}
+
+struct MappedReader<T, F>
+{
+	reader: OwnedSyncEventReader<T>,
+	map: F,
+}
+
+impl<T, E, F> Member<E> for MappedReader<T, F>
+where
+	T: Send + Sync,
+	F: Fn(&T) -> E + Send + Sync,
+{
+	fn read_into(&self, events: &mut Vec<(E, EventMeta)>)
+	{
+		events.extend(
+			self.reader
+				.read_with_meta()
+				.map(|(event, meta)| ((self.map)(event), *meta)),
+		);
+	}
+
+	fn flush(&self) { self.reader.flush_channel(); }
+}
+
+impl<E> Default for GroupReader<E>
+{
+	fn default() -> Self
+	{
+		GroupReader {
+			members: Vec::new(),
+		}
+	}
+}
+
+impl<E> GroupReader<E>
+{
+	pub fn new() -> Self { Self::default() }
+
+	/// Adds the channel of the reader, converting its events with `Into`
+	///
+	/// Panics if the channel does not keep metadata.
+	pub fn with<T>(self, reader: OwnedSyncEventReader<T>) -> Self
+	where
+		T: Clone + Into<E> + Send + Sync + 'static,
+	{
+		self.with_map(reader, |event: &T| event.clone().into())
+	}
+
+	/// Adds the channel of the reader, converting its events with `map`
+	///
+	/// Panics if the channel does not keep metadata.
+	pub fn with_map<T, F>(mut self, reader: OwnedSyncEventReader<T>, map: F) -> Self
+	where
+		T: Send + Sync + 'static,
+		F: Fn(&T) -> E + Send + Sync + 'static,
+	{
+		assert!(
+			reader.channel().has_meta(),
+			"channels of a group reader must keep metadata"
+		);
+		self.members.push(Box::new(MappedReader { reader, map }));
+		self
+	}
+
+	/// Reads all unread events of all channels, in the order they were sent
+	pub fn read(&self) -> impl Iterator<Item = E> { self.read_with_meta().map(|(event, _)| event) }
+
+	/// Like [`read`](GroupReader::read), with the metadata of each event
+	pub fn read_with_meta(&self) -> impl Iterator<Item = (E, EventMeta)>
+	{
+		let mut events = Vec::new();
+		for member in &self.members {
+			member.read_into(&mut events);
+		}
+		events.sort_by_key(|(_, meta)| meta.order);
+		events.into_iter()
+	}
+
+	/// Flushes all channels of the group
+	///
+	/// Not to be used on channels flushed by others, like those in the
+	/// [`Events`](crate::registry::Events) of the `App`.
+	pub fn flush_channels(&self)
+	{
+		for member in &self.members {
+			member.flush();
+		}
+	}
+}
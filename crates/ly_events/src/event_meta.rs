@@ -0,0 +1,32 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
+
+/// Order of the next event sent to any channel keeping metadata
+static ORDER: AtomicU64 = AtomicU64::new(0);
+
+/// Metadata of a sent event
+///
+/// Recorded on send by channels created
+/// [`with_meta`](crate::channel::SyncEventChannel::with_meta), and read
+/// together with the events by
+/// [`read_with_meta`](crate::channel::SyncEventReader::read_with_meta).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EventMeta
+{
+	/// When the event was sent
+	pub timestamp: Instant,
+	/// Frame of the global [`EventScheduler`](crate::schedule::EventScheduler)
+	/// when the event was sent
+	pub frame: u64,
+	/// Number of events sent to the channel before this one
+	pub sequence: u64,
+	/// Number of events sent before this one to all channels keeping metadata,
+	/// for merging channels in send order, see
+	/// [`GroupReader`](crate::channel::GroupReader)
+	pub order: u64,
+	/// Id of the writer that sent the event, see
+	/// [`SyncEventWriter::id`](crate::channel::SyncEventWriter::id)
+	pub writer: usize,
+}
+
+pub(crate) fn next_order() -> u64 { ORDER.fetch_add(1, Ordering::Relaxed) }
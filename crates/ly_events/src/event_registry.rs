@@ -0,0 +1,238 @@
+use ly_log::core_prelude::*;
+use parking_lot::RwLock;
+use std::any::{type_name, Any, TypeId};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::event_bound::Overflow;
+use crate::event_stats::ChannelStats;
+use crate::owned_event_channel::{OwnedSyncEventReader, OwnedSyncEventWriter};
+use crate::ring_event_channel::{Retention, RingEventChannel};
+use crate::sync_event_channel::SyncEventChannel;
+
+/// A [`SyncEventChannel`] with the event type erased
+pub trait AnyChannel: Send + Sync
+{
+	/// Flushes the channel, see [`SyncEventChannel::flush`]
+	fn flush(&self);
+
+	/// Name of the event type, for reporting
+	fn event_type_name(&self) -> &'static str;
+
+	/// Gets the channel as `Any`, to downcast to the `SyncEventChannel`
+	fn into_any(self: Arc<Self>) -> Arc<dyn Any + Send + Sync>;
+
+	/// Takes a snapshot of the statistics, see [`SyncEventChannel::stats`]
+	fn stats(&self) -> ChannelStats;
+}
+
+impl<T> AnyChannel for SyncEventChannel<T>
+where
+	T: Send + Sync + 'static,
+{
+	fn flush(&self) { SyncEventChannel::flush(self); }
+
+	fn event_type_name(&self) -> &'static str { type_name::<T>() }
+
+	fn into_any(self: Arc<Self>) -> Arc<dyn Any + Send + Sync> { self }
+
+	fn stats(&self) -> ChannelStats { SyncEventChannel::stats(self) }
+}
+
+/// An event type declaring how its channel is configured
+///
+/// Usually derived with `#[derive(Event)]`, configured by `#[event(...)]`
+/// attributes:
+/// - `capacity = n` makes the channel [`bounded`](SyncEventChannel::bounded),
+///   with the overflow policy `overflow = DropOldest` (the default),
+///   `DropNewest`, `Block` or `Error`
+/// - `coalesce = path` sets the [`Coalesce`](crate::channel::Coalesce) rule
+///   returned by the function at `path`, like `MouseEvent::coalescing`
+/// - `meta` keeps [metadata](SyncEventChannel::with_meta)
+/// - `retention(flushes = n)` or `retention(events = n)` sets the
+///   [`RETENTION`](Event::RETENTION) of ring channels
+/// - `serde` implements `Serialize` and `Deserialize`, for recording or
+///   bridging the events, and needs the `serde` feature. The `#[serde(...)]`
+///   attributes of the type are used.
+/// - `crate = "path"` sets the path of this crate, `::ly_events` by default,
+///   e.g. `"rustly::events"`
+///
+/// ```
+/// # use ly_events::channel::Overflow;
+/// # use ly_events::registry::{Event, Events};
+/// #[derive(Event, Debug, PartialEq)]
+/// #[event(capacity = 64, overflow = DropNewest, meta)]
+/// struct Damage(u32);
+///
+/// let events = Events::default();
+/// let channel = events.add_event::<Damage>();
+/// assert_eq!(channel.capacity(), Some(64));
+/// assert!(channel.has_meta());
+/// ```
+pub trait Event: Send + Sync + Sized + 'static
+{
+	/// How many flushes a [`RingEventChannel`] of the event keeps readable
+	const RETENTION: Retention = Retention::Flushes(1);
+
+	/// Creates a channel, configured as declared by the event
+	fn channel() -> SyncEventChannel<Self> { SyncEventChannel::default() }
+
+	/// Creates a ring channel with the [`RETENTION`](Event::RETENTION) of the
+	/// event
+	fn ring_channel() -> RingEventChannel<Self> { RingEventChannel::new(Self::RETENTION) }
+}
+
+/// Registry of one [`SyncEventChannel`] per event type
+///
+/// Channels are looked up by the `TypeId` of the event, so readers and
+/// writers can be had by anyone knowing the event type, without knowing who
+/// registered the channel.
+#[derive(Default)]
+pub struct Events
+{
+	channels: RwLock<HashMap<TypeId, Arc<dyn AnyChannel>>>,
+}
+
+impl Events
+{
+	/// Registers a channel for events of type `T`, unless there already is one
+	///
+	/// Returns the registered channel.
+	pub fn add<T>(&self) -> Arc<SyncEventChannel<T>>
+	where
+		T: Send + Sync + 'static,
+	{
+		self.add_with(SyncEventChannel::default)
+	}
+
+	/// Registers the channel of an [`Event`], configured as the event declares,
+	/// unless there already is one
+	///
+	/// Returns the registered channel.
+	pub fn add_event<T>(&self) -> Arc<SyncEventChannel<T>>
+	where
+		T: Event,
+	{
+		self.add_with(T::channel)
+	}
+
+	/// Registers a bounded channel for events of type `T`, unless there
+	/// already is one
+	///
+	/// See [`SyncEventChannel::bounded`]. Returns the registered channel,
+	/// which keeps its own capacity if it was already registered.
+	pub fn add_bounded<T>(&self, capacity: usize, overflow: Overflow) -> Arc<SyncEventChannel<T>>
+	where
+		T: Send + Sync + 'static,
+	{
+		self.add_with(|| SyncEventChannel::bounded(capacity, overflow))
+	}
+
+	/// Registers the channel made by `new` for events of type `T`, unless
+	/// there already is one
+	///
+	/// For channels made in other ways than the default, e.g.
+	/// [`with_meta`](SyncEventChannel::with_meta). Returns the registered
+	/// channel.
+	pub fn add_with<T, F>(&self, new: F) -> Arc<SyncEventChannel<T>>
+	where
+		T: Send + Sync + 'static,
+		F: FnOnce() -> SyncEventChannel<T>,
+	{
+		if let Some(channel) = self.channel::<T>() {
+			return channel;
+		}
+		let channel = Arc::clone(
+			self.channels
+				.write()
+				.entry(TypeId::of::<T>())
+				.or_insert_with(|| Arc::new(new())),
+		);
+		downcast(channel).unwrap()
+	}
+
+	/// Gets the channel of events of type `T`, if registered
+	pub fn channel<T>(&self) -> Option<Arc<SyncEventChannel<T>>>
+	where
+		T: Send + Sync + 'static,
+	{
+		self.get_by_id(TypeId::of::<T>()).and_then(downcast)
+	}
+
+	/// Gets the channel of the event type with the `TypeId`, if registered
+	pub fn get_by_id(&self, type_id: TypeId) -> Option<Arc<dyn AnyChannel>>
+	{
+		self.channels.read().get(&type_id).cloned()
+	}
+
+	/// Checks if there is a channel for events of type `T`
+	pub fn contains<T>(&self) -> bool
+	where
+		T: 'static,
+	{
+		self.channels.read().contains_key(&TypeId::of::<T>())
+	}
+
+	/// Gets a writer to the channel of events of type `T`, if registered
+	pub fn get_writer<T>(&self) -> Option<OwnedSyncEventWriter<T>>
+	where
+		T: Send + Sync + 'static,
+	{
+		self.channel::<T>()
+			.map(|channel| channel.get_owned_writer())
+	}
+
+	/// Gets a reader of the channel of events of type `T`, if registered and
+	/// not drained
+	pub fn get_reader<T>(&self) -> Option<OwnedSyncEventReader<T>>
+	where
+		T: Send + Sync + 'static,
+	{
+		self.channel::<T>()
+			.and_then(|channel| channel.get_owned_reader().ok())
+	}
+
+	/// Flushes the channels of all registered event types
+	pub fn flush(&self)
+	{
+		// don't hold the lock while flushing, so channels may be added
+		for channel in self.all_channels() {
+			channel.flush();
+		}
+	}
+
+	/// Takes a snapshot of the statistics of all registered channels
+	///
+	/// The statistics are sorted by channel id, the order the channels were
+	/// created in.
+	pub fn stats(&self) -> Vec<ChannelStats>
+	{
+		let mut stats: Vec<_> = self
+			.all_channels()
+			.iter()
+			.map(|channel| channel.stats())
+			.collect();
+		stats.sort_by_key(|stats| stats.channel_id);
+		stats
+	}
+
+	/// Logs the statistics of all registered channels
+	pub fn log_stats(&self)
+	{
+		for stats in self.stats() {
+			core_info!("{}", stats);
+		}
+	}
+
+	fn all_channels(&self) -> Vec<Arc<dyn AnyChannel>>
+	{
+		self.channels.read().values().cloned().collect()
+	}
+}
+
+fn downcast<T>(channel: Arc<dyn AnyChannel>) -> Option<Arc<SyncEventChannel<T>>>
+where
+	T: Send + Sync + 'static,
+{
+	channel.into_any().downcast().ok()
+}
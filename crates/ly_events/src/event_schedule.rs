@@ -0,0 +1,141 @@
+use parking_lot::{const_mutex, Mutex};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
+
+/// When a scheduled event is due
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Due
+{
+	/// Due once the scheduler is advanced past this point in time, as measured
+	/// by the clock advancing the scheduler
+	At(Instant),
+	/// Due once the scheduler is advanced to this frame
+	Frame(u64),
+}
+
+struct Scheduled
+{
+	due: Due,
+	inject: Box<dyn FnOnce() + Send>,
+}
+
+/// Holds events that should be sent to their channels at a later time
+///
+/// The scheduler does not run by itself, it has to be advanced by whoever
+/// owns the frame clock, usually the `App`. Time is measured by that clock,
+/// not the wall clock, so that replaying the same ticks gives the same events.
+pub struct EventScheduler
+{
+	frame: AtomicU64,
+	now: Mutex<Option<Instant>>,
+	pending: Mutex<Vec<Scheduled>>,
+}
+
+static SCHEDULER: EventScheduler = EventScheduler {
+	frame: AtomicU64::new(0),
+	now: const_mutex(None),
+	pending: const_mutex(Vec::new()),
+};
+
+/// Get the global scheduler, used by the delayed sends of the writers
+pub fn scheduler() -> &'static EventScheduler { &SCHEDULER }
+
+impl EventScheduler
+{
+	/// The frame the scheduler was last advanced to
+	pub fn current_frame(&self) -> u64 { self.frame.load(Ordering::Acquire) }
+
+	/// The time the scheduler was last advanced to
+	///
+	/// Falls back to the wall clock if it has never been advanced.
+	pub fn now(&self) -> Instant { self.now.lock().unwrap_or_else(Instant::now) }
+
+	/// Number of events waiting to be injected
+	pub fn pending(&self) -> usize { self.pending.lock().len() }
+
+	/// Advances the scheduler to `frame` at time `now`
+	///
+	/// Injects all events that are due into their channels, in the order
+	/// they were scheduled. Returns the number of injected events.
+	pub fn advance(&self, frame: u64, now: Instant) -> usize
+	{
+		self.frame.store(frame, Ordering::Release);
+		*self.now.lock() = Some(now);
+		let due = {
+			let mut pending = self.pending.lock();
+			let mut due = Vec::new();
+			let mut i = 0;
+			while i < pending.len() {
+				let is_due = match pending[i].due {
+					Due::At(instant) => instant <= now,
+					Due::Frame(f) => f <= frame,
+				};
+				if is_due {
+					due.push(pending.remove(i));
+				}
+				else {
+					i += 1;
+				}
+			}
+			due
+		};
+
+		// inject outside the lock, sending may wake other threads
+		let injected = due.len();
+		for scheduled in due {
+			(scheduled.inject)();
+		}
+		injected
+	}
+
+	/// Schedule a closure injecting an event, run once `due` is reached
+	pub(crate) fn schedule(&self, due: Due, inject: Box<dyn FnOnce() + Send>)
+	{
+		self.pending.lock().push(Scheduled { due, inject });
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+	use std::sync::atomic::AtomicUsize;
+	use std::sync::Arc;
+	use std::time::Duration;
+
+	#[test]
+	fn schedule_001()
+	{
+		let scheduler = EventScheduler {
+			frame: AtomicU64::new(0),
+			now: Mutex::new(None),
+			pending: Mutex::new(Vec::new()),
+		};
+		let count = Arc::new(AtomicUsize::new(0));
+		let start = Instant::now();
+
+		let c = Arc::clone(&count);
+		scheduler.schedule(
+			Due::Frame(2),
+			Box::new(move || {
+				c.fetch_add(1, Ordering::Relaxed);
+			}),
+		);
+		let c = Arc::clone(&count);
+		scheduler.schedule(
+			Due::At(start + Duration::from_millis(10)),
+			Box::new(move || {
+				c.fetch_add(10, Ordering::Relaxed);
+			}),
+		);
+
+		assert_eq!(scheduler.advance(1, start), 0);
+		assert_eq!(scheduler.advance(2, start), 1);
+		assert_eq!(count.load(Ordering::Relaxed), 1);
+		assert_eq!(scheduler.pending(), 1);
+
+		assert_eq!(scheduler.advance(3, start + Duration::from_millis(10)), 1);
+		assert_eq!(count.load(Ordering::Relaxed), 11);
+		assert_eq!(scheduler.pending(), 0);
+	}
+}
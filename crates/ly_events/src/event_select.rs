@@ -0,0 +1,138 @@
+use crossbeam::sync::Parker;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::sync_event_channel::{add_waker_all, park_checked, parker_waker, EventWaiter};
+
+/// Why [`Select`] did not select a waiter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelectError
+{
+	/// No waiter had new events before the timeout
+	Timeout,
+	/// No waiter has new events, and none of them can be woken
+	Disconnected,
+}
+
+impl fmt::Display for SelectError
+{
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+	{
+		match self {
+			SelectError::Timeout => write!(f, "timed out waiting for new events"),
+			SelectError::Disconnected => write!(f, "no writers left to send events"),
+		}
+	}
+}
+
+impl std::error::Error for SelectError {}
+
+/// Waits for any of several readers, and tells which one has new events
+///
+/// Readers are added with [`add`](Select::add), which gives the index that
+/// [`select`](Select::select) returns when that reader has un-flushed events.
+/// When several readers have new events, they are selected in turns, so that
+/// a busy channel does not starve the others.
+///
+/// ```
+/// # use ly_events::channel::{Select, SyncEventChannel};
+/// let buttons = SyncEventChannel::<u32>::default();
+/// let keys = SyncEventChannel::<char>::default();
+/// let button_reader = buttons.get_reader().unwrap();
+/// let key_reader = keys.get_reader().unwrap();
+///
+/// let mut select = Select::new();
+/// let button = select.add(&button_reader);
+/// let key = select.add(&key_reader);
+///
+/// let key_writer = keys.get_writer();
+/// key_writer.send('a');
+/// assert_eq!(select.select(), Ok(key));
+/// ```
+#[derive(Default)]
+pub struct Select<'a>
+{
+	waiters: Vec<&'a dyn EventWaiter>,
+	/// index to start looking from, for fairness
+	next: usize,
+}
+
+impl<'a> Select<'a>
+{
+	pub fn new() -> Self { Self::default() }
+
+	/// Adds the waiter, returning its index
+	pub fn add(&mut self, waiter: &'a dyn EventWaiter) -> usize
+	{
+		self.waiters.push(waiter);
+		self.waiters.len() - 1
+	}
+
+	/// Gets the indices of all waiters with new events
+	///
+	/// The indices are in the order that they would be selected.
+	pub fn ready(&self) -> Vec<usize>
+	{
+		self.indices()
+			.filter(|&i| self.waiters[i].has_new())
+			.collect()
+	}
+
+	/// Selects a waiter with new events, without waiting
+	pub fn try_select(&mut self) -> Option<usize>
+	{
+		let selected = self.indices().find(|&i| self.waiters[i].has_new())?;
+		self.next = selected + 1;
+		Some(selected)
+	}
+
+	/// Waits for any waiter to have new events, and selects it
+	///
+	/// Returns [`SelectError::Disconnected`] if every waiter is
+	/// [disconnected](EventWaiter::is_disconnected): no new events, and no
+	/// writers left to send them. Channels that never had a writer wait for
+	/// their first one.
+	pub fn select(&mut self) -> Result<usize, SelectError> { self.select_until(None) }
+
+	/// Like [`select`](Select::select), but gives up after `timeout`
+	pub fn select_timeout(&mut self, timeout: Duration) -> Result<usize, SelectError>
+	{
+		self.select_until(Some(Instant::now() + timeout))
+	}
+
+	fn select_until(&mut self, deadline: Option<Instant>) -> Result<usize, SelectError>
+	{
+		loop {
+			if let Some(selected) = self.try_select() {
+				return Ok(selected);
+			}
+			if self.waiters.iter().all(|w| w.is_disconnected()) {
+				return Err(SelectError::Disconnected);
+			}
+
+			let timeout = match deadline {
+				Some(deadline) => {
+					let now = Instant::now();
+					if now >= deadline {
+						return Err(SelectError::Timeout);
+					}
+					deadline - now
+				}
+				None => Duration::from_secs(2),
+			};
+			let p = Parker::new();
+			let waker = parker_waker(&p);
+			if add_waker_all(&self.waiters, &waker) {
+				park_checked(&p, Some(timeout), || add_waker_all(&self.waiters, &waker));
+			}
+		}
+	}
+
+	/// Indices of all waiters, starting from the next in turn
+	fn indices(&self) -> impl Iterator<Item = usize>
+	{
+		let len = self.waiters.len();
+		let next = self.next;
+		(0..len).map(move |i| (next + i) % len)
+	}
+}
@@ -1,43 +1,199 @@
 use crossbeam::sync::{Parker, Unparker};
 use parking_lot::Mutex;
+use std::time::{Duration, Instant};
 
+/// How a [`SignalEvent`] remembers signals
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SignalMode
+{
+	/// Waits until the next signal, a signal without waiters is lost
+	#[default]
+	Pulse,
+	/// Stays set when signaled, until [`reset`](SignalEvent::reset), so waits
+	/// return directly
+	ManualReset,
+	/// Stays set when signaled, until a wait returns, so each signal lets one
+	/// wait through
+	AutoReset,
+	/// Counts the signals, each wait takes one
+	Counting,
+}
+
+/// Wakes waiting threads when signaled
+///
+/// See the [`signal`](crate::signal) module.
 #[derive(Default)]
 pub struct SignalEvent
 {
-	waiters: Mutex<Vec<Unparker>>,
+	mode: SignalMode,
+	state: Mutex<State>,
+}
+
+#[derive(Default)]
+struct State
+{
+	/// Number of signals, for pulse waits to see a signal they missed
+	generation: u64,
+	/// Signals not yet taken by a wait, at most one unless counting
+	permits: usize,
+	waiters: Parked,
 }
 
 impl SignalEvent
 {
+	pub fn new(mode: SignalMode) -> Self
+	{
+		SignalEvent {
+			mode,
+			state: Mutex::default(),
+		}
+	}
+
+	pub fn mode(&self) -> SignalMode { self.mode }
+
 	/// Signal waiting threads to wake
+	///
+	/// Wakes all waiting threads, which in [`SignalMode::AutoReset`] and
+	/// [`SignalMode::Counting`] then compete for the signal.
 	pub fn signal(&self)
 	{
-		let mut waiters = self.waiters.lock();
-		signal_waiters(&mut waiters);
+		let mut state = self.state.lock();
+		state.generation += 1;
+		match self.mode {
+			SignalMode::Pulse => (),
+			SignalMode::ManualReset | SignalMode::AutoReset => state.permits = 1,
+			SignalMode::Counting => state.permits += 1,
+		}
+		state.waiters.unpark_all();
 	}
 
+	/// Clears the signals not yet taken by a wait
+	pub fn reset(&self) { self.state.lock().permits = 0; }
+
+	/// Checks if a wait would return without blocking
+	pub fn is_set(&self) -> bool { self.state.lock().permits > 0 }
+
 	/// Wait for signal
-	pub fn wait(&self)
+	pub fn wait(&self) { self.wait_signal(None); }
+
+	/// Wait for signal, for at most `timeout`
+	///
+	/// Returns false if timed out.
+	pub fn wait_timeout(&self, timeout: Duration) -> bool
+	{
+		self.wait_signal(Some(Instant::now() + timeout))
+	}
+
+	/// Waits until `predicate` is true, checking it now and after every signal
+	///
+	/// Does not take a signal, whatever the mode. The predicate is called with
+	/// the signal locked, so a thread changing what it checks and then
+	/// signaling is never missed, and it must not use the signal itself.
+	pub fn wait_until<F>(&self, mut predicate: F)
+	where
+		F: FnMut() -> bool,
+	{
+		self.wait_for(None, |_| predicate());
+	}
+
+	fn wait_signal(&self, deadline: Option<Instant>) -> bool
+	{
+		let generation = self.state.lock().generation;
+		match self.mode {
+			SignalMode::Pulse => self.wait_for(deadline, |state| state.generation != generation),
+			SignalMode::ManualReset => self.wait_for(deadline, |state| state.permits > 0),
+			SignalMode::AutoReset | SignalMode::Counting => self.wait_for(deadline, |state| {
+				let taken = state.permits > 0;
+				state.permits -= taken as usize;
+				taken
+			}),
+		}
+	}
+
+	/// Parks until `done`, which is checked with the state locked
+	fn wait_for<F>(&self, deadline: Option<Instant>, mut done: F) -> bool
+	where
+		F: FnMut(&mut State) -> bool,
 	{
-		let p = Parker::new();
-		add_waiter(&mut self.waiters.lock(), &p);
-		p.park();
+		park_until(
+			&self.state,
+			deadline,
+			|state| &mut state.waiters,
+			|state| done(state).then_some(()),
+		)
+		.is_some()
 	}
 }
 
-pub(crate) fn signal_waiters(waiters: &mut Vec<Unparker>)
+/// Threads parked until woken
+///
+/// A thread is added at most once, and is removed when woken or when it stops
+/// waiting, so timed out waits don't pile up.
+#[derive(Default)]
+pub(crate) struct Parked
+{
+	/// The parkers are told apart by address, they stay in place while parked
+	unparkers: Vec<(usize, Unparker)>,
+}
+
+impl Parked
 {
-	for waiter in waiters.iter() {
-		waiter.unpark();
+	/// Wakes all the parked threads
+	pub(crate) fn unpark_all(&mut self)
+	{
+		for (_, unparker) in self.unparkers.drain(..) {
+			unparker.unpark();
+		}
+	}
+
+	#[cfg(test)]
+	pub(crate) fn is_empty(&self) -> bool { self.unparkers.is_empty() }
+
+	fn add(&mut self, p: &Parker)
+	{
+		let id = p as *const Parker as usize;
+		if self.unparkers.iter().all(|(other, _)| *other != id) {
+			self.unparkers.push((id, p.unparker().clone()));
+		}
+	}
+
+	fn remove(&mut self, p: &Parker)
+	{
+		let id = p as *const Parker as usize;
+		self.unparkers.retain(|(other, _)| *other != id);
 	}
-	waiters.clear();
 }
 
-pub(crate) fn add_waiter<'a>(waiters: &mut Vec<Unparker>, p: &'a Parker) -> &'a Parker
+/// Parks until `done` gives a result, or until the deadline
+///
+/// `done` is checked with `state` locked, now and every time the thread is
+/// woken from the waiters given by `waiters`. Returns None if timed out.
+pub(crate) fn park_until<S, R, W, F>(
+	state: &Mutex<S>,
+	deadline: Option<Instant>,
+	waiters: W,
+	mut done: F,
+) -> Option<R>
+where
+	W: Fn(&mut S) -> &mut Parked,
+	F: FnMut(&mut S) -> Option<R>,
 {
-	let u = p.unparker().clone();
-	waiters.push(u);
-	p
+	let p = Parker::new();
+	loop {
+		{
+			let mut state = state.lock();
+			let result = done(&mut state);
+			if result.is_some() || deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+				waiters(&mut state).remove(&p);
+				return result;
+			}
+			waiters(&mut state).add(&p);
+		}
+		match deadline {
+			Some(deadline) => p.park_deadline(deadline),
+			None => p.park(),
+		}
+	}
 }
 
 #[cfg(test)]
@@ -54,13 +210,16 @@ mod tests
 	{
 		let total = Arc::new(Mutex::new(0));
 		let t = Arc::clone(&total);
-		let signal = Arc::new(SignalEvent::default());
+		let signal = Arc::new(SignalEvent::new(SignalMode::AutoReset));
 		let s = Arc::clone(&signal);
+		let added = Arc::new(SignalEvent::new(SignalMode::AutoReset));
+		let a = Arc::clone(&added);
 
 		let adder = thread::spawn(move || {
 			for _ in 1..3 {
 				s.wait();
 				t.lock().add_assign(1);
+				a.signal();
 			}
 		});
 
@@ -69,16 +228,78 @@ mod tests
 			assert!(tlock.eq(&0));
 		}
 
-		// current implementation doesn't deal with signals before waits
-		thread::sleep(Duration::from_millis(10));
-
+		// signals before the wait are kept, no need to wait for the adder
 		for i in 1..3 {
 			signal.signal();
-			thread::sleep(Duration::from_millis(10));
+			added.wait();
 			{
 				assert!(total.lock().eq(&i));
 			}
 		}
 		adder.join().unwrap();
 	}
+
+	#[test]
+	fn signal_002()
+	{
+		let pulse = SignalEvent::default();
+		pulse.signal();
+		assert!(!pulse.wait_timeout(Duration::from_millis(1)));
+
+		let manual = SignalEvent::new(SignalMode::ManualReset);
+		manual.signal();
+		manual.wait();
+		assert!(manual.wait_timeout(Duration::ZERO));
+		manual.reset();
+		assert!(!manual.wait_timeout(Duration::from_millis(1)));
+
+		let auto = SignalEvent::new(SignalMode::AutoReset);
+		auto.signal();
+		auto.signal();
+		assert!(auto.wait_timeout(Duration::ZERO));
+		assert!(!auto.is_set());
+
+		let counting = SignalEvent::new(SignalMode::Counting);
+		for _ in 0..3 {
+			counting.signal();
+		}
+		for _ in 0..3 {
+			counting.wait();
+		}
+		assert!(!counting.wait_timeout(Duration::from_millis(1)));
+	}
+
+	#[test]
+	/// test timed out waits not being left as waiters
+	fn signal_004()
+	{
+		let signal = SignalEvent::default();
+		for _ in 0..3 {
+			assert!(!signal.wait_timeout(Duration::from_millis(1)));
+		}
+		assert!(signal.state.lock().waiters.is_empty());
+	}
+
+	#[test]
+	fn signal_003()
+	{
+		let count = Arc::new(Mutex::new(0));
+		let signal = Arc::new(SignalEvent::default());
+		let workers = (0..4)
+			.map(|_| {
+				let count = Arc::clone(&count);
+				let signal = Arc::clone(&signal);
+				thread::spawn(move || {
+					count.lock().add_assign(1);
+					signal.signal();
+				})
+			})
+			.collect::<Vec<_>>();
+
+		signal.wait_until(|| *count.lock() == 4);
+		assert_eq!(*count.lock(), 4);
+		for worker in workers {
+			worker.join().unwrap();
+		}
+	}
 }
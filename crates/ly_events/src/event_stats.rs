@@ -0,0 +1,66 @@
+use std::fmt;
+use std::time::Duration;
+
+/// Statistics of a [`SyncEventChannel`](crate::channel::SyncEventChannel)
+///
+/// A snapshot taken by
+/// [`SyncEventChannel::stats`](crate::channel::SyncEventChannel::stats), or
+/// for all registered channels by
+/// [`Events::stats`](crate::registry::Events::stats). Counts are since the
+/// channel was created.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChannelStats
+{
+	pub channel_id: usize,
+	/// Name of the event type
+	pub event_type: &'static str,
+	/// Events sent and not dropped, including the un-flushed events
+	pub sent: u64,
+	/// Events made readable by flushes, after coalescing
+	pub flushed: u64,
+	/// Events dropped because the channel was full
+	pub dropped: u64,
+	/// Number of flushes
+	pub flushes: u64,
+	/// Un-flushed events
+	pub pending: usize,
+	/// Most un-flushed events taken by a flush
+	pub peak_pending: usize,
+	pub writers: usize,
+	pub readers: usize,
+	/// Number of flushes each reader has not read, zero for a reader that has
+	/// read the last flush
+	pub reader_lag: Vec<u64>,
+	/// Time spent waiting for the lock of the flushed events, and by writers
+	/// waiting for room in a full bounded channel
+	pub blocked: Duration,
+}
+
+impl ChannelStats
+{
+	/// Lag of the reader that is furthest behind
+	pub fn max_lag(&self) -> u64 { self.reader_lag.iter().copied().max().unwrap_or(0) }
+}
+
+impl fmt::Display for ChannelStats
+{
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+	{
+		write!(
+			f,
+			"channel {} ({}): {} sent, {} flushed, {} dropped, {} pending (peak {}), {} writers, \
+			 {} readers (max lag {}), blocked {:?}",
+			self.channel_id,
+			self.event_type,
+			self.sent,
+			self.flushed,
+			self.dropped,
+			self.pending,
+			self.peak_pending,
+			self.writers,
+			self.readers,
+			self.max_lag(),
+			self.blocked
+		)
+	}
+}
@@ -2,7 +2,13 @@
 
 use ly_input::{Key, MouseButton};
 
-#[derive(Debug)]
+use crate::event_coalesce::{Coalesce, LatestBy, MergeBy};
+use crate::event_registry::Events;
+use crate::owned_event_channel::OwnedSyncEventWriter;
+use crate::sync_event_channel::SyncEventChannel;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 /// Buttons, mouse and keyboard
 pub enum ButtonEvent
 {
@@ -13,7 +19,8 @@ pub enum ButtonEvent
 	KeyReleased(Key),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 /// Event related to moving mouse
 pub enum MouseEvent
 {
@@ -31,9 +38,216 @@ pub enum MouseEvent
 	MouseMove(f64, f64),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum WindowEvent
 {
 	WindowResized(usize, usize),
 	WindowClose,
 }
+
+/// A key was pressed
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KeyPressed(pub Key);
+
+/// A key was released
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KeyReleased(pub Key);
+
+/// A mouse button was pressed
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MousePressed(pub MouseButton);
+
+/// A mouse button was released
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MouseReleased(pub MouseButton);
+
+/// The mouse wheel was scrolled, by a delta
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MouseScrolled(pub f64, pub f64);
+
+/// The cursor moved to pixel coordinates, see [`MouseEvent::CursorMove`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CursorMoved(pub f64, pub f64);
+
+/// The mouse moved, by a raw delta, see [`MouseEvent::MouseMove`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MouseMoved(pub f64, pub f64);
+
+/// The window was resized, to a width and height
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Resized(pub usize, pub usize);
+
+/// Closing the window was requested
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CloseRequested;
+
+/// Sends grouped events to the channels of the fine-grained events
+///
+/// For the window to feed both the grouped channels, like [`ButtonEvent`],
+/// and the fine-grained ones, like [`KeyPressed`], which keep metadata to be
+/// read together by a [`GroupReader`](crate::channel::GroupReader). Events
+/// given back by a full or closed channel are dropped.
+pub struct SplitWriter
+{
+	key_pressed: OwnedSyncEventWriter<KeyPressed>,
+	key_released: OwnedSyncEventWriter<KeyReleased>,
+	mouse_pressed: OwnedSyncEventWriter<MousePressed>,
+	mouse_released: OwnedSyncEventWriter<MouseReleased>,
+	mouse_scrolled: OwnedSyncEventWriter<MouseScrolled>,
+	cursor_moved: OwnedSyncEventWriter<CursorMoved>,
+	mouse_moved: OwnedSyncEventWriter<MouseMoved>,
+	resized: OwnedSyncEventWriter<Resized>,
+	close_requested: OwnedSyncEventWriter<CloseRequested>,
+}
+
+impl SplitWriter
+{
+	/// Registers the channels of the fine-grained events, keeping metadata,
+	/// unless already registered
+	pub fn new(events: &Events) -> Self
+	{
+		fn writer<T>(events: &Events) -> OwnedSyncEventWriter<T>
+		where
+			T: Send + Sync + 'static,
+		{
+			events
+				.add_with(|| SyncEventChannel::default().with_meta())
+				.get_owned_writer()
+		}
+
+		SplitWriter {
+			key_pressed: writer(events),
+			key_released: writer(events),
+			mouse_pressed: writer(events),
+			mouse_released: writer(events),
+			mouse_scrolled: writer(events),
+			cursor_moved: writer(events),
+			mouse_moved: writer(events),
+			resized: writer(events),
+			close_requested: writer(events),
+		}
+	}
+
+	pub fn button(&self, event: &ButtonEvent)
+	{
+		match *event {
+			ButtonEvent::MouseScroll(x, y) => self.mouse_scrolled.send(MouseScrolled(x, y)).ok(),
+			ButtonEvent::MousePressed(b) => self.mouse_pressed.send(MousePressed(b)).ok(),
+			ButtonEvent::MouseReleased(b) => self.mouse_released.send(MouseReleased(b)).ok(),
+			ButtonEvent::KeyPressed(k) => self.key_pressed.send(KeyPressed(k)).ok(),
+			ButtonEvent::KeyReleased(k) => self.key_released.send(KeyReleased(k)).ok(),
+		};
+	}
+
+	pub fn mouse(&self, event: &MouseEvent)
+	{
+		match *event {
+			MouseEvent::CursorMove(x, y) => self.cursor_moved.send(CursorMoved(x, y)).ok(),
+			MouseEvent::MouseMove(x, y) => self.mouse_moved.send(MouseMoved(x, y)).ok(),
+		};
+	}
+
+	pub fn window(&self, event: &WindowEvent)
+	{
+		match *event {
+			WindowEvent::WindowResized(w, h) => self.resized.send(Resized(w, h)).ok(),
+			WindowEvent::WindowClose => self.close_requested.send(CloseRequested).ok(),
+		};
+	}
+}
+
+impl From<KeyPressed> for ButtonEvent
+{
+	fn from(e: KeyPressed) -> Self { ButtonEvent::KeyPressed(e.0) }
+}
+
+impl From<KeyReleased> for ButtonEvent
+{
+	fn from(e: KeyReleased) -> Self { ButtonEvent::KeyReleased(e.0) }
+}
+
+impl From<MousePressed> for ButtonEvent
+{
+	fn from(e: MousePressed) -> Self { ButtonEvent::MousePressed(e.0) }
+}
+
+impl From<MouseReleased> for ButtonEvent
+{
+	fn from(e: MouseReleased) -> Self { ButtonEvent::MouseReleased(e.0) }
+}
+
+impl From<MouseScrolled> for ButtonEvent
+{
+	fn from(e: MouseScrolled) -> Self { ButtonEvent::MouseScroll(e.0, e.1) }
+}
+
+impl From<CursorMoved> for MouseEvent
+{
+	fn from(e: CursorMoved) -> Self { MouseEvent::CursorMove(e.0, e.1) }
+}
+
+impl From<MouseMoved> for MouseEvent
+{
+	fn from(e: MouseMoved) -> Self { MouseEvent::MouseMove(e.0, e.1) }
+}
+
+impl From<Resized> for WindowEvent
+{
+	fn from(e: Resized) -> Self { WindowEvent::WindowResized(e.0, e.1) }
+}
+
+impl From<CloseRequested> for WindowEvent
+{
+	fn from(_: CloseRequested) -> Self { WindowEvent::WindowClose }
+}
+
+impl ButtonEvent
+{
+	/// Coalescing rule summing the `MouseScroll` deltas of a flush
+	///
+	/// Presses and releases are kept, as their order matters.
+	pub fn coalescing() -> impl Coalesce<ButtonEvent>
+	{
+		MergeBy::new(
+			|e: &ButtonEvent| matches!(e, ButtonEvent::MouseScroll(..)).then_some(()),
+			|sum, e| {
+				if let (ButtonEvent::MouseScroll(x, y), ButtonEvent::MouseScroll(dx, dy)) = (sum, e)
+				{
+					*x += dx;
+					*y += dy;
+				}
+			},
+		)
+	}
+}
+
+impl MouseEvent
+{
+	/// Coalescing rule summing the `MouseMove` deltas, and keeping the latest
+	/// `CursorMove`, of a flush
+	pub fn coalescing() -> impl Coalesce<MouseEvent>
+	{
+		MergeBy::new(
+			|e: &MouseEvent| matches!(e, MouseEvent::MouseMove(..)).then_some(()),
+			|sum, e| {
+				if let (MouseEvent::MouseMove(x, y), MouseEvent::MouseMove(dx, dy)) = (sum, e) {
+					*x += dx;
+					*y += dy;
+				}
+			},
+		)
+		.then(LatestBy::new(|e: &MouseEvent| {
+			matches!(e, MouseEvent::CursorMove(..)).then_some(())
+		}))
+	}
+}
@@ -0,0 +1,272 @@
+use parking_lot::Mutex;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::event_signal::{park_until, Parked};
+
+/// Why a [`FrameSync`] wait returned without a frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameSyncError
+{
+	/// The frame sync is closed, and the frame will never be produced
+	Closed,
+	/// No frame before the timeout
+	Timeout,
+}
+
+impl fmt::Display for FrameSyncError
+{
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+	{
+		match self {
+			FrameSyncError::Closed => write!(f, "frame sync is closed"),
+			FrameSyncError::Timeout => write!(f, "timed out waiting for frame"),
+		}
+	}
+}
+
+impl std::error::Error for FrameSyncError {}
+
+/// Hands frames from a producer, like the main loop, to worker threads
+///
+/// The producer calls [`produce`](FrameSync::produce) when a frame is ready,
+/// and each [`FrameWorker`] waits for it, works on it, and marks it
+/// [`consumed`](FrameWorker::consume). The producer may run ahead of the
+/// slowest worker by at most `in_flight` frames, after that `produce` waits.
+///
+/// Frames are numbered from 0. [`close`](FrameSync::close) wakes everyone,
+/// for shutting down without deadlocks.
+pub struct FrameSync
+{
+	in_flight: u64,
+	state: Mutex<State>,
+}
+
+/// Waits for frames of a [`FrameSync`]
+///
+/// Created by [`FrameSync::get_worker`]. The producer is not held back by
+/// dropped workers.
+pub struct FrameWorker<'a>
+{
+	sync: &'a FrameSync,
+	id: usize,
+}
+
+struct State
+{
+	/// Number of produced frames
+	produced: u64,
+	/// Number of consumed frames, by worker id
+	consumed: Vec<(usize, u64)>,
+	next_id: usize,
+	closed: bool,
+	producers: Parked,
+	workers: Parked,
+}
+
+impl State
+{
+	fn in_flight(&self) -> u64
+	{
+		let slowest = self.consumed.iter().map(|(_, consumed)| *consumed).min();
+		self.produced - slowest.unwrap_or(self.produced)
+	}
+
+	fn consumed_mut(&mut self, id: usize) -> &mut u64
+	{
+		let i = self.consumed.iter().position(|(worker, _)| *worker == id);
+		&mut self.consumed[i.expect("frame worker is registered")].1
+	}
+}
+
+impl FrameSync
+{
+	/// Creates a frame sync letting the producer run `in_flight` frames ahead
+	///
+	/// Panics if `in_flight` is 0.
+	pub fn new(in_flight: usize) -> Self
+	{
+		assert!(
+			in_flight > 0,
+			"frame sync needs at least one frame in flight"
+		);
+		FrameSync {
+			in_flight: in_flight as u64,
+			state: Mutex::new(State {
+				produced: 0,
+				consumed: Vec::new(),
+				next_id: 0,
+				closed: false,
+				producers: Parked::default(),
+				workers: Parked::default(),
+			}),
+		}
+	}
+
+	pub fn in_flight(&self) -> usize { self.in_flight as usize }
+
+	/// Number of produced frames, and the number of the next frame
+	pub fn produced(&self) -> u64 { self.state.lock().produced }
+
+	/// Registers a worker, which waits for the next produced frame
+	pub fn get_worker(&self) -> FrameWorker<'_>
+	{
+		let mut state = self.state.lock();
+		let id = state.next_id;
+		state.next_id += 1;
+		let produced = state.produced;
+		state.consumed.push((id, produced));
+		FrameWorker { sync: self, id }
+	}
+
+	/// Produces the next frame, and wakes the workers waiting for it
+	///
+	/// Waits while `in_flight` frames are not consumed by all workers. Returns
+	/// the frame number.
+	pub fn produce(&self) -> Result<u64, FrameSyncError> { self.produce_until(None) }
+
+	/// Like [`produce`](FrameSync::produce), waiting for at most `timeout`
+	pub fn produce_timeout(&self, timeout: Duration) -> Result<u64, FrameSyncError>
+	{
+		self.produce_until(Some(Instant::now() + timeout))
+	}
+
+	/// Closes the frame sync, waking the producer and all workers
+	///
+	/// Frames produced before are still handed to the workers, after that
+	/// waits return [`FrameSyncError::Closed`].
+	pub fn close(&self)
+	{
+		let mut state = self.state.lock();
+		state.closed = true;
+		state.producers.unpark_all();
+		state.workers.unpark_all();
+	}
+
+	pub fn is_closed(&self) -> bool { self.state.lock().closed }
+
+	fn produce_until(&self, deadline: Option<Instant>) -> Result<u64, FrameSyncError>
+	{
+		self.wait(
+			deadline,
+			|state| &mut state.producers,
+			|state| {
+				if state.closed {
+					return Some(Err(FrameSyncError::Closed));
+				}
+				if state.in_flight() >= self.in_flight {
+					return None;
+				}
+				state.produced += 1;
+				state.workers.unpark_all();
+				Some(Ok(state.produced - 1))
+			},
+		)
+	}
+
+	/// Parks until `done` gives a result, checked with the state locked
+	fn wait<W, F>(
+		&self,
+		deadline: Option<Instant>,
+		waiters: W,
+		done: F,
+	) -> Result<u64, FrameSyncError>
+	where
+		W: Fn(&mut State) -> &mut Parked,
+		F: FnMut(&mut State) -> Option<Result<u64, FrameSyncError>>,
+	{
+		park_until(&self.state, deadline, waiters, done).unwrap_or(Err(FrameSyncError::Timeout))
+	}
+}
+
+impl<'a> FrameWorker<'a>
+{
+	/// Waits for the frame after the last consumed one to be produced
+	///
+	/// Returns the frame number, or [`FrameSyncError::Closed`] if the frame
+	/// sync is closed before.
+	pub fn wait_frame(&self) -> Result<u64, FrameSyncError> { self.wait_frame_until(None) }
+
+	/// Like [`wait_frame`](FrameWorker::wait_frame), waiting for at most
+	/// `timeout`
+	pub fn wait_frame_timeout(&self, timeout: Duration) -> Result<u64, FrameSyncError>
+	{
+		self.wait_frame_until(Some(Instant::now() + timeout))
+	}
+
+	/// Marks all frames up to and including `frame` as consumed, letting the
+	/// producer continue
+	///
+	/// Consuming a later frame than the one waited for skips frames.
+	pub fn consume(&self, frame: u64)
+	{
+		let mut state = self.sync.state.lock();
+		let produced = state.produced;
+		let consumed = state.consumed_mut(self.id);
+		*consumed = (*consumed).max(frame + 1).min(produced);
+		state.producers.unpark_all();
+	}
+
+	/// Number of the next frame to consume
+	pub fn next_frame(&self) -> u64 { *self.sync.state.lock().consumed_mut(self.id) }
+
+	fn wait_frame_until(&self, deadline: Option<Instant>) -> Result<u64, FrameSyncError>
+	{
+		self.sync.wait(
+			deadline,
+			|state| &mut state.workers,
+			|state| {
+				let next = *state.consumed_mut(self.id);
+				if next < state.produced {
+					Some(Ok(next))
+				}
+				else if state.closed {
+					Some(Err(FrameSyncError::Closed))
+				}
+				else {
+					None
+				}
+			},
+		)
+	}
+}
+
+impl<'a> Drop for FrameWorker<'a>
+{
+	fn drop(&mut self)
+	{
+		let mut state = self.sync.state.lock();
+		state.consumed.retain(|(worker, _)| *worker != self.id);
+		state.producers.unpark_all();
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+
+	#[test]
+	/// test timed out waits not being left as waiters
+	fn frame_sync_001()
+	{
+		let sync = FrameSync::new(1);
+		let worker = sync.get_worker();
+		for _ in 0..3 {
+			assert_eq!(
+				worker.wait_frame_timeout(Duration::from_millis(1)),
+				Err(FrameSyncError::Timeout)
+			);
+		}
+		assert_eq!(sync.produce(), Ok(0));
+		for _ in 0..3 {
+			assert_eq!(
+				sync.produce_timeout(Duration::from_millis(1)),
+				Err(FrameSyncError::Timeout)
+			);
+		}
+		let state = sync.state.lock();
+		assert!(state.producers.is_empty());
+		assert!(state.workers.is_empty());
+	}
+}
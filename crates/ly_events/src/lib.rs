@@ -5,30 +5,63 @@
 //!
 //! The most important module is [channel], which is probably why you are here.
 
+// lets the derive macro refer to the crate as `::ly_events` inside it too
+extern crate self as ly_events;
+
+mod async_event_channel;
+mod event_bound;
+#[cfg(all(unix, feature = "serde"))]
+mod event_bridge;
 mod event_channel;
+mod event_close;
+mod event_coalesce;
+mod event_drain;
+mod event_filter;
+mod event_group;
+mod event_meta;
+mod event_registry;
+mod event_schedule;
+mod event_select;
 mod event_signal;
+mod event_stats;
 mod event_types;
+mod frame_sync;
+mod owned_event_channel;
+mod request_channel;
+mod ring_event_channel;
 mod sync_event_channel;
+mod work_queue;
 
 /// Module for sending signal events to waiting threads
 ///
-/// Currently this module only contains [`signal::SignalEvent`]
+/// Contains [`signal::SignalEvent`], and [`signal::FrameSync`] for handing
+/// frames from the main loop to worker processes.
 ///
 /// The signal is [`Sync`], but needs to be wrapped in something
 /// to acually be shared between threads, like [`std::sync::Arc`].
 ///
-/// Waiting always parks current thread until next, whether or not
-/// the event has been signaled before.
+/// What a signal sent before a wait does depends on the
+/// [`SignalMode`](signal::SignalMode):
+/// - `Pulse`, the default, is lost, waiting parks until the next signal
+/// - `ManualReset` keeps the signal set, letting all waits through until reset
+/// - `AutoReset` keeps the signal set, letting one wait through
+/// - `Counting` counts the signals, letting one wait through per signal
+///
+/// [`wait_until`](signal::SignalEvent::wait_until) waits for a condition,
+/// checked on every signal, and
+/// [`wait_timeout`](signal::SignalEvent::wait_timeout) gives up after a
+/// while.
 ///
 /// ### Example
 /// ```
-/// # use ly_events::signal::SignalEvent;
+/// # use ly_events::signal::{SignalEvent, SignalMode};
 /// # use std::sync::Arc;
 /// # use std::sync::atomic::{AtomicBool, Ordering};
 /// # use std::thread;
-/// # use std::time::Duration;
-/// let signal = Arc::new(SignalEvent::default());
+/// let signal = Arc::new(SignalEvent::new(SignalMode::AutoReset));
 /// let s = Arc::clone(&signal);
+/// let done = Arc::new(SignalEvent::new(SignalMode::AutoReset));
+/// let d = Arc::clone(&done);
 /// let running = Arc::new(AtomicBool::new(false));
 /// let r = Arc::clone(&running);
 ///
@@ -36,21 +69,19 @@ mod sync_event_channel;
 ///     // Do stuff before wait
 ///     s.wait();
 ///     r.store(true, Ordering::Relaxed);
-///     s.signal();
+///     d.signal();
 ///     // Do stuff after wake
 /// });
 ///
-/// // do some stuff so that the other thread have entered wait()
-/// thread::sleep(Duration::from_millis(2));
-///
 /// assert_eq!(running.load(Ordering::Relaxed), false);
-/// signal.signal(); // Wake waiting thread
-/// signal.wait();   // Wait for thread to continue
+/// signal.signal(); // Wake the thread, even if it is not waiting yet
+/// done.wait();     // Wait for thread to continue
 /// assert_eq!(running.load(Ordering::Relaxed), true);
 /// ```
 pub mod signal
 {
-	pub use super::event_signal::SignalEvent;
+	pub use super::event_signal::{SignalEvent, SignalMode};
+	pub use super::frame_sync::*;
 }
 
 /// Module for sending events through channels
@@ -91,7 +122,7 @@ pub mod signal
 /// assert_eq!(events, Vec::<&TestEvent>::default(),
 ///     "initial events empty");
 ///
-/// writer.send(event);
+/// writer.send(event).unwrap();
 ///
 /// let events = reader.read().collect::<Vec<&TestEvent>>();
 /// assert_eq!(events, Vec::<&TestEvent>::default(),
@@ -115,6 +146,11 @@ pub mod signal
 /// as for the single-threaded event channel. In fact, the sync channel is just
 /// a wrapper adding additional sync logic.
 ///
+/// Sending on the sync channel is lock-free. Every writer has its own buffer,
+/// which is merged into the channel on flush. Events are read in the order
+/// they were sent, also between writers. A writer is `Send` but not `Sync`,
+/// so each thread should get its own writer.
+///
 /// With the current implementation, the reader has a borrow of the channel.
 /// This makes it necessary for the reading thread to also have a clone of
 /// the `Arc` (or equivalent) holding the channel, because of lifetimes. In
@@ -130,7 +166,7 @@ pub mod signal
 ///
 /// let c = Arc::clone(&channel);
 /// thread::spawn(move || {
-///     let reader = c.get_reader();
+///     let reader = c.get_reader().unwrap();
 ///     reader.flush_channel();
 ///     for event in reader.read() {
 ///         // do stuff
@@ -138,6 +174,31 @@ pub mod signal
 /// });
 /// ```
 ///
+/// Alternatively,
+/// [`get_owned_reader`](channel::SyncEventChannel::get_owned_reader)
+/// and [`get_owned_writer`](channel::SyncEventChannel::get_owned_writer) give
+/// readers and writers holding their own clone of the `Arc`. They are
+/// `Send + Sync + 'static`, so they can be moved into threads directly, or
+/// kept in resources and system-local state between updates.
+/// ```
+/// # use std::thread;
+/// # use std::sync::Arc;
+/// # use ly_events::channel::SyncEventChannel;
+/// let channel = Arc::new(SyncEventChannel::<usize>::default());
+/// let reader = channel.get_owned_reader().unwrap();
+/// let writer = channel.get_owned_writer();
+///
+/// thread::spawn(move || writer.send(42)).join().unwrap();
+/// let reader = thread::spawn(move || {
+///     reader.flush_channel();
+///     assert_eq!(reader.read().collect::<Vec<_>>(), [&42]);
+///     reader
+/// })
+/// .join()
+/// .unwrap();
+/// assert_eq!(reader.read().count(), 0);
+/// ```
+///
 /// ### Waiting for events
 ///
 /// The [`SyncEventReader`](channel::SyncEventReader) has some synchronization
@@ -165,8 +226,8 @@ pub mod signal
 ///     c.get_writer().send(TestEvent { data: 42 });
 /// });
 ///
-/// let reader1 = channel1.get_reader();
-/// let reader2 = channel2.get_reader();
+/// let reader1 = channel1.get_reader().unwrap();
+/// let reader2 = channel2.get_reader().unwrap();
 /// let readers: [&dyn EventWaiter; 2] = [&reader1, &reader2];
 ///
 /// wait_any_new(&readers);
@@ -176,18 +237,455 @@ pub mod signal
 ///
 /// Note that [`wait_any_new`](channel::wait_any_new) uses dynamic dispatch,
 /// so it will be more performant to wait on a specific event reader.
+///
+/// [`wait_any_new`](channel::wait_any_new) does not tell which channel has
+/// new events. A [`Select`](channel::Select) does, by returning the index of
+/// a reader with new events, taking turns when several have them. It can
+/// also wait with a timeout, or give the set of all ready readers.
+///
+/// ### Closing
+///
+/// A channel counts its writers and readers, see
+/// [`get_num_writers`](channel::SyncEventChannel::get_num_writers) and
+/// [`get_num_readers`](channel::SyncEventChannel::get_num_readers). Once the
+/// channel is [closed](channel::SyncEventChannel::close), or its last writer
+/// is dropped, and the readers have read all events, the waits and
+/// [`try_read`](channel::SyncEventReader::try_read) give
+/// [`Disconnected`](channel::Disconnected), so worker loops can exit.
+/// Events sent to a closed channel, or one whose readers are all dropped, are
+/// given back by [`send`](channel::SyncEventWriter::send).
+/// ```
+/// # use std::thread;
+/// # use std::sync::Arc;
+/// # use ly_events::channel::SyncEventChannel;
+/// let channel = Arc::new(SyncEventChannel::<usize>::default());
+/// let writer = channel.get_writer();
+/// let reader = channel.get_owned_reader().unwrap();
+/// let worker = thread::spawn(move || {
+///     let mut total = 0;
+///     while reader.wait_new().is_ok() {
+///         reader.flush_channel();
+///         total += reader.read().sum::<usize>();
+///     }
+///     total
+/// });
+///
+/// writer.send(1);
+/// writer.send(2);
+/// channel.close();
+/// assert_eq!(worker.join().unwrap(), 3);
+/// ```
+///
+/// ### Async
+///
+/// The waits are also available as futures, woken by the `Waker` of the task,
+/// so events can be processed in async code together with async IO and
+/// timers. [`next_batch`](channel::SyncEventReader::next_batch) waits for
+/// the next flushed batch of events, and
+/// [`stream`](channel::SyncEventReader::stream) gives a `Stream` of cloned
+/// events. Both end once all events are read and the channel is closed, or
+/// the last writer is dropped.
+/// ```
+/// # use std::thread;
+/// # use std::sync::Arc;
+/// # use futures_util::StreamExt;
+/// # use ly_events::channel::SyncEventChannel;
+/// let channel = Arc::new(SyncEventChannel::<usize>::default());
+/// let reader = channel.get_reader().unwrap();
+/// let writer = channel.get_owned_writer();
+/// let owner = Arc::clone(&channel);
+/// thread::spawn(move || {
+///     for i in 0..10 {
+///         writer.send(i);
+///     }
+///     owner.flush();
+/// });
+///
+/// let events = futures_executor::block_on(reader.stream().collect::<Vec<_>>());
+/// assert_eq!(events, (0..10).collect::<Vec<_>>());
+/// ```
+///
+/// A waiter is added before checking for what it waits for, so a send or
+/// flush happening meanwhile is seen by either the waiter or the sender.
+///
+/// ### Metadata
+///
+/// A channel created [`with_meta`](channel::SyncEventChannel::with_meta)
+/// records an [`EventMeta`](channel::EventMeta) for every sent event: when it
+/// was sent, in which frame, its sequence number in the channel and the writer
+/// that sent it. It is read together with the events by
+/// [`read_with_meta`](channel::SyncEventReader::read_with_meta), e.g. for
+/// measuring input latency or recording replays.
+///
+/// ### Partial reads
+///
+/// [`read`](channel::SyncEventReader::read) reads all flushed events at once.
+/// A reader can also [`peek`](channel::SyncEventReader::peek) at them without
+/// reading, read a few with [`read_n`](channel::SyncEventReader::read_n),
+/// [`rewind`](channel::SyncEventReader::rewind) to read them again, and, in
+/// channels keeping metadata, read the ones sent since a sequence number with
+/// [`read_since`](channel::SyncEventReader::read_since). All of it is within
+/// the current flush, which drops the events flushed before, so retrying from
+/// a sequence number only works until the next flush.
+/// ```
+/// # use ly_events::channel::SyncEventChannel;
+/// let channel = SyncEventChannel::<u32>::default();
+/// let writer = channel.get_writer();
+/// let reader = channel.get_reader().unwrap();
+///
+/// for i in 0..4 {
+///     writer.send(i);
+/// }
+/// reader.flush_channel();
+/// assert_eq!(reader.peek().count(), 4);
+/// assert_eq!(reader.read_n(3).collect::<Vec<_>>(), [&0, &1, &2]);
+/// assert_eq!(reader.read().collect::<Vec<_>>(), [&3]);
+///
+/// reader.rewind();
+/// assert_eq!(reader.read().count(), 4);
+/// ```
+///
+/// ### Draining
+///
+/// Readers only get references to the events, so taking ownership of events
+/// with large payloads, like buffers or asset data, means cloning them. The
+/// only reader of a channel can instead be a
+/// [`SyncEventDrainer`](channel::SyncEventDrainer), which moves the flushed
+/// events out of the channel. The channel enforces the exclusivity: a drainer
+/// is refused while there are readers, and a reader is refused while there
+/// is a drainer.
+/// ```
+/// # use ly_events::channel::{Drained, NotExclusive, SyncEventChannel};
+/// let channel = SyncEventChannel::<Vec<u8>>::default();
+/// let writer = channel.get_writer();
+/// let drainer = channel.get_drainer().unwrap();
+/// assert_eq!(channel.get_drainer().err(), Some(NotExclusive(1)));
+/// assert_eq!(channel.get_reader().err(), Some(Drained));
+///
+/// writer.send(vec![0; 1024]);
+/// drainer.flush_channel();
+/// let buffers: Vec<Vec<u8>> = drainer.drain().collect();
+/// assert_eq!(buffers.len(), 1);
+/// assert_eq!(drainer.drain().count(), 0);
+/// ```
+///
+/// ### Bounded channels
+///
+/// The un-flushed events of a channel grow until it is flushed, so a stalled
+/// reader responsible for flushing makes memory grow without limit. A
+/// [`bounded`](channel::SyncEventChannel::bounded) channel holds at most a
+/// number of un-flushed events, and its [`Overflow`](channel::Overflow) policy
+/// decides what a full channel does with sent events: block the writer until
+/// the next flush, drop the oldest or the newest event, or give the event back
+/// from [`send`](channel::SyncEventWriter::send).
+/// ```
+/// # use ly_events::channel::{Overflow, SendError, SyncEventChannel};
+/// let channel = SyncEventChannel::<u32>::bounded(2, Overflow::Error);
+/// let writer = channel.get_writer();
+/// let reader = channel.get_reader().unwrap();
+///
+/// assert_eq!(writer.send(1), Ok(()));
+/// assert_eq!(writer.send(2), Ok(()));
+/// assert_eq!(writer.send(3), Err(SendError::Full(3)));
+/// assert_eq!(channel.dropped(), 0, "given back");
+///
+/// reader.flush_channel();
+/// assert_eq!(writer.send(5), Ok(()));
+/// assert_eq!(reader.read().collect::<Vec<_>>(), [&1, &2]);
+/// ```
+///
+/// ### Coalescing and filtering
+///
+/// High-frequency events, like mouse movement, can be coalesced on flush with
+/// a [`Coalesce`](channel::Coalesce) rule set on the channel, e.g. merging
+/// deltas with [`MergeBy`](channel::MergeBy), keeping the latest event per
+/// key with [`LatestBy`](channel::LatestBy), or dropping repeated events with
+/// [`Dedupe`](channel::Dedupe). Readers only caring about some events can be
+/// [filtered](channel::SyncEventReader::filter), skipping the other events
+/// without copying them.
+/// ```
+/// # use ly_events::channel::{Coalesce, Dedupe, MergeBy, SyncEventChannel};
+/// let channel = SyncEventChannel::<(char, i32)>::default();
+/// channel.set_coalesce(Dedupe.then(MergeBy::new(
+///     |e: &(char, i32)| (e.0 == 'd').then_some(()),
+///     |sum, e| sum.1 += e.1,
+/// )));
+/// let writer = channel.get_writer();
+/// let reader = channel.get_reader().unwrap().filter(|e| e.0 != 'x');
+///
+/// for e in [('a', 0), ('a', 0), ('d', 1), ('x', 0), ('d', 2), ('a', 0)] {
+///     writer.send(e);
+/// }
+/// reader.flush_channel();
+/// assert_eq!(reader.read().collect::<Vec<_>>(), [&('a', 0), &('d', 3), &('a', 0)]);
+/// ```
+///
+/// The mouse and button [types] have ready-made rules, see
+/// [`MouseEvent::coalescing`](types::MouseEvent::coalescing).
+///
+/// ## Work queues
+///
+/// In a [`WorkQueue`](channel::WorkQueue), every event is taken by only one
+/// of the readers, e.g. for jobs that one of several workers should do. A
+/// taken event is put back in the queue if dropped without being
+/// acknowledged, so that another worker can take it.
+/// ```
+/// # use std::thread;
+/// # use ly_events::channel::WorkQueue;
+/// let queue = WorkQueue::<usize>::default();
+/// let writer = queue.get_writer();
+/// for job in 0..10 {
+///     writer.send(job);
+/// }
+/// drop(writer);
+///
+/// thread::scope(|s| {
+///     for _ in 0..2 {
+///         let reader = queue.get_reader();
+///         s.spawn(move || {
+///             while let Some(job) = reader.wait_take() {
+///                 // do the job
+///                 job.ack();
+///             }
+///         });
+///     }
+/// });
+/// assert!(queue.is_empty());
+/// ```
+///
+/// A [`RequestChannel`](channel::RequestChannel) is a work queue of
+/// requests, where sending a request gives a [`Reply`](channel::Reply) to
+/// wait on. The handler taking the request responds through it.
+/// ```
+/// # use std::thread;
+/// # use ly_events::channel::RequestChannel;
+/// let channel = RequestChannel::<u32, u32>::default();
+/// let requester = channel.get_requester();
+/// let handler = channel.get_handler();
+///
+/// let reply = requester.send_request(21);
+/// thread::scope(|s| {
+///     s.spawn(|| {
+///         let request = handler.wait_take().unwrap();
+///         let response = *request * 2;
+///         request.respond(response);
+///     });
+/// });
+/// assert_eq!(reply.wait(), Ok(42));
+/// ```
+///
+/// ## Retaining events
+///
+/// A flush on the channels above drops the events of the previous flush, so
+/// a reader that doesn't read between two flushes misses events. The
+/// [`RingEventChannel`](channel::RingEventChannel) and
+/// [`SyncRingEventChannel`](channel::SyncRingEventChannel) instead keep
+/// flushed events according to a [`Retention`](channel::Retention), and each
+/// reader keeps track of what it has read. A reader that falls behind the
+/// retention gets [`Lagged`](channel::Lagged) with the number of missed events.
+/// ```
+/// # use ly_events::channel::{Lagged, Retention, RingEventChannel};
+/// let channel = RingEventChannel::new(Retention::Flushes(2));
+/// let writer = channel.get_writer();
+/// let reader = channel.get_reader();
+///
+/// writer.send(1);
+/// channel.flush();
+/// writer.send(2);
+/// channel.flush();
+/// assert_eq!(reader.read().unwrap().collect::<Vec<_>>(), [&1, &2]);
+///
+/// for i in 3..6 {
+///     writer.send(i);
+///     channel.flush();
+/// }
+/// assert_eq!(reader.read().err(), Some(Lagged(1)));
+/// assert_eq!(reader.read().unwrap().collect::<Vec<_>>(), [&4, &5]);
+/// ```
 pub mod channel
 {
+	pub use super::async_event_channel::*;
+	pub use super::event_bound::*;
 	pub use super::event_channel::*;
+	pub use super::event_close::*;
+	pub use super::event_coalesce::*;
+	pub use super::event_drain::*;
+	pub use super::event_filter::*;
+	pub use super::event_group::*;
+	pub use super::event_meta::*;
+	pub use super::event_select::*;
+	pub use super::event_stats::*;
+	pub use super::owned_event_channel::*;
+	pub use super::request_channel::*;
+	pub use super::ring_event_channel::*;
 	pub use super::sync_event_channel::*;
+	pub use super::work_queue::*;
+}
+
+/// Module for sending events at a later time
+///
+/// The [`EventScheduler`](schedule::EventScheduler) holds events sent with
+/// [`send_delayed`](channel::SyncEventWriter::send_delayed) and
+/// [`send_at_frame`](channel::SyncEventWriter::send_at_frame), and injects
+/// them into their channels when they are due. The scheduled events outlive
+/// the call, so only writers that can keep their channel alive schedule:
+/// [`OwnedSyncEventWriter`](channel::OwnedSyncEventWriter), holding an `Arc`
+/// of it, and writers borrowing a channel for `'static`.
+///
+/// There is one global scheduler, which is advanced by the `App` every update.
+/// Without something advancing it, scheduled events are never sent.
+///
+/// ### Example
+/// ```
+/// # use ly_events::channel::SyncEventChannel;
+/// # use ly_events::schedule::scheduler;
+/// # use std::time::Instant;
+/// // delayed sends need a channel living for the rest of the program
+/// let channel: &'static SyncEventChannel<usize> =
+///     Box::leak(Box::new(SyncEventChannel::default()));
+/// let writer = channel.get_writer();
+/// let reader = channel.get_reader().unwrap();
+///
+/// let frame = scheduler().current_frame();
+/// writer.send_at_frame(42, frame + 1);
+///
+/// scheduler().advance(frame + 1, Instant::now());
+/// reader.flush_channel();
+/// assert_eq!(reader.read().collect::<Vec<_>>(), [&42]);
+/// ```
+pub mod schedule
+{
+	pub use super::event_schedule::*;
+}
+
+/// Module for finding channels by event type
+///
+/// The [`Events`](registry::Events) registry holds one
+/// [`SyncEventChannel`](channel::SyncEventChannel) per event type, keyed by
+/// `TypeId`. Anyone knowing the event type can get readers and writers,
+/// without knowing who added the channel.
+///
+/// The `App` has an `Events` resource, where event types are added with
+/// `add_event`, and flushes all its channels every update before running the
+/// systems. Readers of these channels should therefore not flush them.
+///
+/// An event type may declare how its channel is configured, by deriving
+/// [`Event`](registry::Event), and is then registered with
+/// [`add_event`](registry::Events::add_event), keeping the configuration
+/// with the type rather than in setup code.
+///
+/// [`stats`](registry::Events::stats) gives the
+/// [`ChannelStats`](channel::ChannelStats) of all registered channels, to
+/// show which channels are hot or backed up, e.g. in a debug overlay, or in
+/// the log with [`log_stats`](registry::Events::log_stats).
+///
+/// ### Example
+/// ```
+/// # use ly_events::registry::Events;
+/// let events = Events::default();
+/// events.add::<u32>();
+///
+/// let writer = events.get_writer::<u32>().unwrap();
+/// let reader = events.get_reader::<u32>().unwrap();
+/// assert!(events.get_reader::<i32>().is_none());
+///
+/// writer.send(42);
+/// events.flush();
+/// assert_eq!(reader.read().collect::<Vec<_>>(), [&42]);
+/// ```
+pub mod registry
+{
+	pub use super::event_registry::*;
+	pub use ly_events_derive::Event;
+}
+
+/// Module for sending events between processes
+///
+/// An [`EventBridge`](bridge::EventBridge) mirrors a channel over a Unix
+/// domain socket, to [`BridgeClient`](bridge::BridgeClient)s in other
+/// processes, e.g. an editor or a debug tool attached to a running game.
+/// Events flow both ways: the flushed events of the bridged channel are sent
+/// to every client, and events sent by a client are sent to the bridged
+/// channel.
+///
+/// Events are serialized with `bincode`, so the bridge is only available with
+/// the `serde` feature. Each event is a frame, prefixed by its length. A
+/// connection starts with both sides sending the
+/// [`PROTOCOL_VERSION`](bridge::PROTOCOL_VERSION) and the name of the event
+/// type, and is refused if they do not match.
+///
+/// ### Example
+/// ```
+/// # use ly_events::bridge::{BridgeClient, EventBridge};
+/// # use ly_events::channel::SyncEventChannel;
+/// # use std::sync::Arc;
+/// # use std::thread;
+/// # use std::time::Duration;
+/// # let path = std::env::temp_dir().join(format!("ly_bridge_doc_{}", std::process::id()));
+/// let channel = Arc::new(SyncEventChannel::<u32>::default());
+/// let bridge = EventBridge::listen(&channel, &path).unwrap();
+///
+/// // usually in another process
+/// let client = BridgeClient::<u32>::connect(&path);
+/// let reader = client.channel().get_reader().unwrap();
+/// while bridge.connections() == 0 {
+///     thread::sleep(Duration::from_millis(1));
+/// }
+///
+/// channel.get_writer().send(42);
+/// channel.flush();
+/// let mut received = Vec::new();
+/// while received.is_empty() {
+///     reader.flush_channel();
+///     received.extend(reader.read().copied());
+/// }
+/// assert_eq!(received, [42]);
+/// ```
+#[cfg(all(unix, feature = "serde"))]
+pub mod bridge
+{
+	pub use super::event_bridge::*;
+}
+
+/// Used by the code generated by `#[derive(Event)]`
+#[doc(hidden)]
+pub mod __private
+{
+	#[cfg(feature = "serde")]
+	pub use serde;
 }
 
 /// Provides event types to be used with the LY engine
 ///
-/// TODO: Consider having a channel per event, and not
-/// grouped together like this. With some abstraction
-/// to have a "group reader" this might be more effective,
-/// as there are less/no irrelevant events for a reader.
+/// The grouped events, like [`ButtonEvent`](types::ButtonEvent), are also
+/// sent as fine-grained events, one channel per event, like
+/// [`KeyPressed`](types::KeyPressed), by the window when the app asks for
+/// them, through a [`SplitWriter`](types::SplitWriter). A reader of a
+/// fine-grained channel only gets the events it cares about, and several of
+/// them are read together, in the order they were sent, by a
+/// [`GroupReader`](channel::GroupReader).
+///
+/// ### Example
+/// ```
+/// # use ly_events::channel::GroupReader;
+/// # use ly_events::registry::Events;
+/// # use ly_events::types::*;
+/// # use ly_input::Key;
+/// let events = Events::default();
+/// let split = SplitWriter::new(&events);
+/// let reader = GroupReader::<ButtonEvent>::new()
+///     .with(events.get_reader::<KeyPressed>().unwrap())
+///     .with(events.get_reader::<KeyReleased>().unwrap());
+///
+/// split.button(&ButtonEvent::KeyReleased(Key::A));
+/// split.mouse(&MouseEvent::CursorMove(1.0, 2.0));
+/// split.button(&ButtonEvent::KeyPressed(Key::B));
+/// events.flush();
+/// assert_eq!(
+///     reader.read().collect::<Vec<_>>(),
+///     [ButtonEvent::KeyReleased(Key::A), ButtonEvent::KeyPressed(Key::B)]
+/// );
+/// ```
 pub mod types
 {
 	pub use super::event_types::*;
@@ -221,7 +719,7 @@ mod tests
 		let events = reader.read().collect::<Vec<&TestEvent>>();
 		assert_eq!(events, Vec::<&TestEvent>::default(), "initial events empty");
 
-		writer.send(event0);
+		writer.send(event0).unwrap();
 		test_channel.flush();
 
 		let events = reader.read().collect::<Vec<&TestEvent>>();
@@ -231,7 +729,7 @@ mod tests
 			"reader can read flushed event0"
 		);
 
-		writer.send(event1);
+		writer.send(event1).unwrap();
 		test_channel.flush();
 
 		let events = reader.read().collect::<Vec<&TestEvent>>();
@@ -272,13 +770,13 @@ mod tests
 			let writer = c.get_writer();
 			for i in 0..10 {
 				let event = TestEvent { data: 2 * i };
-				writer.send(event);
+				writer.send(event).unwrap();
 				thread::sleep(Duration::from_millis(1));
 			}
 		});
 
 		let rec1 = thread::spawn(move || {
-			let rec = channel.get_reader();
+			let rec = channel.get_reader().unwrap();
 			loop {
 				thread::sleep(Duration::from_millis(5));
 				let mut got_events = false;
@@ -312,7 +810,7 @@ mod tests
 		let emitter1 = thread::spawn(move || {
 			let writer = c.get_writer();
 			for i in 1..11 {
-				writer.send(());
+				writer.send(()).unwrap();
 				thread::sleep(Duration::from_millis(2));
 				assert!(total.lock().eq(&i));
 			}
@@ -320,9 +818,9 @@ mod tests
 
 		let total = Arc::clone(&total_loc);
 		let rec1 = thread::spawn(move || {
-			let rec = channel.get_reader();
+			let rec = channel.get_reader().unwrap();
 			for _ in 1..11 {
-				rec.wait_new();
+				rec.wait_new().unwrap();
 				rec.flush_channel();
 				for _ in rec.read() {
 					total.lock().add_assign(1);
@@ -349,7 +847,7 @@ mod tests
 			let writer = c.get_writer();
 			thread::sleep(Duration::from_millis(5)); // TODO shouldn't need
 			for i in 1..11 {
-				writer.send(());
+				writer.send(()).unwrap();
 				thread::sleep(Duration::from_millis(5));
 
 				// two readers should update when main thread flushes
@@ -360,9 +858,9 @@ mod tests
 		let c = Arc::clone(&channel);
 		let total = Arc::clone(&total_loc);
 		let rec1 = thread::spawn(move || {
-			let rec = c.get_reader();
+			let rec = c.get_reader().unwrap();
 			for _ in 1..11 {
-				rec.wait_flushed();
+				rec.wait_flushed().unwrap();
 				for _ in rec.read() {
 					total.lock().add_assign(1);
 				}
@@ -372,18 +870,18 @@ mod tests
 		let c = Arc::clone(&channel);
 		let total = Arc::clone(&total_loc);
 		let rec2 = thread::spawn(move || {
-			let rec = c.get_reader();
+			let rec = c.get_reader().unwrap();
 			for _ in 1..11 {
-				rec.wait_flushed();
+				rec.wait_flushed().unwrap();
 				for _ in rec.read() {
 					total.lock().add_assign(1);
 				}
 			}
 		});
 
-		let rec = channel.get_reader();
+		let rec = channel.get_reader().unwrap();
 		for _ in 1..11 {
-			rec.wait_new();
+			rec.wait_new().unwrap();
 			rec.flush_channel();
 		}
 
@@ -406,7 +904,7 @@ mod tests
 		let emitter1 = thread::spawn(move || {
 			let writer = c.get_writer();
 			for i in 1..11 {
-				writer.send(());
+				writer.send(()).unwrap();
 				thread::sleep(Duration::from_millis(2));
 				assert!(total.lock().eq(&i));
 			}
@@ -414,9 +912,9 @@ mod tests
 
 		let total = Arc::clone(&total_loc);
 		let rec1 = thread::spawn(move || {
-			let rec = channel.get_reader();
+			let rec = channel.get_reader().unwrap();
 			for _ in 1..11 {
-				rec.wait_new();
+				rec.wait_new().unwrap();
 				rec.flush_channel();
 				for _ in rec.read() {
 					total.lock().add_assign(1);
@@ -429,4 +927,978 @@ mod tests
 		let total = total_loc.lock();
 		assert!(total.eq(&10));
 	}
+
+	#[test]
+	/// test many writers sending while flushing
+	fn sync_005()
+	{
+		let channel = Arc::new(SyncEventChannel::<TestEvent>::default());
+		let emitters = (0..4)
+			.map(|t| {
+				let c = Arc::clone(&channel);
+				thread::spawn(move || {
+					let writer = c.get_writer();
+					for i in 0..10000 {
+						writer
+							.send(TestEvent {
+								data: t * 10000 + i,
+							})
+							.unwrap();
+					}
+				})
+			})
+			.collect::<Vec<_>>();
+
+		let reader = channel.get_reader().unwrap();
+		let mut received = Vec::new();
+		let mut done = false;
+		while !done {
+			done = emitters.iter().all(|e| e.is_finished());
+			reader.flush_channel();
+			received.extend(reader.read().map(|e| e.data));
+			assert_eq!(reader.read().count(), 0, "cannot read twice");
+		}
+
+		received.sort_unstable();
+		assert_eq!(received, (0..40000).collect::<Vec<_>>());
+	}
+
+	#[test]
+	/// test that events are dropped once, whether read or not
+	fn sync_006()
+	{
+		let counter = Arc::new(());
+		let channel = SyncEventChannel::<Arc<()>>::default();
+		let reader = channel.get_reader().unwrap();
+		{
+			let writer = channel.get_writer();
+			for _ in 0..100 {
+				writer.send(Arc::clone(&counter)).unwrap();
+			}
+			channel.flush();
+			assert_eq!(reader.read().count(), 100);
+			for _ in 0..50 {
+				writer.send(Arc::clone(&counter)).unwrap();
+			}
+		}
+		assert_eq!(Arc::strong_count(&counter), 151);
+
+		let writer = channel.get_writer();
+		writer.send(Arc::clone(&counter)).unwrap();
+		channel.flush();
+		assert_eq!(reader.read().count(), 51, "events of dropped writer kept");
+		assert_eq!(Arc::strong_count(&counter), 52);
+
+		writer.send(Arc::clone(&counter)).unwrap();
+		drop(writer);
+		drop(reader);
+		drop(channel);
+		assert_eq!(Arc::strong_count(&counter), 1);
+	}
+
+	#[test]
+	/// test owned readers and writers kept in state moved between threads
+	fn sync_007()
+	{
+		fn assert_static_send_sync<T: Send + Sync + 'static>(_: &T) {}
+
+		struct State
+		{
+			reader: OwnedSyncEventReader<usize>,
+			writer: OwnedSyncEventWriter<usize>,
+		}
+
+		let channel = Arc::new(SyncEventChannel::<usize>::default());
+		let state = State {
+			reader: channel.get_owned_reader().unwrap(),
+			writer: channel.get_owned_writer(),
+		};
+		assert_static_send_sync(&state.reader);
+		assert_static_send_sync(&state.writer);
+		drop(channel);
+
+		let state = thread::spawn(move || {
+			state.writer.send(1).unwrap();
+			state.reader.flush_channel();
+			assert_eq!(state.reader.read().collect::<Vec<_>>(), [&1]);
+			state.writer.send(2).unwrap();
+			state
+		})
+		.join()
+		.unwrap();
+
+		let reader = state.reader.clone();
+		state.reader.flush_channel();
+		assert_eq!(state.reader.read().collect::<Vec<_>>(), [&2]);
+		assert_eq!(
+			reader.read().collect::<Vec<_>>(),
+			[&2],
+			"clone has own cursor"
+		);
+
+		let writer = state.writer.clone();
+		drop(state.writer);
+		assert!(reader.channel_has_writers());
+		drop(writer);
+		assert!(!reader.channel_has_writers());
+		assert_eq!(reader.wait_new(), Err(Disconnected));
+	}
+
+	#[test]
+	/// test the async interface of the reader
+	fn sync_008()
+	{
+		use futures_executor::block_on;
+		use futures_util::{FutureExt, StreamExt};
+
+		let channel = Arc::new(SyncEventChannel::<usize>::default());
+		let reader = channel.get_reader().unwrap();
+		let writer = channel.get_owned_writer();
+		writer.send(1).unwrap();
+		writer.send(2).unwrap();
+		block_on(async {
+			assert_eq!(reader.wait_new_async().await, Ok(1));
+			let unflushed = reader.next_batch().now_or_never();
+			assert!(unflushed.is_none(), "not flushed by the reader");
+			channel.flush();
+			let batch = reader.next_batch().await.unwrap();
+			assert_eq!(batch.collect::<Vec<_>>(), [&1, &2]);
+		});
+
+		let flusher = channel.get_owned_reader().unwrap();
+		let (read, has_read) = std::sync::mpsc::channel();
+		let handle = thread::spawn(move || {
+			thread::sleep(Duration::from_millis(10));
+			writer.send(3).unwrap();
+			flusher.flush_channel();
+			has_read.recv().unwrap();
+			for i in 4..100 {
+				writer.send(i).unwrap();
+			}
+			flusher.flush_channel();
+		});
+		block_on(reader.wait_flushed_async()).unwrap();
+		assert_eq!(reader.read().collect::<Vec<_>>(), [&3]);
+		read.send(()).unwrap();
+		let events = block_on(reader.stream().collect::<Vec<_>>());
+		assert_eq!(events, (4..100).collect::<Vec<_>>());
+		handle.join().unwrap();
+
+		assert!(block_on(reader.next_batch()).is_none());
+		assert_eq!(block_on(reader.wait_new_async()), Err(Disconnected));
+		assert_eq!(block_on(reader.wait_flushed_async()), Err(Disconnected));
+	}
+
+	#[test]
+	/// test the send order between writers, and adding writers while reading
+	fn sync_009()
+	{
+		let channel = SyncEventChannel::<usize>::default();
+		let reader = channel.get_reader().unwrap();
+		let writer0 = channel.get_writer();
+		let writer1 = channel.get_writer();
+		for i in 0..100 {
+			match i % 3 {
+				0 => writer1.send(i).unwrap(),
+				_ => writer0.send(i).unwrap(),
+			}
+		}
+		drop(writer1);
+		channel.flush();
+		assert_eq!(
+			reader.read().copied().collect::<Vec<_>>(),
+			(0..100).collect::<Vec<_>>()
+		);
+
+		writer0.send(0).unwrap();
+		channel.flush();
+		for e in reader.read() {
+			let writer = channel.get_writer();
+			writer.send(e + 1).unwrap();
+		}
+		channel.flush();
+		assert_eq!(reader.read().collect::<Vec<_>>(), [&1]);
+	}
+
+	#[test]
+	/// test the send order of a lone writer with the events sent by the
+	/// channel, and once a second writer is added
+	fn sync_010()
+	{
+		let channel = SyncEventChannel::<usize>::default();
+		let reader = channel.get_reader().unwrap();
+		let writer0 = channel.get_writer();
+		writer0.send(0).unwrap();
+		channel.send(1, writer0.id());
+		writer0.send(2).unwrap();
+		writer0.send(3).unwrap();
+		channel.send(4, writer0.id());
+		let writer1 = channel.get_writer();
+		writer1.send(5).unwrap();
+		writer0.send(6).unwrap();
+		channel.send(7, writer1.id());
+		writer1.send(8).unwrap();
+		channel.flush();
+		assert_eq!(
+			reader.read().copied().collect::<Vec<_>>(),
+			(0..9).collect::<Vec<_>>()
+		);
+	}
+
+	#[test]
+	/// test waiting for a flush before the first writer is created
+	fn sync_011()
+	{
+		use futures_executor::block_on;
+		use futures_util::FutureExt;
+
+		let channel = Arc::new(SyncEventChannel::<usize>::default());
+		let reader = channel.get_reader().unwrap();
+		let mut flushed = reader.wait_flushed_async();
+		assert!((&mut flushed).now_or_never().is_none(), "waits for writers");
+
+		let sender = Arc::clone(&channel);
+		let handle = thread::spawn(move || {
+			thread::sleep(Duration::from_millis(10));
+			let writer = sender.get_owned_writer();
+			writer.send(1).unwrap();
+			drop(writer);
+			thread::sleep(Duration::from_millis(10));
+			sender.flush();
+		});
+		assert_eq!(block_on(flushed), Ok(()));
+		handle.join().unwrap();
+		assert_eq!(reader.read().collect::<Vec<_>>(), [&1]);
+		assert_eq!(block_on(reader.wait_flushed_async()), Err(Disconnected));
+	}
+
+	#[test]
+	/// test that select reports the ready channel, in turns
+	fn select_001()
+	{
+		let channel_a = SyncEventChannel::<usize>::default();
+		let channel_b = SyncEventChannel::<usize>::default();
+		let reader_a = channel_a.get_reader().unwrap();
+		let reader_b = channel_b.get_reader().unwrap();
+		let mut select = Select::new();
+		let a = select.add(&reader_a);
+		let b = select.add(&reader_b);
+
+		assert_eq!(select.try_select(), None);
+		assert_eq!(
+			select.select_timeout(Duration::from_millis(5)),
+			Err(SelectError::Timeout),
+			"waits for the first writers"
+		);
+
+		let writer_a = channel_a.get_writer();
+		let writer_b = channel_b.get_writer();
+		assert_eq!(
+			select.select_timeout(Duration::from_millis(5)),
+			Err(SelectError::Timeout)
+		);
+
+		writer_a.send(1).unwrap();
+		writer_b.send(2).unwrap();
+		assert_eq!(select.ready(), [a, b]);
+		assert_eq!(select.select(), Ok(a));
+		assert_eq!(select.select(), Ok(b), "takes turns");
+		assert_eq!(select.select(), Ok(a));
+		channel_a.flush();
+		assert_eq!(select.select(), Ok(b));
+		assert_eq!(select.select(), Ok(b));
+		channel_b.flush();
+
+		thread::scope(|s| {
+			s.spawn(move || {
+				thread::sleep(Duration::from_millis(10));
+				writer_b.send(3).unwrap();
+			});
+			assert_eq!(select.select(), Ok(b));
+		});
+	}
+
+	#[test]
+	/// test that select waits for channels to get their first writer, and
+	/// is disconnected once all writers are dropped
+	fn select_002()
+	{
+		let channel_a = SyncEventChannel::<usize>::default();
+		let channel_b = SyncEventChannel::<usize>::default();
+		let reader_a = channel_a.get_reader().unwrap();
+		let reader_b = channel_b.get_reader().unwrap();
+		let mut select = Select::new();
+		select.add(&reader_a);
+		let b = select.add(&reader_b);
+
+		thread::scope(|s| {
+			s.spawn(|| {
+				thread::sleep(Duration::from_millis(10));
+				channel_b.get_writer().send(1).unwrap();
+			});
+			assert_eq!(select.select(), Ok(b));
+		});
+		channel_b.flush();
+		assert_eq!(
+			select.select_timeout(Duration::from_millis(5)),
+			Err(SelectError::Timeout),
+			"channel_a may still get writers"
+		);
+
+		drop(channel_a.get_writer());
+		assert_eq!(select.select(), Err(SelectError::Disconnected));
+	}
+
+	#[test]
+	/// test looking up channels by event type
+	fn registry_001()
+	{
+		use super::registry::Events;
+		use std::any::TypeId;
+
+		let events = Events::default();
+		let channel = events.add::<usize>();
+		assert!(Arc::ptr_eq(&channel, &events.add::<usize>()), "added once");
+		assert!(events.contains::<usize>());
+		assert!(!events.contains::<u8>());
+		assert!(events.get_writer::<u8>().is_none());
+
+		let reader = events.get_reader::<usize>().unwrap();
+		let writer = channel.get_writer();
+		writer.send(1).unwrap();
+		events.add::<u8>().get_writer().send(2).unwrap();
+		events.flush();
+		assert_eq!(reader.read().collect::<Vec<_>>(), [&1]);
+		assert_eq!(
+			events
+				.get_by_id(TypeId::of::<u8>())
+				.unwrap()
+				.event_type_name(),
+			"u8"
+		);
+	}
+
+	#[test]
+	/// test that work is taken once, and requeued unless acknowledged
+	fn work_001()
+	{
+		let queue = WorkQueue::<usize>::default();
+		let reader = queue.get_reader();
+		let writer = queue.get_writer();
+		writer.send(1);
+		writer.send(2);
+
+		let work = reader.take().unwrap();
+		assert_eq!(*work, 1);
+		assert_eq!(queue.in_progress(), 1);
+		drop(work);
+		assert_eq!(queue.in_progress(), 0);
+		assert_eq!(reader.take().unwrap().ack(), 1, "requeued first");
+		assert_eq!(reader.take().unwrap().ack(), 2);
+		assert!(reader.take().is_none());
+
+		let taken = Mutex::new(Vec::new());
+		thread::scope(|s| {
+			for _ in 0..4 {
+				let reader = queue.get_reader();
+				let taken = &taken;
+				s.spawn(move || {
+					while let Some(work) = reader.wait_take() {
+						taken.lock().push(work.ack());
+					}
+				});
+			}
+			for i in 0..1000 {
+				writer.send(i);
+			}
+			drop(writer);
+		});
+
+		let mut taken = taken.into_inner();
+		taken.sort_unstable();
+		assert_eq!(taken, (0..1000).collect::<Vec<_>>());
+		assert_eq!(reader.wait_new(), 0);
+	}
+
+	#[test]
+	/// test that requests are responded to through the reply
+	fn request_001()
+	{
+		let channel = RequestChannel::<usize, String>::default();
+		let requester = channel.get_requester();
+		let handler = channel.get_handler();
+
+		let reply = requester.send_request(1);
+		assert!(!reply.is_ready());
+		assert_eq!(reply.try_get(), None);
+		handler.take().unwrap().respond("1".to_string());
+		assert_eq!(reply.try_get(), Some(Ok("1".to_string())));
+		assert_eq!(reply.try_get(), Some(Err(ReplyError::NoReply)), "taken");
+
+		let reply = requester.send_request(2);
+		drop(handler.take());
+		assert_eq!(reply.wait(), Err(ReplyError::NoReply));
+
+		let reply = requester.send_request(3);
+		assert_eq!(
+			reply.wait_timeout(Duration::from_millis(5)),
+			Err(ReplyError::Timeout)
+		);
+
+		thread::scope(|s| {
+			s.spawn(|| {
+				while let Some(request) = handler.wait_take() {
+					let response = request.to_string();
+					request.respond(response);
+				}
+			});
+			let replies: Vec<_> = (4..100).map(|i| requester.send_request(i)).collect();
+			assert_eq!(reply.wait(), Ok("3".to_string()));
+			for (i, reply) in (4..100).zip(replies) {
+				assert_eq!(futures_executor::block_on(reply), Ok(i.to_string()));
+			}
+			drop(requester);
+		});
+	}
+
+	#[test]
+	/// test coalescing rules on flush, and filtered readers
+	fn coalesce_001()
+	{
+		use crate::types::MouseEvent::{self, CursorMove, MouseMove};
+
+		let channel = SyncEventChannel::<MouseEvent>::default();
+		channel.set_coalesce(MouseEvent::coalescing());
+		let writer = channel.get_writer();
+		let reader = channel.get_reader().unwrap();
+		let cursor = channel
+			.get_reader()
+			.unwrap()
+			.filter(|e| matches!(e, CursorMove(..)));
+
+		for i in 0..4 {
+			writer.send(CursorMove(i as f64, 0.0)).unwrap();
+			writer.send(MouseMove(1.0, -1.0)).unwrap();
+		}
+		channel.flush();
+		assert_eq!(
+			reader.read().collect::<Vec<_>>(),
+			[&MouseMove(4.0, -4.0), &CursorMove(3.0, 0.0)]
+		);
+		assert_eq!(cursor.read().collect::<Vec<_>>(), [&CursorMove(3.0, 0.0)]);
+		assert_eq!(cursor.read().count(), 0);
+
+		let channel = SyncEventChannel::<(u8, u8)>::default();
+		channel.set_coalesce(LatestBy::new(|e: &(u8, u8)| Some(e.0)).then(Dedupe));
+		let writer = channel.get_writer();
+		let reader = channel.get_reader().unwrap();
+		for e in [(0, 0), (1, 0), (0, 1), (2, 0), (2, 1), (1, 0)] {
+			writer.send(e).unwrap();
+		}
+		channel.flush();
+		assert_eq!(
+			reader.read().collect::<Vec<_>>(),
+			[&(0, 1), &(2, 1), &(1, 0)]
+		);
+	}
+
+	#[test]
+	/// test the overflow policies of bounded channels
+	fn bounded_001()
+	{
+		let read = |channel: &SyncEventChannel<usize>| {
+			channel.flush();
+			channel
+				.get_reader()
+				.unwrap()
+				.read()
+				.copied()
+				.collect::<Vec<_>>()
+		};
+
+		let channel = SyncEventChannel::bounded(3, Overflow::DropOldest);
+		let writer = channel.get_writer();
+		let other = channel.get_writer();
+		(0..4).for_each(|i| writer.send(i).unwrap());
+		other.send(4).unwrap();
+		assert_eq!(read(&channel), [2, 3, 4]);
+		assert_eq!(channel.dropped(), 2);
+
+		let channel = SyncEventChannel::bounded(3, Overflow::DropNewest);
+		let writer = channel.get_writer();
+		(0..5).for_each(|i| writer.send(i).unwrap());
+		assert_eq!(read(&channel), [0, 1, 2]);
+		assert_eq!(channel.dropped(), 2);
+		assert_eq!(channel.capacity(), Some(3));
+
+		let channel = SyncEventChannel::bounded(2, Overflow::Block);
+		let reader = channel.get_reader().unwrap();
+		thread::scope(|s| {
+			let writer = channel.get_writer();
+			s.spawn(move || (0..10).for_each(|i| writer.send(i).unwrap()));
+
+			let mut events = Vec::new();
+			while events.len() < 10 {
+				reader.wait_new().unwrap();
+				reader.flush_channel();
+				let read: Vec<_> = reader.read().copied().collect();
+				assert!(read.len() <= 2);
+				events.extend(read);
+			}
+			assert_eq!(events, (0..10).collect::<Vec<_>>());
+		});
+		assert_eq!(channel.dropped(), 0);
+	}
+
+	#[test]
+	/// test recording metadata of sent events
+	fn meta_001()
+	{
+		let channel = SyncEventChannel::<usize>::default().with_meta();
+		channel.set_coalesce(MergeBy::new(
+			|e: &usize| (*e >= 10).then_some(()),
+			|sum, e| *sum += e,
+		));
+		let reader = channel.get_reader().unwrap();
+		let writer0 = channel.get_writer();
+		let writer1 = channel.get_writer();
+		assert_ne!(writer0.id(), writer1.id());
+
+		writer0.send(0).unwrap();
+		writer1.send(1).unwrap();
+		writer0.send(10).unwrap();
+		writer1.send(20).unwrap();
+		drop(writer1);
+		channel.flush();
+
+		let events: Vec<_> = reader
+			.read_with_meta()
+			.map(|(e, meta)| (*e, meta.sequence, meta.writer))
+			.collect();
+		assert_eq!(
+			events,
+			[(0, 0, 0), (1, 1, 1), (30, 2, 0)],
+			"merged into first event with key"
+		);
+		assert_eq!(reader.read_with_meta().count(), 0);
+
+		writer0.send(2).unwrap();
+		channel.flush();
+		let (_, meta) = reader.read_with_meta().next().unwrap();
+		assert_eq!(meta.sequence, 4);
+		assert!(meta.timestamp <= std::time::Instant::now());
+	}
+
+	#[test]
+	/// test channel statistics
+	fn stats_001()
+	{
+		let channel = SyncEventChannel::<usize>::bounded(3, Overflow::DropNewest);
+		channel.set_coalesce(Dedupe);
+		let writer = channel.get_writer();
+		let reader0 = channel.get_reader().unwrap();
+		let reader1 = channel.get_reader().unwrap();
+		drop(channel.get_reader().unwrap());
+
+		[1, 1, 2, 3]
+			.into_iter()
+			.for_each(|e| writer.send(e).unwrap());
+		channel.flush();
+		reader0.read().count();
+		writer.send(4).unwrap();
+		channel.flush();
+		writer.send(5).unwrap();
+
+		let stats = channel.stats();
+		assert_eq!(stats.event_type, "usize");
+		assert_eq!((stats.sent, stats.flushed, stats.dropped), (5, 3, 1));
+		assert_eq!(
+			(stats.flushes, stats.pending, stats.peak_pending),
+			(2, 1, 3)
+		);
+		assert_eq!((stats.writers, stats.readers), (1, 2));
+		assert_eq!(stats.reader_lag, [1, 2]);
+		assert_eq!(stats.max_lag(), 2);
+		reader1.read().count();
+		assert_eq!(channel.stats().reader_lag, [1, 0]);
+		let _late = channel.get_reader().unwrap();
+		assert_eq!(
+			channel.stats().reader_lag,
+			[1, 0, 1],
+			"counted from creation"
+		);
+	}
+
+	#[cfg(all(unix, feature = "serde"))]
+	#[test]
+	fn bridge_001()
+	{
+		use crate::bridge::{BridgeClient, EventBridge};
+
+		fn wait_for(mut done: impl FnMut() -> bool)
+		{
+			let start = std::time::Instant::now();
+			while !done() {
+				assert!(start.elapsed() < Duration::from_secs(5));
+				thread::sleep(Duration::from_millis(1));
+			}
+		}
+
+		let path = std::env::temp_dir().join(format!("ly_bridge_001_{}", std::process::id()));
+		let channel = Arc::new(SyncEventChannel::<u32>::default());
+		let reader = channel.get_reader().unwrap();
+		let bridge = EventBridge::listen(&channel, &path).unwrap();
+		let client = BridgeClient::<u32>::connect(&path);
+		let client_reader = client.channel().get_reader().unwrap();
+		let mismatched = BridgeClient::<i64>::connect(&path);
+		wait_for(|| bridge.connections() == 1 && client.is_connected());
+
+		// bridged channel to client
+		channel.get_writer().send(1).unwrap();
+		channel.flush();
+		assert_eq!(reader.read().collect::<Vec<_>>(), [&1]);
+		let mut received = Vec::new();
+		wait_for(|| {
+			client_reader.flush_channel();
+			received.extend(client_reader.read().copied());
+			!received.is_empty()
+		});
+		assert_eq!(received, [1]);
+
+		// client to bridged channel, and mirrored back
+		client.send(&2).unwrap();
+		let mut injected = Vec::new();
+		wait_for(|| {
+			channel.flush();
+			injected.extend(reader.read().copied());
+			!injected.is_empty()
+		});
+		assert_eq!(injected, [2]);
+		received.clear();
+		wait_for(|| {
+			client_reader.flush_channel();
+			received.extend(client_reader.read().copied());
+			!received.is_empty()
+		});
+		assert_eq!(received, [2]);
+
+		// reconnects when the bridge is restarted
+		drop(bridge);
+		wait_for(|| !client.is_connected());
+		assert!(client.send(&3).is_err());
+		let bridge = EventBridge::listen(&channel, &path).unwrap();
+		wait_for(|| bridge.connections() == 1 && client.is_connected());
+		assert!(!mismatched.is_connected());
+	}
+
+	#[test]
+	fn frame_001()
+	{
+		use crate::signal::{FrameSync, FrameSyncError};
+
+		let sync = FrameSync::new(2);
+		let fast = sync.get_worker();
+		let slow = sync.get_worker();
+		assert_eq!(sync.produce(), Ok(0));
+		assert_eq!(sync.produce(), Ok(1));
+		assert_eq!(
+			sync.produce_timeout(Duration::from_millis(1)),
+			Err(FrameSyncError::Timeout)
+		);
+
+		// held back by the slowest worker
+		assert_eq!(fast.wait_frame(), Ok(0));
+		fast.consume(1);
+		assert_eq!(
+			fast.wait_frame_timeout(Duration::from_millis(1)),
+			Err(FrameSyncError::Timeout)
+		);
+		assert!(sync.produce_timeout(Duration::from_millis(1)).is_err());
+		assert_eq!(slow.wait_frame(), Ok(0));
+		slow.consume(0);
+		assert_eq!(sync.produce(), Ok(2));
+		drop(slow);
+		assert_eq!(sync.produce(), Ok(3));
+
+		thread::scope(|s| {
+			let worker = sync.get_worker();
+			let frames = s.spawn(move || {
+				let mut frames = Vec::new();
+				while let Ok(frame) = worker.wait_frame() {
+					frames.push(frame);
+					worker.consume(frame);
+				}
+				frames
+			});
+			fast.consume(3);
+			for _ in 0..10 {
+				let frame = sync.produce().unwrap();
+				fast.consume(frame);
+			}
+			sync.close();
+			assert_eq!(frames.join().unwrap(), (4..14).collect::<Vec<_>>());
+		});
+		assert_eq!(sync.produce(), Err(FrameSyncError::Closed));
+	}
+
+	#[test]
+	/// test reading channels of different types in send order
+	fn group_001()
+	{
+		#[derive(Debug, PartialEq)]
+		enum Merged
+		{
+			Number(usize),
+			Text(String),
+		}
+
+		let numbers = Arc::new(SyncEventChannel::<usize>::default().with_meta());
+		let texts = Arc::new(SyncEventChannel::<&str>::default().with_meta());
+		let unread = Arc::new(SyncEventChannel::<usize>::default().with_meta());
+		let reader = GroupReader::new()
+			.with_map(numbers.get_owned_reader().unwrap(), |n| Merged::Number(*n))
+			.with_map(texts.get_owned_reader().unwrap(), |t| {
+				Merged::Text(t.to_string())
+			});
+
+		let number_writers = [numbers.get_writer(), numbers.get_writer()];
+		let text_writer = texts.get_writer();
+		number_writers[1].send(1).unwrap();
+		text_writer.send("a").unwrap();
+		unread.get_writer().send(0).unwrap();
+		number_writers[0].send(2).unwrap();
+		text_writer.send("b").unwrap();
+		number_writers[1].send(3).unwrap();
+		reader.flush_channels();
+		assert_eq!(
+			reader.read().collect::<Vec<_>>(),
+			[
+				Merged::Number(1),
+				Merged::Text("a".into()),
+				Merged::Number(2),
+				Merged::Text("b".into()),
+				Merged::Number(3),
+			]
+		);
+		assert_eq!(reader.read().count(), 0);
+
+		let orders: Vec<_> = {
+			text_writer.send("c").unwrap();
+			number_writers[0].send(4).unwrap();
+			reader.flush_channels();
+			reader
+				.read_with_meta()
+				.map(|(_, meta)| meta.order)
+				.collect()
+		};
+		assert!(orders[0] < orders[1]);
+	}
+
+	#[test]
+	/// test channels configured by derived events
+	fn event_001()
+	{
+		use crate::registry::{Event, Events};
+
+		fn latest() -> impl Coalesce<Moved> { LatestBy::new(|_: &Moved| Some(())) }
+
+		#[derive(Event, Debug, Clone, PartialEq)]
+		#[event(capacity = 2, overflow = Error, coalesce = latest)]
+		#[event(retention(events = 3))]
+		struct Moved(u32);
+
+		#[derive(Event)]
+		enum Plain
+		{
+			_A,
+		}
+
+		let events = Events::default();
+		let channel = events.add_event::<Moved>();
+		assert_eq!(channel.capacity(), Some(2));
+		assert!(!channel.has_meta());
+		let writer = channel.get_writer();
+		let reader = channel.get_reader().unwrap();
+		assert!(writer.send(Moved(1)).is_ok());
+		assert!(writer.send(Moved(2)).is_ok());
+		assert!(writer.send(Moved(3)).is_err());
+		events.flush();
+		assert_eq!(reader.read().collect::<Vec<_>>(), [&Moved(2)]);
+
+		assert_eq!(Moved::RETENTION, Retention::Events(3));
+		assert_eq!(Plain::RETENTION, Retention::Flushes(1));
+		assert_eq!(events.add_event::<Plain>().capacity(), None);
+	}
+
+	#[cfg(feature = "serde")]
+	#[test]
+	/// test serde implemented by derived events
+	fn event_002()
+	{
+		use crate::registry::Event;
+
+		#[derive(Event, Debug, PartialEq)]
+		#[event(serde)]
+		#[serde(rename_all = "snake_case")]
+		enum Command
+		{
+			Spawn
+			{
+				name: String,
+				#[serde(default)]
+				count: u32,
+			},
+			Quit,
+		}
+
+		let command = Command::Spawn {
+			name: "ship".into(),
+			count: 3,
+		};
+		let bytes = bincode::serialize(&command).unwrap();
+		assert_eq!(bincode::deserialize::<Command>(&bytes).unwrap(), command);
+		let bytes = bincode::serialize(&Command::Quit).unwrap();
+		assert_eq!(
+			bincode::deserialize::<Command>(&bytes).unwrap(),
+			Command::Quit
+		);
+	}
+
+	#[test]
+	/// test draining the events of a channel without other readers
+	fn drain_001()
+	{
+		let channel = Arc::new(SyncEventChannel::<String>::default().with_meta());
+		let writer = channel.get_writer();
+		{
+			let _reader = channel.get_reader().unwrap();
+			assert_eq!(channel.get_owned_drainer().err(), Some(NotExclusive(1)));
+		}
+		let drainer = channel.get_owned_drainer().unwrap();
+		assert_eq!(channel.get_owned_reader().err(), Some(Drained));
+
+		writer.send("a".to_string()).unwrap();
+		writer.send("b".to_string()).unwrap();
+		drainer.flush_channel();
+		let drained: Vec<_> = drainer
+			.drain_with_meta()
+			.map(|(e, meta)| (e, meta.sequence))
+			.collect();
+		assert_eq!(drained, [("a".to_string(), 0), ("b".to_string(), 1)]);
+		assert_eq!(drainer.drain().count(), 0);
+		assert_eq!(channel.stats().reader_lag, [0]);
+
+		writer.send("c".to_string()).unwrap();
+		drainer.flush_channel();
+		drop(drainer);
+		let reader = channel.get_reader().unwrap();
+		assert_eq!(
+			reader.read().collect::<Vec<_>>(),
+			[&"c".to_string()],
+			"not drained"
+		);
+	}
+
+	#[test]
+	fn cursor_001()
+	{
+		let channel = Arc::new(SyncEventChannel::<u32>::default().with_meta());
+		let writer = channel.get_writer();
+		let reader = channel.get_owned_reader().unwrap();
+
+		for i in 0..5 {
+			writer.send(i).unwrap();
+		}
+		reader.flush_channel();
+		assert_eq!(reader.read_n(2).collect::<Vec<_>>(), [&0, &1]);
+		assert_eq!(channel.stats().reader_lag, [1], "partly read");
+		let clone = reader.clone();
+		assert_eq!(clone.peek().collect::<Vec<_>>(), [&2, &3, &4]);
+		assert_eq!(reader.read_n(2).collect::<Vec<_>>(), [&2, &3]);
+		assert_eq!(reader.read_n(2).collect::<Vec<_>>(), [&4]);
+		assert_eq!(reader.read().count(), 0);
+		assert_eq!(clone.read().count(), 3);
+
+		reader.seek(3);
+		assert_eq!(reader.read().collect::<Vec<_>>(), [&3, &4]);
+		let since: Vec<_> = reader.read_since(1).map(|(e, _)| *e).collect();
+		assert_eq!(since, [1, 2, 3, 4]);
+		assert_eq!(reader.peek().count(), 0);
+
+		// a partly read flush is replaced by the next one
+		reader.rewind();
+		reader.read_n(1).count();
+		writer.send(5).unwrap();
+		reader.flush_channel();
+		assert_eq!(reader.read().collect::<Vec<_>>(), [&5]);
+	}
+
+	#[test]
+	/// test counting writers and readers, and closing channels
+	fn close_001()
+	{
+		let channel = EventChannel::<u32>::default();
+		let writer = channel.get_writer();
+		writer.send(1).unwrap();
+		{
+			let _other = channel.get_writer();
+			assert_eq!(channel.get_num_writers(), 2);
+		}
+		assert_eq!(channel.get_num_writers(), 1);
+		let reader = channel.get_reader();
+		assert_eq!(channel.get_num_readers(), 1);
+		writer.send(2).unwrap();
+		channel.close();
+		assert_eq!(writer.send(3), Err(Closed(3)));
+		channel.flush();
+		assert_eq!(reader.try_read().unwrap().collect::<Vec<_>>(), [&1, &2]);
+		assert!(reader.try_read().is_err());
+
+		let channel = EventChannel::<u32>::default();
+		let writer = channel.get_writer();
+		drop(channel.get_reader());
+		assert_eq!(writer.send(1), Err(Closed(1)), "readers dropped");
+
+		let channel = Arc::new(SyncEventChannel::<u32>::bounded(1, Overflow::Block));
+		let reader = channel.get_reader().unwrap();
+		assert!(reader.try_read().is_ok(), "no writer yet");
+		let writer = channel.get_owned_writer();
+		assert_eq!(channel.get_num_readers(), 1);
+		assert_eq!(channel.get_num_writers(), 1);
+		writer.send(1).unwrap();
+		let (sent, blocked) = std::sync::mpsc::channel();
+		thread::spawn(move || {
+			let result = writer.send(2);
+			drop(writer);
+			sent.send(result).unwrap();
+		});
+		while !channel.has_blocked_writers() {
+			thread::yield_now();
+		}
+		channel.close();
+		let woken = blocked.recv_timeout(Duration::from_secs(5));
+		assert_eq!(woken, Ok(Err(SendError::Closed(2))), "close wakes writers");
+
+		assert_eq!(reader.wait_new(), Ok(0), "un-flushed event");
+		assert_eq!(reader.try_read().unwrap().count(), 0);
+		reader.flush_channel();
+		assert_eq!(reader.wait_flushed(), Ok(()));
+		assert_eq!(reader.try_read().unwrap().collect::<Vec<_>>(), [&1]);
+		assert_eq!(reader.wait_new(), Err(Disconnected));
+		assert_eq!(reader.wait_flushed(), Err(Disconnected));
+		assert!(reader.try_read().is_err());
+
+		let channel = Arc::new(SyncEventChannel::<u32>::bounded(1, Overflow::Block));
+		let writer = channel.get_owned_writer();
+		let reader = channel.get_reader().unwrap();
+		writer.send(1).unwrap();
+		let (sent, blocked) = std::sync::mpsc::channel();
+		thread::spawn(move || sent.send(writer.send(2)).unwrap());
+		while !channel.has_blocked_writers() {
+			thread::yield_now();
+		}
+		drop(reader);
+		let woken = blocked.recv_timeout(Duration::from_secs(5));
+		assert_eq!(woken, Ok(Err(SendError::Closed(2))), "readers dropped");
+	}
 }
@@ -0,0 +1,253 @@
+use parking_lot::Mutex;
+use std::sync::Arc;
+use std::task::Waker;
+use std::time::Duration;
+
+use crate::event_close::{Disconnected, SendError};
+use crate::event_drain::Drained;
+use crate::event_meta::EventMeta;
+use crate::schedule::{self, Due};
+use crate::sync_event_channel::{Cursor, EventWaiter, SyncEventChannel, WriterBuffer};
+
+/// Thread-safe event writer owning a handle to its channel
+///
+/// Created by [`SyncEventChannel::get_owned_writer`].
+///
+/// Unlike [`SyncEventWriter`](crate::channel::SyncEventWriter), the writer is
+/// `Send + Sync + 'static`, so it can be moved into threads or stored in
+/// resources and system-local state. Sends from several threads through the
+/// same writer are serialized, so prefer a clone per thread, which gets its
+/// own buffer.
+pub struct OwnedSyncEventWriter<T>
+{
+	channel: Arc<SyncEventChannel<T>>,
+	buffer: Arc<WriterBuffer<T>>,
+	push_lock: Mutex<()>,
+}
+
+/// Thread-safe event reader owning a handle to its channel
+///
+/// Created by [`SyncEventChannel::get_owned_reader`].
+///
+/// Unlike [`SyncEventReader`](crate::channel::SyncEventReader), the reader is
+/// `Send + Sync + 'static`, and keeps track of what it has read on its own.
+/// A clone starts out having read what the original has read.
+pub struct OwnedSyncEventReader<T>
+{
+	pub(crate) cursor: Arc<Cursor>,
+	pub(crate) channel: Arc<SyncEventChannel<T>>,
+}
+
+impl<T> SyncEventChannel<T>
+{
+	/// Gets a writer that holds a clone of the `Arc` of the channel
+	pub fn get_owned_writer(self: &Arc<Self>) -> OwnedSyncEventWriter<T>
+	{
+		OwnedSyncEventWriter {
+			channel: Arc::clone(self),
+			buffer: self.add_writer(),
+			push_lock: Mutex::new(()),
+		}
+	}
+
+	/// Gets a reader that holds a clone of the `Arc` of the channel
+	///
+	/// Fails if the channel has a drainer.
+	pub fn get_owned_reader(self: &Arc<Self>) -> Result<OwnedSyncEventReader<T>, Drained>
+	{
+		Ok(OwnedSyncEventReader {
+			cursor: self.add_reader()?,
+			channel: Arc::clone(self),
+		})
+	}
+}
+
+impl<T> OwnedSyncEventWriter<T>
+{
+	/// Sends the event to the channel
+	///
+	/// See [`SyncEventWriter::send`](crate::channel::SyncEventWriter::send).
+	pub fn send(&self, event: T) -> Result<(), SendError<T>> { self.push(event) }
+
+	/// Id of the writer, unique within its channel
+	///
+	/// See [`SyncEventWriter::id`](crate::channel::SyncEventWriter::id).
+	pub fn id(&self) -> usize { self.buffer.id() }
+
+	/// Gets the channel the writer sends to
+	pub fn channel(&self) -> &Arc<SyncEventChannel<T>> { &self.channel }
+
+	fn push(&self, event: T) -> Result<(), SendError<T>>
+	{
+		let _lock = self.push_lock.lock();
+		unsafe { self.channel.push(&self.buffer, event) }
+	}
+}
+
+impl<T> OwnedSyncEventWriter<T>
+where
+	T: Send + Sync + 'static,
+{
+	/// Sends the event to the channel once `delay` has passed
+	///
+	/// See
+	/// [`SyncEventWriter::send_delayed`](crate::channel::SyncEventWriter::send_delayed).
+	/// The scheduled event keeps the channel alive until it is sent.
+	pub fn send_delayed(&self, event: T, delay: Duration)
+	{
+		self.schedule(event, Due::At(schedule::scheduler().now() + delay));
+	}
+
+	/// Sends the event to the channel when the scheduler reaches `frame`
+	pub fn send_at_frame(&self, event: T, frame: u64) { self.schedule(event, Due::Frame(frame)); }
+
+	fn schedule(&self, event: T, due: Due)
+	{
+		let channel = Arc::clone(&self.channel);
+		let writer = self.id();
+		schedule::scheduler().schedule(due, Box::new(move || channel.send(event, writer)));
+	}
+}
+
+impl<T> Clone for OwnedSyncEventWriter<T>
+{
+	fn clone(&self) -> Self { self.channel.get_owned_writer() }
+}
+
+impl<T> Drop for OwnedSyncEventWriter<T>
+{
+	fn drop(&mut self) { self.channel.remove_writer(&self.buffer); }
+}
+
+impl<T> OwnedSyncEventReader<T>
+{
+	/// Reads all unread events from this channel
+	///
+	/// See [`SyncEventReader::read`](crate::channel::SyncEventReader::read).
+	pub fn read(&self) -> impl Iterator<Item = &T> { self.channel.read_from(&self.cursor) }
+
+	/// Reads all unread events, unless the reader is disconnected
+	///
+	/// See
+	/// [`SyncEventReader::try_read`](crate::channel::SyncEventReader::try_read).
+	pub fn try_read(&self) -> Result<impl Iterator<Item = &T>, Disconnected>
+	{
+		self.channel.read_status(&self.cursor)?;
+		Ok(self.read())
+	}
+
+	/// Reads all unread events, together with their metadata
+	///
+	/// See
+	/// [`SyncEventReader::read_with_meta`](crate::channel::SyncEventReader::read_with_meta).
+	pub fn read_with_meta(&self) -> impl Iterator<Item = (&T, &EventMeta)>
+	{
+		self.channel.read_meta_from(&self.cursor)
+	}
+
+	/// Gets the unread events, without marking them as read
+	///
+	/// See [`SyncEventReader::peek`](crate::channel::SyncEventReader::peek).
+	pub fn peek(&self) -> impl Iterator<Item = &T> { self.channel.peek_from(&self.cursor) }
+
+	/// Reads at most `n` of the unread events
+	///
+	/// See [`SyncEventReader::read_n`](crate::channel::SyncEventReader::read_n).
+	pub fn read_n(&self, n: usize) -> impl Iterator<Item = &T>
+	{
+		self.channel.read_n_from(&self.cursor, n)
+	}
+
+	/// Moves the reader to `position` in the flushed events
+	///
+	/// See [`SyncEventReader::seek`](crate::channel::SyncEventReader::seek).
+	pub fn seek(&self, position: usize) { self.channel.seek_cursor(&self.cursor, position); }
+
+	/// Marks all flushed events as unread, to read them again
+	///
+	/// See [`SyncEventReader::rewind`](crate::channel::SyncEventReader::rewind).
+	pub fn rewind(&self) { self.seek(0); }
+
+	/// Reads the events of the current flush sent as `sequence` or later,
+	/// whether read or not
+	///
+	/// See
+	/// [`SyncEventReader::read_since`](crate::channel::SyncEventReader::read_since).
+	pub fn read_since(&self, sequence: u64) -> impl Iterator<Item = (&T, &EventMeta)>
+	{
+		self.channel.read_since_from(&self.cursor, sequence)
+	}
+
+	/// Initiates a flush on the reader's connected channel
+	pub fn flush_channel(&self) { self.channel.flush(); }
+
+	/// Waits for un-flushed events to be present, returns number of active
+	/// writers
+	///
+	/// See [`SyncEventReader::wait_new`](crate::channel::SyncEventReader::wait_new).
+	pub fn wait_new(&self) -> Result<usize, Disconnected> { self.channel.wait_new() }
+
+	/// Like [`wait_new`](OwnedSyncEventReader::wait_new), with a timeout in ms
+	pub fn wait_new_timeout(&self, timeout_ms: u64) -> Result<usize, Disconnected>
+	{
+		self.channel.wait_new_timeout(timeout_ms)
+	}
+
+	/// Waits for flushed un-read events to be present
+	///
+	/// See
+	/// [`SyncEventReader::wait_flushed`](crate::channel::SyncEventReader::wait_flushed).
+	pub fn wait_flushed(&self) -> Result<(), Disconnected>
+	{
+		self.channel.wait_flushed(&self.cursor)
+	}
+
+	/// Like [`wait_flushed`](OwnedSyncEventReader::wait_flushed), with a
+	/// timeout in ms
+	pub fn wait_flushed_timeout(&self, timeout_ms: u64) -> Result<(), Disconnected>
+	{
+		self.channel.wait_flushed_timeout(&self.cursor, timeout_ms)
+	}
+
+	/// Checks if there are any writers connected to reading channel
+	pub fn channel_has_writers(&self) -> bool { self.channel.has_writers() }
+
+	/// Gets the channel the reader reads from
+	pub fn channel(&self) -> &Arc<SyncEventChannel<T>> { &self.channel }
+}
+
+impl<T> Drop for OwnedSyncEventReader<T>
+{
+	fn drop(&mut self) { self.channel.remove_reader(); }
+}
+
+impl<T> Clone for OwnedSyncEventReader<T>
+{
+	fn clone(&self) -> Self
+	{
+		// a drainer is refused while this reader lives
+		let cursor = self
+			.channel
+			.add_reader()
+			.expect("a channel with readers has no drainer");
+		cursor.copy_from(&self.cursor);
+		OwnedSyncEventReader {
+			cursor,
+			channel: Arc::clone(&self.channel),
+		}
+	}
+}
+
+impl<T> EventWaiter for OwnedSyncEventReader<T>
+{
+	fn add_waker_new(&self, waker: &Waker) -> Result<(), String>
+	{
+		self.channel.add_waker_new(waker)
+	}
+
+	fn get_num_wakers(&self) -> usize { self.channel.get_num_writers() }
+
+	fn has_new(&self) -> bool { self.channel.has_new_events() }
+
+	fn is_disconnected(&self) -> bool { self.channel.is_disconnected() }
+}
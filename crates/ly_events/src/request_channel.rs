@@ -0,0 +1,298 @@
+use crossbeam::sync::Parker;
+use parking_lot::Mutex;
+use std::fmt;
+use std::future::Future;
+use std::ops::Deref;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, Waker};
+use std::time::{Duration, Instant};
+
+use crate::sync_event_channel::{parker_waker, EventWaiter, Waiters};
+use crate::work_queue::{WorkQueue, WorkQueueReader, WorkQueueWriter};
+
+/// Why a [`Reply`] did not give a response
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplyError
+{
+	/// The request was dropped without a response, or the response has
+	/// already been taken
+	NoReply,
+	/// No response before the timeout
+	Timeout,
+}
+
+impl fmt::Display for ReplyError
+{
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+	{
+		match self {
+			ReplyError::NoReply => write!(f, "request was not responded to"),
+			ReplyError::Timeout => write!(f, "timed out waiting for response"),
+		}
+	}
+}
+
+impl std::error::Error for ReplyError {}
+
+/// Thread-safe channel of requests, that are responded to by handlers
+///
+/// Every request is taken by one of the handlers, like in a [`WorkQueue`].
+/// Sending a request gives a [`Reply`] for the response, and the handler
+/// responds through the [`Request`] it took.
+pub struct RequestChannel<Req, Resp>
+{
+	queue: WorkQueue<Request<Req, Resp>>,
+}
+
+/// Sends requests to a [`RequestChannel`]
+///
+/// Created by [`RequestChannel::get_requester`].
+pub struct Requester<'a, Req, Resp>
+{
+	writer: WorkQueueWriter<'a, Request<Req, Resp>>,
+}
+
+/// Takes requests from a [`RequestChannel`] to respond to
+///
+/// Created by [`RequestChannel::get_handler`].
+pub struct RequestHandler<'a, Req, Resp>
+{
+	reader: WorkQueueReader<'a, Request<Req, Resp>>,
+}
+
+/// A request taken by a [`RequestHandler`]
+///
+/// Derefs to the request. Dropping it without responding gives
+/// [`ReplyError::NoReply`] to the requester.
+pub struct Request<Req, Resp>
+{
+	request: Req,
+	reply: Option<Arc<ReplySlot<Resp>>>,
+}
+
+/// The response to a request, once the handler has responded
+///
+/// Created by [`Requester::send_request`]. Can be checked with
+/// [`try_get`](Reply::try_get), waited on, or awaited.
+pub struct Reply<Resp>
+{
+	slot: Arc<ReplySlot<Resp>>,
+}
+
+enum ReplyState<Resp>
+{
+	Pending,
+	Ready(Resp),
+	/// request dropped without response, or response taken
+	Done,
+}
+
+struct ReplySlot<Resp>
+{
+	state: Mutex<ReplyState<Resp>>,
+	waiters: Waiters,
+}
+
+impl<Req, Resp> Default for RequestChannel<Req, Resp>
+{
+	fn default() -> Self
+	{
+		RequestChannel {
+			queue: WorkQueue::default(),
+		}
+	}
+}
+
+impl<Req, Resp> RequestChannel<Req, Resp>
+{
+	/// Creates a requester for this channel
+	pub fn get_requester(&self) -> Requester<'_, Req, Resp>
+	{
+		Requester {
+			writer: self.queue.get_writer(),
+		}
+	}
+
+	/// Creates a handler for this channel
+	pub fn get_handler(&self) -> RequestHandler<'_, Req, Resp>
+	{
+		RequestHandler {
+			reader: self.queue.get_reader(),
+		}
+	}
+
+	/// Number of requests waiting for a handler
+	pub fn len(&self) -> usize { self.queue.len() }
+
+	pub fn is_empty(&self) -> bool { self.queue.is_empty() }
+}
+
+impl<'a, Req, Resp> Requester<'a, Req, Resp>
+{
+	/// Sends the request, to be responded to by one handler
+	pub fn send_request(&self, request: Req) -> Reply<Resp>
+	{
+		let slot = Arc::new(ReplySlot {
+			state: Mutex::new(ReplyState::Pending),
+			waiters: Waiters::default(),
+		});
+		self.writer.send(Request {
+			request,
+			reply: Some(Arc::clone(&slot)),
+		});
+		Reply { slot }
+	}
+}
+
+impl<'a, Req, Resp> RequestHandler<'a, Req, Resp>
+{
+	/// Takes the next request, if there is one
+	pub fn take(&self) -> Option<Request<Req, Resp>> { self.reader.take().map(|work| work.ack()) }
+
+	/// Waits for a request and takes it
+	///
+	/// Returns `None` if there are no requests and no requesters left.
+	pub fn wait_take(&self) -> Option<Request<Req, Resp>>
+	{
+		self.reader.wait_take().map(|work| work.ack())
+	}
+
+	/// Checks if there are any requesters connected to the channel
+	pub fn channel_has_requesters(&self) -> bool { self.reader.queue_has_writers() }
+}
+
+impl<'a, Req, Resp> EventWaiter for RequestHandler<'a, Req, Resp>
+{
+	fn add_waker_new(&self, waker: &Waker) -> Result<(), String>
+	{
+		self.reader.add_waker_new(waker)
+	}
+
+	fn get_num_wakers(&self) -> usize { self.reader.get_num_wakers() }
+
+	fn has_new(&self) -> bool { self.reader.has_new() }
+
+	fn is_disconnected(&self) -> bool { self.reader.is_disconnected() }
+}
+
+impl<Req, Resp> Request<Req, Resp>
+{
+	/// Responds to the request, waking the requester if waiting
+	pub fn respond(mut self, response: Resp)
+	{
+		if let Some(slot) = self.reply.take() {
+			slot.set(ReplyState::Ready(response));
+		}
+	}
+}
+
+impl<Req, Resp> Deref for Request<Req, Resp>
+{
+	type Target = Req;
+
+	fn deref(&self) -> &Req { &self.request }
+}
+
+impl<Req, Resp> Drop for Request<Req, Resp>
+{
+	fn drop(&mut self)
+	{
+		if let Some(slot) = self.reply.take() {
+			slot.set(ReplyState::Done);
+		}
+	}
+}
+
+impl<Resp> ReplySlot<Resp>
+{
+	/// Sets the state, and wakes the waiters
+	///
+	/// The waiters check the state after being added, so no wakeup is missed.
+	fn set(&self, state: ReplyState<Resp>)
+	{
+		*self.state.lock() = state;
+		self.waiters.signal_if_waiting();
+	}
+
+	fn take(&self) -> Option<Result<Resp, ReplyError>>
+	{
+		let mut state = self.state.lock();
+		match std::mem::replace(&mut *state, ReplyState::Done) {
+			ReplyState::Pending => {
+				*state = ReplyState::Pending;
+				None
+			}
+			ReplyState::Ready(response) => Some(Ok(response)),
+			ReplyState::Done => Some(Err(ReplyError::NoReply)),
+		}
+	}
+
+	fn add_waker(&self, waker: &Waker) -> Result<(), ()>
+	{
+		self.waiters
+			.add_unless(waker, || !matches!(*self.state.lock(), ReplyState::Pending))
+	}
+}
+
+impl<Resp> Reply<Resp>
+{
+	/// Checks if the handler has responded, or dropped the request
+	pub fn is_ready(&self) -> bool { !matches!(*self.slot.state.lock(), ReplyState::Pending) }
+
+	/// Takes the response without waiting
+	///
+	/// Gives `None` while the request is not yet responded to.
+	pub fn try_get(&self) -> Option<Result<Resp, ReplyError>> { self.slot.take() }
+
+	/// Waits for the response
+	pub fn wait(self) -> Result<Resp, ReplyError> { self.wait_until(None) }
+
+	/// Waits for the response, at most `timeout`
+	///
+	/// The response can still be had after [`ReplyError::Timeout`].
+	pub fn wait_timeout(&self, timeout: Duration) -> Result<Resp, ReplyError>
+	{
+		self.wait_until(Some(Instant::now() + timeout))
+	}
+
+	fn wait_until(&self, deadline: Option<Instant>) -> Result<Resp, ReplyError>
+	{
+		let p = Parker::new();
+		let waker = parker_waker(&p);
+		loop {
+			if let Some(result) = self.slot.take() {
+				return result;
+			}
+			if self.slot.add_waker(&waker).is_ok() {
+				match deadline {
+					Some(deadline) => {
+						let now = Instant::now();
+						if now >= deadline {
+							return Err(ReplyError::Timeout);
+						}
+						p.park_timeout(deadline - now);
+					}
+					None => p.park(),
+				}
+			}
+		}
+	}
+}
+
+impl<Resp> Future for Reply<Resp>
+{
+	type Output = Result<Resp, ReplyError>;
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output>
+	{
+		loop {
+			if let Some(result) = self.slot.take() {
+				return Poll::Ready(result);
+			}
+			if self.slot.add_waker(cx.waker()).is_ok() {
+				return Poll::Pending;
+			}
+		}
+	}
+}
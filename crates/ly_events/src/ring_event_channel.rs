@@ -0,0 +1,366 @@
+use parking_lot::{Mutex, RwLock, RwLockReadGuard};
+use std::cell::{Cell, UnsafeCell};
+use std::collections::vec_deque::Iter;
+use std::collections::VecDeque;
+use std::fmt;
+
+/// How many flushed events a ring channel keeps readable
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Retention
+{
+	/// Keep the events of the last `n` flushes
+	Flushes(usize),
+	/// Keep the last `n` flushed events
+	Events(usize),
+}
+
+/// A reader fell behind the retention of its channel
+///
+/// Holds the number of events the reader missed. The reader continues from
+/// the oldest retained event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Lagged(pub u64);
+
+impl fmt::Display for Lagged
+{
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+	{
+		write!(f, "reader lagged by {} events", self.0)
+	}
+}
+
+impl std::error::Error for Lagged {}
+
+/// Flushed events of a ring channel, numbered by sequence
+struct Ring<T>
+{
+	retention: Retention,
+	events: VecDeque<T>,
+	/// sequence of the first event in `events`
+	first_seq: u64,
+	/// sequence of the first event of each retained flush
+	flush_starts: VecDeque<u64>,
+}
+
+impl<T> Ring<T>
+{
+	fn new(retention: Retention) -> Self
+	{
+		Ring {
+			retention,
+			events: VecDeque::new(),
+			first_seq: 0,
+			flush_starts: VecDeque::new(),
+		}
+	}
+
+	fn end_seq(&self) -> u64 { self.first_seq + self.events.len() as u64 }
+
+	/// Makes `events` readable, dropping what is no longer retained
+	fn push_flush(&mut self, events: &mut Vec<T>)
+	{
+		self.flush_starts.push_back(self.end_seq());
+		self.events.extend(events.drain(..));
+
+		let keep_from = match self.retention {
+			Retention::Flushes(n) => {
+				while self.flush_starts.len() > n {
+					self.flush_starts.pop_front();
+				}
+				self.flush_starts.front().copied().unwrap_or(self.end_seq())
+			}
+			Retention::Events(n) => {
+				self.flush_starts.clear();
+				self.end_seq().saturating_sub(n as u64)
+			}
+		};
+
+		while self.first_seq < keep_from {
+			self.events.pop_front();
+			self.first_seq += 1;
+		}
+	}
+
+	/// Moves `cursor` to the oldest retained event if it fell behind,
+	/// returning the number of events it missed
+	fn catch_up(&self, cursor: &Cell<u64>) -> Result<(), Lagged>
+	{
+		let at = cursor.get();
+		if at < self.first_seq {
+			cursor.set(self.first_seq);
+			return Err(Lagged(self.first_seq - at));
+		}
+		Ok(())
+	}
+
+	/// Gets the unread events from `cursor`, and moves it to the end
+	fn read_from(&self, cursor: &Cell<u64>) -> Iter<'_, T>
+	{
+		let start = (cursor.get() - self.first_seq) as usize;
+		cursor.set(self.end_seq());
+		self.events.range(start..)
+	}
+
+	fn unread(&self, cursor: &Cell<u64>) -> u64 { self.end_seq().saturating_sub(cursor.get()) }
+}
+
+/// Single-threaded event channel retaining events over several flushes
+///
+/// Unlike the [`EventChannel`](crate::channel::EventChannel), a flush does
+/// not drop the previously flushed events, they are kept according to the
+/// [`Retention`] of the channel. Each reader has its own cursor, so a reader
+/// that doesn't read between two flushes still gets all retained events.
+pub struct RingEventChannel<T>
+{
+	ring: UnsafeCell<Ring<T>>,
+	pending: UnsafeCell<Vec<T>>,
+}
+
+/// Reader of a [`RingEventChannel`]
+///
+/// Created by [`RingEventChannel::get_reader`].
+/// Borrows the channel immutably upon creation.
+pub struct RingEventReader<'a, T>
+{
+	cursor: Cell<u64>,
+	channel: &'a RingEventChannel<T>,
+}
+
+impl<T> RingEventChannel<T>
+{
+	pub fn new(retention: Retention) -> Self
+	{
+		RingEventChannel {
+			ring: UnsafeCell::new(Ring::new(retention)),
+			pending: UnsafeCell::new(Vec::new()),
+		}
+	}
+
+	/// Flushes events on the channel
+	///
+	/// Makes the currently sent un-flushed events readable, and drops the
+	/// flushed events that are no longer retained.
+	pub fn flush(&self)
+	{
+		unsafe {
+			(*self.ring.get()).push_flush(&mut *self.pending.get());
+		}
+	}
+
+	/// Creates a writer for this channel
+	pub fn get_writer(&self) -> RingEventWriter<'_, T> { RingEventWriter { channel: self } }
+
+	/// Creates a reader for this channel
+	///
+	/// The reader starts at the oldest retained event.
+	pub fn get_reader(&self) -> RingEventReader<'_, T>
+	{
+		let first_seq = unsafe { (*self.ring.get()).first_seq };
+		RingEventReader {
+			cursor: Cell::new(first_seq),
+			channel: self,
+		}
+	}
+
+	fn send(&self, event: T)
+	{
+		unsafe {
+			(*self.pending.get()).push(event);
+		}
+	}
+}
+
+/// Writer of a [`RingEventChannel`]
+///
+/// Created by [`RingEventChannel::get_writer`].
+/// Borrows the channel immutably upon creation.
+pub struct RingEventWriter<'a, T>
+{
+	channel: &'a RingEventChannel<T>,
+}
+
+impl<'a, T> RingEventWriter<'a, T>
+{
+	/// Sends the event to the channel
+	pub fn send(&self, event: T) { self.channel.send(event); }
+}
+
+impl<'a, T> RingEventReader<'a, T>
+{
+	/// Reads all unread retained events
+	///
+	/// If the reader fell behind the retention of the channel, it gets
+	/// [`Lagged`] with the number of missed events instead, and the next read
+	/// starts at the oldest retained event.
+	pub fn read(&self) -> Result<impl Iterator<Item = &T>, Lagged>
+	{
+		unsafe {
+			let ring = &*self.channel.ring.get();
+			ring.catch_up(&self.cursor)?;
+			Ok(ring.read_from(&self.cursor))
+		}
+	}
+
+	/// Number of flushed events this reader has not read
+	pub fn unread(&self) -> u64 { unsafe { (*self.channel.ring.get()).unread(&self.cursor) } }
+
+	/// Initiates a flush on the reader's connected channel
+	pub fn flush_channel(&self) { self.channel.flush(); }
+}
+
+/// Thread-safe version of the [`RingEventChannel`]
+pub struct SyncRingEventChannel<T>
+{
+	ring: RwLock<Ring<T>>,
+	pending: Mutex<Vec<T>>,
+}
+
+/// Reader of a [`SyncRingEventChannel`]
+///
+/// Created by [`SyncRingEventChannel::get_reader`].
+/// Borrows the channel immutably upon creation.
+pub struct SyncRingEventReader<'a, T>
+{
+	cursor: Cell<u64>,
+	channel: &'a SyncRingEventChannel<T>,
+}
+
+/// Writer of a [`SyncRingEventChannel`]
+///
+/// Created by [`SyncRingEventChannel::get_writer`].
+/// Borrows the channel immutably upon creation.
+pub struct SyncRingEventWriter<'a, T>
+{
+	channel: &'a SyncRingEventChannel<T>,
+}
+
+impl<T> SyncRingEventChannel<T>
+{
+	pub fn new(retention: Retention) -> Self
+	{
+		SyncRingEventChannel {
+			ring: RwLock::new(Ring::new(retention)),
+			pending: Mutex::new(Vec::new()),
+		}
+	}
+
+	/// Flushes events on the channel
+	///
+	/// Waits for readers currently iterating over events to finish.
+	pub fn flush(&self)
+	{
+		let mut pending = self.pending.lock();
+		self.ring.write().push_flush(&mut pending);
+	}
+
+	/// Creates a writer for this channel
+	pub fn get_writer(&self) -> SyncRingEventWriter<'_, T> { SyncRingEventWriter { channel: self } }
+
+	/// Creates a reader for this channel
+	///
+	/// The reader starts at the oldest retained event.
+	pub fn get_reader(&self) -> SyncRingEventReader<'_, T>
+	{
+		SyncRingEventReader {
+			cursor: Cell::new(self.ring.read().first_seq),
+			channel: self,
+		}
+	}
+}
+
+impl<'a, T> SyncRingEventWriter<'a, T>
+{
+	/// Sends the event to the channel
+	pub fn send(&self, event: T) { self.channel.pending.lock().push(event); }
+}
+
+impl<'a, T> SyncRingEventReader<'a, T>
+{
+	/// Reads all unread retained events
+	///
+	/// Like [`RingEventReader::read`], the channel can not be flushed while
+	/// iterating over the events.
+	pub fn read(&self) -> Result<impl Iterator<Item = &T>, Lagged>
+	{
+		let ring = self.channel.ring.read();
+		ring.catch_up(&self.cursor)?;
+		let iterator = ring.read_from(&self.cursor);
+		// the guard is kept alive by the iterator, the events outlive it
+		let iterator: Iter<'a, T> = unsafe { std::mem::transmute(iterator) };
+		Ok(SyncRingIterator {
+			_read_lock: ring,
+			iterator,
+		})
+	}
+
+	/// Number of flushed events this reader has not read
+	pub fn unread(&self) -> u64 { self.channel.ring.read().unread(&self.cursor) }
+
+	/// Initiates a flush on the reader's connected channel
+	pub fn flush_channel(&self) { self.channel.flush(); }
+}
+
+struct SyncRingIterator<'a, T>
+{
+	_read_lock: RwLockReadGuard<'a, Ring<T>>,
+	iterator: Iter<'a, T>,
+}
+
+impl<'a, T> Iterator for SyncRingIterator<'a, T>
+{
+	type Item = &'a T;
+
+	fn next(&mut self) -> Option<Self::Item> { self.iterator.next() }
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+
+	#[test]
+	fn ring_001()
+	{
+		let channel = RingEventChannel::new(Retention::Flushes(2));
+		let writer = channel.get_writer();
+		let slow = channel.get_reader();
+		let fast = channel.get_reader();
+
+		for i in 0..3 {
+			writer.send(2 * i);
+			writer.send(2 * i + 1);
+			channel.flush();
+			if i == 0 {
+				assert_eq!(fast.read().unwrap().collect::<Vec<_>>(), [&0, &1]);
+			}
+		}
+
+		assert_eq!(fast.unread(), 4);
+		assert_eq!(fast.read().unwrap().collect::<Vec<_>>(), [&2, &3, &4, &5]);
+		assert_eq!(fast.read().unwrap().count(), 0, "cannot read twice");
+
+		assert_eq!(slow.read().err(), Some(Lagged(2)), "first flush dropped");
+		assert_eq!(slow.read().unwrap().collect::<Vec<_>>(), [&2, &3, &4, &5]);
+	}
+
+	#[test]
+	fn ring_002()
+	{
+		let channel = SyncRingEventChannel::new(Retention::Events(3));
+		let writer = channel.get_writer();
+		let reader = channel.get_reader();
+
+		for i in 0..5 {
+			writer.send(i);
+			channel.flush();
+		}
+		assert_eq!(reader.read().err(), Some(Lagged(2)));
+		assert_eq!(reader.read().unwrap().collect::<Vec<_>>(), [&2, &3, &4]);
+
+		writer.send(5);
+		channel.flush();
+		let late = channel.get_reader();
+		assert_eq!(late.read().unwrap().collect::<Vec<_>>(), [&3, &4, &5]);
+		assert_eq!(reader.read().unwrap().collect::<Vec<_>>(), [&5]);
+	}
+}
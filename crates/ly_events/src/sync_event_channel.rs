@@ -1,13 +1,26 @@
 use crossbeam::sync::{Parker, Unparker};
 use ly_log::core_prelude::*;
-use parking_lot::{Mutex, RwLock, RwLockReadGuard};
-use std::cell::UnsafeCell;
+use parking_lot::{Mutex, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::any::type_name;
+use std::cell::{Cell, UnsafeCell};
+use std::iter::{self, Zip};
+use std::marker::PhantomData;
+use std::mem::MaybeUninit;
+use std::ops::{ControlFlow, Range};
+use std::ptr;
 use std::slice::Iter;
-use std::sync::atomic::{AtomicUsize, Ordering};
-use std::time::Duration;
+use std::sync::atomic::{self, AtomicBool, AtomicPtr, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Weak};
+use std::task::{Wake, Waker};
+use std::time::{Duration, Instant};
 
-use crate::channel::{EventChannel, ReadableEventBuffer};
-use crate::event_signal;
+use crate::event_bound::{Bound, Overflow};
+use crate::event_close::{Disconnected, SendError};
+use crate::event_coalesce::Coalesce;
+use crate::event_drain::{Drained, NotExclusive};
+use crate::event_meta::{self, EventMeta};
+use crate::event_stats::ChannelStats;
+use crate::schedule::{self, Due};
 
 /// Thread-safe event channel
 ///
@@ -15,15 +28,539 @@ use crate::event_signal;
 /// stack unless wrapped in `Arc` or something similar. This is important
 /// information regarding having the reader and emitting channel in different
 /// threads, keep in mind the reader has a borrow to the channel.
+///
+/// Every writer has its own send buffer, so sending is lock-free and writers
+/// on different threads never contend with each other. The buffers are
+/// merged into the readable events on flush, in the order the events were
+/// sent.
+///
+/// By default the un-flushed events grow without limit. A channel created with
+/// [`bounded`](SyncEventChannel::bounded) holds at most a number of
+/// un-flushed events, and handles sends to a full channel by its
+/// [`Overflow`] policy.
+///
+/// A channel created [`with_meta`](SyncEventChannel::with_meta) records an
+/// [`EventMeta`] for every sent event, read with
+/// [`read_with_meta`](SyncEventReader::read_with_meta).
+///
+/// Statistics of the channel, like the number of events sent and how far
+/// behind the readers are, are had from [`stats`](SyncEventChannel::stats).
+///
+/// A channel that is [closed](SyncEventChannel::close), or has no writers
+/// left, is disconnected for its readers once they have read all events, see
+/// [`Disconnected`].
 pub struct SyncEventChannel<T>
 {
 	channel_id: usize,
-	channel: EventChannel<T>,
-	write_mutex: Mutex<()>,
-	flush_mutex: RwLock<()>,
-	new_event_waiters: UnsafeCell<Vec<Unparker>>,
-	flushed_waiters: UnsafeCell<Vec<Unparker>>,
-	writers: UnsafeCell<AtomicUsize>,
+	events: RwLock<Events<T>>,
+	/// Events not sent by a writer, like scheduled events, and events left by
+	/// dropped writers
+	shared: Mutex<Batch<T>>,
+	has_shared: AtomicBool,
+	/// Send buffers of the live writers, the channel side of the buffers is
+	/// only used while holding the lock
+	buffers: Mutex<Vec<Arc<WriterBuffer<T>>>>,
+	new_event_waiters: Waiters,
+	flushed_waiters: Waiters,
+	writers: AtomicUsize,
+	next_writer_id: AtomicUsize,
+	bound: Option<Bound>,
+	/// Events dropped because the channel was full
+	dropped: AtomicU64,
+	keep_meta: bool,
+	/// Sequence number of the next event, which orders the events of all
+	/// writers on flush, see [`sort_key`]
+	sequence: AtomicU64,
+	/// Read cursors of the live readers
+	readers: Mutex<Vec<Weak<Cursor>>>,
+	/// Number of live readers, including a drainer
+	num_readers: AtomicUsize,
+	/// Set by the first reader, events sent before are kept for the readers
+	/// to come
+	had_readers: AtomicBool,
+	/// Set while a drainer has the channel, which then has no other readers
+	drained: AtomicBool,
+	closed: AtomicBool,
+	/// Time spent blocked, in ns
+	blocked: AtomicU64,
+}
+
+struct Events<T>
+{
+	flushed: Batch<T>,
+	/// Number of flushes, readers compare it to the flush they last read
+	generation: u64,
+	/// Applied to the flushed events on every flush
+	coalesce: Option<Box<dyn Coalesce<T>>>,
+	/// Events taken by flushes
+	taken: u64,
+	/// Events made readable by flushes, after coalescing
+	readable: u64,
+	peak_taken: usize,
+}
+
+const BLOCK_SIZE: usize = 32;
+
+struct Block<T>
+{
+	slots: [UnsafeCell<MaybeUninit<T>>; BLOCK_SIZE],
+	next: AtomicPtr<Block<T>>,
+}
+
+impl<T> Block<T>
+{
+	fn alloc() -> *mut Block<T>
+	{
+		Box::into_raw(Box::new(Block {
+			slots: std::array::from_fn(|_| UnsafeCell::new(MaybeUninit::uninit())),
+			next: AtomicPtr::new(ptr::null_mut()),
+		}))
+	}
+}
+
+/// Position in a [`SendBuffer`], the index in the block is `count % BLOCK_SIZE`
+struct Position<T>
+{
+	block: *mut Block<T>,
+	count: usize,
+}
+
+impl<T> Position<T>
+{
+	/// Gets the index in the block, moving to the next block if at the end
+	///
+	/// The next block must exist.
+	unsafe fn advance(&mut self) -> usize
+	{
+		let index = self.count % BLOCK_SIZE;
+		if index == 0 && self.count != 0 {
+			self.block = (*self.block).next.load(Ordering::Acquire);
+		}
+		index
+	}
+}
+
+/// Un-flushed events of one writer
+///
+/// A single-producer single-consumer queue of blocks. The writer pushes to
+/// the tail without locking, the channel takes from the head while holding
+/// the lock of its send buffers.
+pub(crate) struct SendBuffer<T>
+{
+	/// Number of events pushed, only written by the writer
+	pushed: AtomicUsize,
+	tail: UnsafeCell<Position<T>>,
+	head: UnsafeCell<Position<T>>,
+}
+
+unsafe impl<T: Send> Send for SendBuffer<T> {}
+unsafe impl<T: Send> Sync for SendBuffer<T> {}
+
+impl<T> Default for SendBuffer<T>
+{
+	fn default() -> Self
+	{
+		let block = Block::alloc();
+		SendBuffer {
+			pushed: AtomicUsize::new(0),
+			tail: UnsafeCell::new(Position { block, count: 0 }),
+			head: UnsafeCell::new(Position { block, count: 0 }),
+		}
+	}
+}
+
+impl<T> SendBuffer<T>
+{
+	/// Pushes to the tail, must only be called by the owning writer
+	#[inline]
+	unsafe fn push(&self, event: T)
+	{
+		let tail = &mut *self.tail.get();
+		if tail.count != 0 && tail.count.is_multiple_of(BLOCK_SIZE) {
+			Self::grow(tail);
+		}
+		let index = tail.advance();
+		(*(*tail.block).slots[index].get()).write(event);
+		tail.count += 1;
+		// makes the event visible to the channel
+		self.pushed.store(tail.count, Ordering::Release);
+	}
+
+	/// Adds a block after the full tail block
+	#[cold]
+	unsafe fn grow(tail: &Position<T>)
+	{
+		(*tail.block).next.store(Block::alloc(), Ordering::Release);
+	}
+
+	/// Moves pushed events to `events`, the caller must hold the lock of the
+	/// channel's send buffers
+	///
+	/// Returns the number of moved events.
+	unsafe fn take_into(&self, events: &mut Vec<T>) -> usize
+	{
+		let n = self.pushed.load(Ordering::Acquire) - (*self.head.get()).count;
+		self.take_n_into(events, n);
+		n
+	}
+
+	/// Moves the `n` first events to `events`, there must be at least `n`
+	/// pushed events
+	unsafe fn take_n_into(&self, events: &mut Vec<T>, n: usize)
+	{
+		let head = &mut *self.head.get();
+		let end = head.count + n;
+		events.reserve(n);
+		while head.count < end {
+			// the writer has moved on to the next block if this one is done
+			let block = head.block;
+			let index = head.advance();
+			if head.block != block {
+				drop(Box::from_raw(block));
+			}
+			let n = (BLOCK_SIZE - index).min(end - head.count);
+			let from = (*head.block).slots[index].get() as *const T;
+			ptr::copy_nonoverlapping(from, events.as_mut_ptr().add(events.len()), n);
+			events.set_len(events.len() + n);
+			head.count += n;
+		}
+	}
+
+	/// Passes the `n` first events to `f` in order, there must be at least `n`
+	/// pushed events
+	unsafe fn take_n_each<F>(&self, n: usize, mut f: F)
+	where
+		F: FnMut(T),
+	{
+		let head = &mut *self.head.get();
+		let end = head.count + n;
+		while head.count < end {
+			let block = head.block;
+			let index = head.advance();
+			if head.block != block {
+				drop(Box::from_raw(block));
+			}
+			let n = (BLOCK_SIZE - index).min(end - head.count);
+			let from = (*head.block).slots[index].get() as *const T;
+			for i in 0..n {
+				f(ptr::read(from.add(i)));
+			}
+			head.count += n;
+		}
+	}
+
+	/// Checks for pushed events, the caller must hold the lock of the
+	/// channel's send buffers
+	unsafe fn is_empty(&self) -> bool { self.len() == 0 }
+
+	/// Number of pushed events, the caller must hold the lock of the channel's
+	/// send buffers
+	unsafe fn len(&self) -> usize { self.pushed.load(Ordering::Acquire) - (*self.head.get()).count }
+
+	/// Number of events taken, the caller must hold the lock of the channel's
+	/// send buffers
+	unsafe fn taken(&self) -> usize { (*self.head.get()).count }
+
+	/// Gets the first pushed event without taking it, the caller must hold the
+	/// lock of the channel's send buffers
+	unsafe fn first(&self) -> Option<&T>
+	{
+		if self.is_empty() {
+			return None;
+		}
+		let head = &*self.head.get();
+		let index = head.count % BLOCK_SIZE;
+		let block = match index == 0 && head.count != 0 {
+			true => (*head.block).next.load(Ordering::Acquire),
+			false => head.block,
+		};
+		Some((*(*block).slots[index].get()).assume_init_ref())
+	}
+}
+
+impl<T> Drop for SendBuffer<T>
+{
+	fn drop(&mut self)
+	{
+		unsafe {
+			self.take_into(&mut Vec::new());
+			drop(Box::from_raw((*self.head.get()).block));
+		}
+	}
+}
+
+/// Send buffer of a writer, with the sort keys of its events, and their
+/// metadata if the channel keeps it
+pub(crate) struct WriterBuffer<T>
+{
+	id: usize,
+	events: SendBuffer<T>,
+	/// Keys of the events, see [`sort_key`], with the index of the first event
+	/// they are for, pushed before it
+	///
+	/// A key is only pushed when it differs from the last one, which a lone
+	/// writer rarely does.
+	keys: SendBuffer<(u64, usize)>,
+	/// Last pushed key, only used by the writer
+	last_key: AtomicU64,
+	/// Key of the next event to take, only used while holding the lock of the
+	/// channel's send buffers
+	key: AtomicU64,
+	/// Pushed before the events, so there is metadata for every pushed event
+	metas: Option<SendBuffer<EventMeta>>,
+}
+
+impl<T> WriterBuffer<T>
+{
+	fn new(id: usize, keep_meta: bool) -> Self
+	{
+		WriterBuffer {
+			id,
+			events: SendBuffer::default(),
+			keys: SendBuffer::default(),
+			last_key: AtomicU64::new(u64::MAX),
+			key: AtomicU64::new(0),
+			metas: keep_meta.then(SendBuffer::default),
+		}
+	}
+
+	pub(crate) fn id(&self) -> usize { self.id }
+
+	/// Pushes the event sorted by `key`, must only be called by the owning
+	/// writer
+	#[inline]
+	unsafe fn push(&self, key: u64, event: T)
+	{
+		if self.last_key.load(Ordering::Relaxed) != key {
+			self.keys
+				.push((key, self.events.pushed.load(Ordering::Relaxed)));
+			self.last_key.store(key, Ordering::Relaxed);
+		}
+		self.events.push(event);
+	}
+
+	/// Merges pushed events and their metadata into `batch`, the caller must
+	/// hold the lock of the channel's send buffers
+	unsafe fn take_into(&self, batch: &mut Batch<T>)
+	{
+		let start = batch.events.len();
+		let n = self.events.len();
+		let mut index = self.events.taken();
+		let end = index + n;
+		self.events.take_n_into(&mut batch.events, n);
+		if let Some(metas) = &self.metas {
+			metas.take_n_into(&mut batch.metas, n);
+		}
+		// keys are pushed before their events, but keys of events not yet
+		// seen are left for the next take
+		while index < end {
+			let next = match self.keys.first() {
+				Some(&(key, from)) if from <= index => {
+					self.keys.take_n_each(1, drop);
+					self.key.store(key, Ordering::Relaxed);
+					continue;
+				}
+				Some(&(_, from)) => from.min(end),
+				None => end,
+			};
+			let key = self.key.load(Ordering::Relaxed);
+			batch.keys.resize(batch.keys.len() + next - index, key);
+			index = next;
+		}
+		batch.merge_from(start);
+	}
+
+	unsafe fn is_empty(&self) -> bool { self.events.is_empty() }
+}
+
+/// Events, and their metadata if the channel keeps it
+///
+/// Un-flushed events are kept sorted by their keys, which are dropped on
+/// flush.
+struct Batch<T>
+{
+	events: Vec<T>,
+	keys: Vec<u64>,
+	metas: Vec<EventMeta>,
+}
+
+impl<T> Default for Batch<T>
+{
+	fn default() -> Self
+	{
+		Batch {
+			events: Vec::new(),
+			keys: Vec::new(),
+			metas: Vec::new(),
+		}
+	}
+}
+
+impl<T> Batch<T>
+{
+	fn push(&mut self, key: u64, event: T, meta: Option<EventMeta>)
+	{
+		self.keys.push(key);
+		self.events.push(event);
+		self.metas.extend(meta);
+	}
+
+	/// Moves the events of `other` in, both sorted by key,
+	/// keeping them sorted
+	fn merge(&mut self, other: &mut Batch<T>)
+	{
+		let start = self.events.len();
+		self.events.append(&mut other.events);
+		self.keys.append(&mut other.keys);
+		self.metas.append(&mut other.metas);
+		self.merge_from(start);
+	}
+
+	/// Sorts the events, when the events before and from `start` are sorted
+	/// by key
+	fn merge_from(&mut self, start: usize)
+	{
+		// the events of one writer are usually all sent after those before
+		if start == 0 || start == self.events.len() || self.keys[start - 1] <= self.keys[start] {
+			return;
+		}
+
+		let mut other = Batch {
+			events: self.events.split_off(start),
+			keys: self.keys.split_off(start),
+			metas: self.metas.split_off(start.min(self.metas.len())),
+		};
+		let mut a = std::mem::take(self).into_entries().peekable();
+		let mut b = std::mem::take(&mut other).into_entries().peekable();
+		loop {
+			let next = match (a.peek(), b.peek()) {
+				(Some(x), Some(y)) if y.0 < x.0 => b.next(),
+				(Some(_), _) => a.next(),
+				(None, _) => b.next(),
+			};
+			match next {
+				Some((key, event, meta)) => self.push(key, event, meta),
+				None => break,
+			}
+		}
+	}
+
+	fn into_entries(self) -> impl Iterator<Item = (u64, T, Option<EventMeta>)>
+	{
+		let metas = self.metas.into_iter().map(Some).chain(iter::repeat(None));
+		self.keys
+			.into_iter()
+			.zip(self.events)
+			.zip(metas)
+			.map(|((key, event), meta)| (key, event, meta))
+	}
+
+	fn clear(&mut self)
+	{
+		self.events.clear();
+		self.keys.clear();
+		self.metas.clear();
+	}
+
+	fn remove_first(&mut self)
+	{
+		self.events.remove(0);
+		self.keys.remove(0);
+		if !self.metas.is_empty() {
+			self.metas.remove(0);
+		}
+	}
+}
+
+/// Key sorting the event numbered `sequence` on flush
+///
+/// Events of a lone writer are already in order, so unless the metadata is
+/// kept they are not numbered. They take twice the number of the next event
+/// instead, which sorts them between the events numbered before and after.
+fn sort_key(sequence: u64) -> u64 { 2 * sequence + 1 }
+
+/// Threads and tasks waiting on a channel
+#[derive(Default)]
+pub(crate) struct Waiters
+{
+	/// Set while there are waiters, lets the channel skip locking when there
+	/// are none
+	///
+	/// Waiters set it before checking if they are ready, and signallers check
+	/// it after making them ready, with fences in between on both sides, so
+	/// either the waiter sees the change or the signaller sees the waiter.
+	/// Sends skip the fence, see [`Waiters::signal_if_flagged`].
+	waiting: AtomicBool,
+	wakers: Mutex<Vec<Waker>>,
+}
+
+impl Waiters
+{
+	/// Adds the waker unless `ready` returns true
+	///
+	/// `ready` is checked while holding the lock used by [`Waiters::signal`].
+	/// A waker that would wake the same task as an added one is not added
+	/// again, so a future may add its waker every time it is polled.
+	pub(crate) fn add_unless<F>(&self, waker: &Waker, ready: F) -> Result<(), ()>
+	where
+		F: FnOnce() -> bool,
+	{
+		let mut wakers = self.wakers.lock();
+		self.waiting.store(true, Ordering::Relaxed);
+		atomic::fence(Ordering::SeqCst);
+		if ready() {
+			return Err(());
+		}
+		if !wakers.iter().any(|w| w.will_wake(waker)) {
+			wakers.push(waker.clone());
+		}
+		Ok(())
+	}
+
+	/// Wakes all waiting threads and tasks
+	pub(crate) fn signal(&self)
+	{
+		let wakers = {
+			let mut wakers = self.wakers.lock();
+			self.waiting.store(false, Ordering::Relaxed);
+			std::mem::take(&mut *wakers)
+		};
+		for waker in wakers {
+			waker.wake();
+		}
+	}
+
+	/// Wakes all waiting threads and tasks, if there are any
+	///
+	/// Must be called after the change the waiters wait for.
+	pub(crate) fn signal_if_waiting(&self)
+	{
+		atomic::fence(Ordering::SeqCst);
+		if self.is_waiting() {
+			self.signal();
+		}
+	}
+
+	/// Wakes all waiting threads and tasks, if the flag shows any without a
+	/// fence
+	///
+	/// For sends, which are too frequent to fence. A waiter being added while
+	/// the change is made may be missed, and is then woken by the next call.
+	/// Threads waiting for new events check again after a short while, see
+	/// [`park_checked`].
+	#[inline]
+	pub(crate) fn signal_if_flagged(&self)
+	{
+		if self.is_waiting() {
+			self.signal();
+		}
+	}
+
+	#[inline]
+	fn is_waiting(&self) -> bool { self.waiting.load(Ordering::Relaxed) }
+
+	#[cfg(test)]
+	fn has_waiters(&self) -> bool { !self.wakers.lock().is_empty() }
 }
 
 static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
@@ -31,202 +568,1102 @@ impl<T> Default for SyncEventChannel<T>
 {
 	fn default() -> Self
 	{
-		let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
-		core_debug!("constructing channel {}", id);
-		SyncEventChannel {
-			channel_id: id,
-			channel: EventChannel::default(),
-			write_mutex: Mutex::new(()),
-			flush_mutex: RwLock::new(()),
-			new_event_waiters: UnsafeCell::new(Vec::new()),
-			flushed_waiters: UnsafeCell::new(Vec::new()),
-			writers: UnsafeCell::new(AtomicUsize::new(0)),
+		let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+		core_debug!("constructing channel {}", id);
+		SyncEventChannel {
+			channel_id: id,
+			events: RwLock::new(Events {
+				flushed: Batch::default(),
+				generation: 0,
+				coalesce: None,
+				taken: 0,
+				readable: 0,
+				peak_taken: 0,
+			}),
+			shared: Mutex::new(Batch::default()),
+			has_shared: AtomicBool::new(false),
+			buffers: Mutex::new(Vec::new()),
+			new_event_waiters: Waiters::default(),
+			flushed_waiters: Waiters::default(),
+			writers: AtomicUsize::new(0),
+			next_writer_id: AtomicUsize::new(0),
+			bound: None,
+			dropped: AtomicU64::new(0),
+			keep_meta: false,
+			sequence: AtomicU64::new(0),
+			readers: Mutex::new(Vec::new()),
+			num_readers: AtomicUsize::new(0),
+			had_readers: AtomicBool::new(false),
+			drained: AtomicBool::new(false),
+			closed: AtomicBool::new(false),
+			blocked: AtomicU64::new(0),
+		}
+	}
+}
+
+/// Thread-safe event writer
+///
+/// Created by [`SyncEventChannel::get_writer`].
+/// Borrows the channel immutably upon creation.
+///
+/// The writer can be sent to another thread, but is not `Sync`, as it owns
+/// a buffer that only one thread may push to. Create one writer per thread.
+///
+/// Scheduling events with [`send_delayed`](SyncEventWriter::send_delayed)
+/// and [`send_at_frame`](SyncEventWriter::send_at_frame) needs the channel to
+/// outlive the borrow, as the scheduler sends the event later. It is only
+/// available on writers of channels living for the rest of the program, like
+/// the resources in the `World`, and on
+/// [`OwnedSyncEventWriter`](crate::channel::OwnedSyncEventWriter), like those
+/// of the [`Events`](crate::registry::Events) registry.
+pub struct SyncEventWriter<'a, T>
+{
+	channel: &'a SyncEventChannel<T>,
+	buffer: Arc<WriterBuffer<T>>,
+	_not_sync: PhantomData<Cell<()>>,
+}
+
+/// Thread-safe event reader
+///
+/// Created by [`SyncEventChannel::get_reader`].
+/// Borrows the channel immutably upon creation.
+pub struct SyncEventReader<'a, T>
+{
+	pub(crate) cursor: Arc<Cursor>,
+	pub(crate) channel: &'a SyncEventChannel<T>,
+}
+
+/// Trait for parking the thread and wait for some future event
+///
+/// Trait is intended for use as trait objects in tandemn with [wait_any_new],
+/// but can of course be used for what you like.
+pub trait EventWaiter
+{
+	/// Add the waker to be woken on some future event
+	///
+	/// Returns an error if not all current events are handled
+	fn add_waker_new(&self, waker: &Waker) -> Result<(), String>;
+
+	/// Add the parker to be notified on some future event
+	///
+	/// Returns an error if not all current events are handled
+	fn add_unparker_new(&self, p: &Parker) -> Result<(), String>
+	{
+		self.add_waker_new(&parker_waker(p))
+	}
+
+	/// Get number of things that can wake the waiter
+	fn get_num_wakers(&self) -> usize;
+
+	/// Checks if there are events the waiter would be woken for
+	///
+	/// The default implementation adds a waker that does nothing, override
+	/// it to check without adding one.
+	fn has_new(&self) -> bool { self.add_waker_new(Waker::noop()).is_err() }
+
+	/// Checks if the waiter has no new events, and can not be woken anymore
+	///
+	/// The default implementation checks for no wakers, override it when the
+	/// waiter's wakers may still be created.
+	fn is_disconnected(&self) -> bool { self.get_num_wakers() == 0 && !self.has_new() }
+}
+
+impl<'a, T> EventWaiter for SyncEventReader<'a, T>
+{
+	/// Add the waker to be woken on the next [SyncEventWriter::send].
+	///
+	/// It is advised to use [wait_any_new] or
+	/// [`wait_new_async`](SyncEventReader::wait_new_async) instead, which
+	/// wrap this function.
+	fn add_waker_new(&self, waker: &Waker) -> Result<(), String>
+	{
+		self.channel.add_waker_new(waker)
+	}
+
+	fn get_num_wakers(&self) -> usize { self.channel.get_num_writers() }
+
+	fn has_new(&self) -> bool { self.channel.has_new_events() }
+
+	fn is_disconnected(&self) -> bool { self.channel.is_disconnected() }
+}
+
+/// Where a reader is in a [`SyncEventChannel`], the last flush it has read
+pub(crate) trait ReadCursor
+{
+	fn get(&self) -> u64;
+
+	/// Sets the read flush, returning the previous one
+	fn replace(&self, generation: u64) -> u64;
+}
+
+impl ReadCursor for Cell<u64>
+{
+	fn get(&self) -> u64 { Cell::get(self) }
+
+	fn replace(&self, generation: u64) -> u64 { Cell::replace(self, generation) }
+}
+
+impl ReadCursor for Cursor
+{
+	fn get(&self) -> u64 { self.generation.load(Ordering::Relaxed) }
+
+	fn replace(&self, generation: u64) -> u64
+	{
+		let mut offset = self.offset.lock();
+		*offset = 0;
+		self.generation.swap(generation, Ordering::Relaxed)
+	}
+}
+
+impl<C> ReadCursor for Arc<C>
+where
+	C: ReadCursor,
+{
+	fn get(&self) -> u64 { C::get(self) }
+
+	fn replace(&self, generation: u64) -> u64 { C::replace(self, generation) }
+}
+
+/// Read cursor of the readers of a [`SyncEventChannel`]
+///
+/// The last flush the reader has read all of, and the number of events it has
+/// read of the flush after it, which are only read in part by
+/// [`read_n`](SyncEventReader::read_n) and [`seek`](SyncEventReader::seek).
+#[derive(Default)]
+pub(crate) struct Cursor
+{
+	generation: AtomicU64,
+	/// Also locked while reading and setting `generation` with it, unless the
+	/// flush is all read
+	offset: Mutex<usize>,
+	/// The last flush before the reader was created, its lag is counted from
+	/// there
+	joined: u64,
+}
+
+impl Cursor
+{
+	/// Creates the cursor of a reader created when the last flush is
+	/// `generation`, which it has not read
+	fn joining(generation: u64) -> Self
+	{
+		Cursor {
+			joined: generation.saturating_sub(1),
+			..Cursor::default()
+		}
+	}
+
+	/// Number of flushes up to `generation` not read, since the reader was
+	/// created
+	fn lag(&self, generation: u64) -> u64 { generation - self.get().max(self.joined) }
+
+	/// Gives the range of the unread events of the flush `generation`, of
+	/// `len` events, and marks at most `n` of them as read
+	fn advance(&self, generation: u64, len: usize, n: usize) -> Range<usize>
+	{
+		// most reads find nothing new, and need no lock
+		if self.generation.load(Ordering::Relaxed) == generation {
+			return len..len;
+		}
+		let mut offset = self.offset.lock();
+		let start = self.start(generation, len, *offset);
+		let end = start.saturating_add(n).min(len);
+		self.set(generation, len, end, &mut offset);
+		start..end
+	}
+
+	/// Gives the range of the unread events, without marking them as read
+	fn unread(&self, generation: u64, len: usize) -> Range<usize>
+	{
+		if self.generation.load(Ordering::Relaxed) == generation {
+			return len..len;
+		}
+		let offset = self.offset.lock();
+		self.start(generation, len, *offset)..len
+	}
+
+	/// Marks the events before `position` of the flush as read, and the rest
+	/// as unread
+	fn seek(&self, generation: u64, len: usize, position: usize)
+	{
+		let mut offset = self.offset.lock();
+		self.set(generation, len, position, &mut offset);
+	}
+
+	/// Starts out where `other` is
+	pub(crate) fn copy_from(&self, other: &Cursor)
+	{
+		let other_offset = other.offset.lock();
+		let mut offset = self.offset.lock();
+		*offset = *other_offset;
+		self.generation
+			.store(other.generation.load(Ordering::Relaxed), Ordering::Relaxed);
+	}
+
+	fn start(&self, generation: u64, len: usize, offset: usize) -> usize
+	{
+		match self.generation.load(Ordering::Relaxed) {
+			read if read == generation => len,
+			read if read + 1 == generation => offset,
+			_ => 0,
+		}
+	}
+
+	fn set(&self, generation: u64, len: usize, position: usize, offset: &mut usize)
+	{
+		// a flush read in part counts as unread, like by `has_unread`
+		let (read, position) = match position < len {
+			true => (generation - 1, position),
+			false => (generation, 0),
+		};
+		*offset = position;
+		self.generation.store(read, Ordering::Relaxed);
+	}
+}
+
+/// Wakes a parked thread
+struct UnparkWaker(Unparker);
+
+impl Wake for UnparkWaker
+{
+	fn wake(self: Arc<Self>) { self.0.unpark(); }
+
+	fn wake_by_ref(self: &Arc<Self>) { self.0.unpark(); }
+}
+
+/// Gets a waker that unparks the thread of the parker
+pub(crate) fn parker_waker(p: &Parker) -> Waker
+{
+	Waker::from(Arc::new(UnparkWaker(p.unparker().clone())))
+}
+
+/// How long a thread waiting for new events first parks, before checking for
+/// them again
+///
+/// Sends do not fence before looking for waiters, so an event sent while a
+/// waiter is being added may be missed by both. The event is visible to the
+/// waiter once the short park is over.
+const RACE_TIMEOUT: Duration = Duration::from_millis(1);
+
+/// Parks for `timeout`, or until woken, checking `keep_waiting` after
+/// [`RACE_TIMEOUT`]
+///
+/// `keep_waiting` should add the waker again, in case it was woken, and
+/// return false if it should not wait any longer.
+pub(crate) fn park_checked<F>(p: &Parker, timeout: Option<Duration>, keep_waiting: F)
+where
+	F: FnOnce() -> bool,
+{
+	match timeout {
+		Some(timeout) if timeout <= RACE_TIMEOUT => p.park_timeout(timeout),
+		Some(timeout) => {
+			p.park_timeout(RACE_TIMEOUT);
+			if keep_waiting() {
+				p.park_timeout(timeout - RACE_TIMEOUT);
+			}
+		}
+		None => {
+			p.park_timeout(RACE_TIMEOUT);
+			if keep_waiting() {
+				p.park();
+			}
+		}
+	}
+}
+
+/// Returns false if any waiter already has new events
+pub(crate) fn add_waker_all(waiters: &[&dyn EventWaiter], waker: &Waker) -> bool
+{
+	waiters
+		.iter()
+		.all(|waiter| waiter.add_waker_new(waker).is_ok())
+}
+
+pub(crate) fn accumulate_wakers(waiters: &[&dyn EventWaiter]) -> usize
+{
+	waiters
+		.iter()
+		.fold(0, |acc, waiter| acc + waiter.get_num_wakers())
+}
+
+/// Wait for any events to be sent to the channels of the passed
+/// [`EventWaiter`]s.
+/// Returns the total number of objects capable of waking the waiters.
+///
+/// The trait object is used to enable iteration over multiple channel types,
+/// perhaps there's a better way, but I don't know about it.
+///
+/// If any channels has unread events, it will return directly, without waiting
+pub fn wait_any_new(readers: &[&dyn EventWaiter]) -> usize
+{
+	let mut wakers = 1;
+	while wakers != 0 {
+		let p = Parker::new();
+		let waker = parker_waker(&p);
+		if !add_waker_all(readers, &waker) {
+			return accumulate_wakers(readers);
+		}
+		park_checked(&p, Some(Duration::from_secs(2)), || {
+			add_waker_all(readers, &waker) && accumulate_wakers(readers) != 0
+		});
+		wakers = accumulate_wakers(readers);
+	}
+	wakers
+}
+
+/// Like [`wait_any_new`], but with a timeout in ms
+pub fn wait_any_new_timeout(readers: &[&dyn EventWaiter], timeout_ms: u64) -> usize
+{
+	let p = Parker::new();
+	let waker = parker_waker(&p);
+	if add_waker_all(readers, &waker) {
+		park_checked(&p, Some(Duration::from_millis(timeout_ms)), || {
+			add_waker_all(readers, &waker) && accumulate_wakers(readers) != 0
+		});
+	}
+	accumulate_wakers(readers)
+}
+
+impl<T> SyncEventChannel<T>
+{
+	/// Sends the event to the channel
+	///
+	/// This also wakes any threads waiting for new events via
+	/// [`SyncEventReader::wait_new`].
+	pub(crate) fn send(&self, e: T, writer: usize)
+	{
+		if self.is_closed() {
+			return;
+		}
+		// scheduled events are not bounded, as there is no one to block or
+		// give them back to
+		if let Some(bound) = &self.bound {
+			bound.add_unbounded();
+		}
+		let mut shared = self.shared.lock();
+		// taken while locked, so the shared events stay sorted
+		let sequence = self.next_sequence();
+		let meta = self.keep_meta.then(|| self.next_meta(writer, sequence));
+		shared.push(sort_key(sequence), e, meta);
+		self.has_shared.store(true, Ordering::Relaxed);
+		drop(shared);
+		self.new_event_waiters.signal_if_waiting();
+	}
+
+	/// Flushes the channel
+	///
+	/// Makes the currently sent un-flushed events readable.
+	///
+	/// This drops all previously flushed events, making them unreadable. The
+	/// rule set with [`set_coalesce`](SyncEventChannel::set_coalesce) is
+	/// applied to the new events.
+	///
+	/// This also wakes any threads waiting for a flush via
+	/// [`SyncEventReader::wait_flushed`].
+	///
+	/// Is is adviced to let one of the readers initiate the flush with
+	/// [`SyncEventReader::flush_channel`],
+	/// as they are controlling consumation of events.
+	pub fn flush(&self)
+	{
+		let (waiting, taken) = {
+			let mut events = self.write_events();
+			let events = &mut *events;
+			events.flushed.clear();
+			{
+				let buffers = self.buffers.lock();
+				if self.has_shared.load(Ordering::Relaxed) {
+					self.take_shared(&mut events.flushed);
+				}
+				for buffer in buffers.iter() {
+					unsafe { buffer.take_into(&mut events.flushed) };
+				}
+			}
+			events.flushed.keys.clear();
+			let taken = events.flushed.events.len();
+			if let Some(rule) = &events.coalesce {
+				let flushed = &mut events.flushed;
+				match self.keep_meta {
+					true => rule.coalesce_with_meta(&mut flushed.events, &mut flushed.metas),
+					false => rule.coalesce(&mut flushed.events),
+				}
+			}
+			events.taken += taken as u64;
+			events.readable += events.flushed.events.len() as u64;
+			events.peak_taken = events.peak_taken.max(taken);
+			events.generation += 1;
+			(self.flushed_waiters.is_waiting(), taken)
+		};
+		if let Some(bound) = &self.bound {
+			bound.release(taken);
+			bound.space_waiters.signal_if_waiting();
+		}
+		if waiting {
+			self.flushed_waiters.signal();
+		}
+	}
+
+	/// Creates a channel holding at most `capacity` un-flushed events
+	///
+	/// Events sent by writers to a full channel are handled by `overflow`.
+	/// Events sent with a delay are always added, but count towards the
+	/// capacity. Dropped events are counted by
+	/// [`dropped`](SyncEventChannel::dropped), and logged as warnings at most
+	/// once per second.
+	pub fn bounded(capacity: usize, overflow: Overflow) -> Self
+	{
+		SyncEventChannel {
+			bound: Some(Bound::new(capacity, overflow)),
+			..Self::default()
+		}
+	}
+
+	/// Makes the channel record an [`EventMeta`] for every sent event
+	///
+	/// The metadata is read with
+	/// [`read_with_meta`](SyncEventReader::read_with_meta). Keeping it costs
+	/// some time on every send.
+	/// ```
+	/// # use ly_events::channel::SyncEventChannel;
+	/// let channel = SyncEventChannel::<char>::default().with_meta();
+	/// let writer = channel.get_writer();
+	/// let reader = channel.get_reader().unwrap();
+	///
+	/// writer.send('a');
+	/// writer.send('b');
+	/// reader.flush_channel();
+	/// for (i, (event, meta)) in reader.read_with_meta().enumerate() {
+	///     assert_eq!(meta.sequence, i as u64);
+	///     assert_eq!(meta.writer, writer.id());
+	/// }
+	/// ```
+	pub fn with_meta(mut self) -> Self
+	{
+		self.keep_meta = true;
+		self
+	}
+
+	/// Checks if the channel records metadata of its events
+	pub fn has_meta(&self) -> bool { self.keep_meta }
+
+	/// Gets the capacity of a bounded channel
+	pub fn capacity(&self) -> Option<usize> { self.bound.as_ref().map(|bound| bound.capacity) }
+
+	/// Number of events dropped because the channel was full
+	pub fn dropped(&self) -> u64 { self.dropped.load(Ordering::Relaxed) }
+
+	/// Sets the rule to coalesce the events of every flush with
+	///
+	/// Replaces any previous rule, and applies from the next flush.
+	pub fn set_coalesce<C>(&self, rule: C)
+	where
+		C: Coalesce<T> + 'static,
+	{
+		self.write_events().coalesce = Some(Box::new(rule));
+	}
+
+	/// Creates a writer for this channel
+	pub fn get_writer(&self) -> SyncEventWriter<T>
+	{
+		core_debug!("getting writer for channel {}", self.channel_id);
+		SyncEventWriter {
+			channel: self,
+			buffer: self.add_writer(),
+			_not_sync: PhantomData,
+		}
+	}
+
+	/// Creates a reader for this channel
+	///
+	/// Fails if the channel has a drainer.
+	pub fn get_reader(&self) -> Result<SyncEventReader<T>, Drained>
+	{
+		core_debug!("getting reader for channel {}", self.channel_id);
+		Ok(SyncEventReader {
+			cursor: self.add_reader()?,
+			channel: self,
+		})
+	}
+
+	/// Adds the send buffer of a new writer
+	///
+	/// Does not lock the events, so writers can be created while reading.
+	pub(crate) fn add_writer(&self) -> Arc<WriterBuffer<T>>
+	{
+		self.writers.fetch_add(1, Ordering::Relaxed);
+		let id = self.next_writer_id.fetch_add(1, Ordering::Relaxed);
+		let buffer = Arc::new(WriterBuffer::new(id, self.keep_meta));
+		self.buffers.lock().push(Arc::clone(&buffer));
+		buffer
+	}
+
+	/// Sends the event from the send buffer of a writer
+	///
+	/// Only one thread at a time may push to a send buffer.
+	///
+	/// Gives back the event if the channel is closed or its readers are all
+	/// dropped, or if it is full with the [`Overflow::Error`] policy.
+	#[inline(always)]
+	pub(crate) unsafe fn push(&self, buffer: &WriterBuffer<T>, event: T)
+		-> Result<(), SendError<T>>
+	{
+		if self.refuses_events() {
+			return Err(SendError::Closed(event));
+		}
+		let event = match &self.bound {
+			Some(bound) if !bound.try_reserve() => match self.overflow(bound, event) {
+				ControlFlow::Continue(event) => event,
+				ControlFlow::Break(result) => return result,
+			},
+			_ => event,
+		};
+		let key = match self.keep_meta || self.next_writer_id.load(Ordering::Relaxed) > 1 {
+			true => self.sequence_into(buffer),
+			false => 2 * self.sequence.load(Ordering::Relaxed),
+		};
+		buffer.push(key, event);
+		self.new_event_waiters.signal_if_flagged();
+		Ok(())
+	}
+
+	/// Handles an event sent to the full channel with its overflow policy
+	///
+	/// Continues with the event if room was made for it, or breaks with the
+	/// result of the send.
+	#[cold]
+	fn overflow(&self, bound: &Bound, event: T) -> ControlFlow<Result<(), SendError<T>>, T>
+	{
+		match bound.overflow {
+			Overflow::Block if self.wait_space(bound) => ControlFlow::Continue(event),
+			Overflow::Block => ControlFlow::Break(Err(SendError::Closed(event))),
+			// takes over the room of the dropped event
+			Overflow::DropOldest if self.drop_oldest() => ControlFlow::Continue(event),
+			Overflow::DropOldest | Overflow::DropNewest => {
+				self.count_dropped();
+				ControlFlow::Break(Ok(()))
+			}
+			Overflow::Error => ControlFlow::Break(Err(SendError::Full(event))),
+		}
+	}
+
+	/// Numbers the next event of the send buffer, pushing its metadata if the
+	/// channel keeps it, and gives its key
+	///
+	/// Only one thread at a time may push to a send buffer.
+	unsafe fn sequence_into(&self, buffer: &WriterBuffer<T>) -> u64
+	{
+		let sequence = self.next_sequence();
+		if let Some(metas) = &buffer.metas {
+			metas.push(self.next_meta(buffer.id, sequence));
+		}
+		sort_key(sequence)
+	}
+
+	/// Gets the sequence number of a sent event
+	///
+	/// An event sent after another, by any writer, gets a larger number.
+	fn next_sequence(&self) -> u64 { self.sequence.fetch_add(1, Ordering::Relaxed) }
+
+	fn next_meta(&self, writer: usize, sequence: u64) -> EventMeta
+	{
+		EventMeta {
+			timestamp: Instant::now(),
+			frame: schedule::scheduler().current_frame(),
+			sequence,
+			order: event_meta::next_order(),
+			writer,
+		}
+	}
+
+	/// Waits until room for an event is reserved
+	///
+	/// Gives false if the channel is closed while waiting.
+	fn wait_space(&self, bound: &Bound) -> bool
+	{
+		let start = Instant::now();
+		let p = Parker::new();
+		let waker = parker_waker(&p);
+		let add_waker = || {
+			bound
+				.space_waiters
+				.add_unless(&waker, || bound.has_space() || self.refuses_events())
+				.is_ok()
+		};
+		while !bound.try_reserve() {
+			if self.refuses_events() {
+				return false;
+			}
+			if add_waker() {
+				p.park();
+			}
+		}
+		self.add_blocked(start.elapsed());
+		true
+	}
+
+	/// Write-locks the events, counting the time spent waiting for the lock
+	fn write_events(&self) -> RwLockWriteGuard<'_, Events<T>>
+	{
+		if let Some(events) = self.events.try_write() {
+			return events;
+		}
+		let start = Instant::now();
+		let events = self.events.write();
+		self.add_blocked(start.elapsed());
+		events
+	}
+
+	fn add_blocked(&self, time: Duration)
+	{
+		self.blocked
+			.fetch_add(time.as_nanos() as u64, Ordering::Relaxed);
+	}
+
+	/// Adds the read cursor of a new reader, unless the channel is drained
+	pub(crate) fn add_reader(&self) -> Result<Arc<Cursor>, Drained>
+	{
+		let cursor = Arc::new(Cursor::joining(self.last_flush()));
+		let mut readers = self.readers.lock();
+		if self.drained.load(Ordering::Relaxed) {
+			return Err(Drained);
+		}
+		readers.retain(|reader| reader.strong_count() != 0);
+		readers.push(Arc::downgrade(&cursor));
+		self.num_readers.fetch_add(1, Ordering::Relaxed);
+		self.had_readers.store(true, Ordering::Relaxed);
+		Ok(cursor)
+	}
+
+	/// Counts a dropped reader, waking the writers blocked on a full channel
+	/// when it was the last, as the channel then refuses events
+	pub(crate) fn remove_reader(&self)
+	{
+		if self.num_readers.fetch_sub(1, Ordering::Relaxed) == 1 {
+			if let Some(bound) = &self.bound {
+				bound.space_waiters.signal();
+			}
+		}
+	}
+
+	/// Registers the read cursor of a drainer, unless there are other readers
+	pub(crate) fn add_drainer(&self) -> Result<Arc<Cursor>, NotExclusive>
+	{
+		let cursor = Arc::new(Cursor::joining(self.last_flush()));
+		let mut readers = self.readers.lock();
+		let num_readers = self.num_readers.load(Ordering::Relaxed);
+		if num_readers != 0 {
+			return Err(NotExclusive(num_readers));
+		}
+		self.drained.store(true, Ordering::Relaxed);
+		readers.retain(|reader| reader.strong_count() != 0);
+		readers.push(Arc::downgrade(&cursor));
+		self.num_readers.fetch_add(1, Ordering::Relaxed);
+		self.had_readers.store(true, Ordering::Relaxed);
+		Ok(cursor)
+	}
+
+	/// Lets the channel have readers again, when its drainer is dropped
+	pub(crate) fn remove_drainer(&self)
+	{
+		{
+			let _readers = self.readers.lock();
+			self.drained.store(false, Ordering::Relaxed);
+		}
+		self.remove_reader();
+	}
+
+	/// Closes the channel, waking all waiting readers and writers
+	///
+	/// Events sent from then on are given back by
+	/// [`send`](SyncEventWriter::send). The events sent
+	/// before can still be flushed and read, after that the readers are
+	/// [`Disconnected`].
+	pub fn close(&self)
+	{
+		self.closed.store(true, Ordering::Release);
+		self.new_event_waiters.signal();
+		self.flushed_waiters.signal();
+		if let Some(bound) = &self.bound {
+			bound.space_waiters.signal();
 		}
 	}
-}
 
-/// Thread-safe event writer
-///
-/// Created by [`SyncEventChannel::get_writer`].
-/// Borrows the channel immutably upon creation.
-pub struct SyncEventWriter<'a, T>
-{
-	channel: &'a SyncEventChannel<T>,
-}
+	pub fn is_closed(&self) -> bool { self.closed.load(Ordering::Acquire) }
 
-/// Thread-safe event reader
-///
-/// Created by [`SyncEventChannel::get_reader`].
-/// Borrows the channel immutably upon creation.
-pub struct SyncEventReader<'a, T>
-{
-	read_events: UnsafeCell<usize>,
-	channel: &'a SyncEventChannel<T>,
-}
+	/// Checks if a writer is waiting for room in the bounded channel
+	#[cfg(test)]
+	pub(crate) fn has_blocked_writers(&self) -> bool
+	{
+		self.bound
+			.as_ref()
+			.is_some_and(|bound| bound.space_waiters.has_waiters())
+	}
 
-unsafe impl<T> Sync for SyncEventChannel<T> {}
+	/// Gets the number of live readers, including a drainer
+	pub fn get_num_readers(&self) -> usize { self.num_readers.load(Ordering::Relaxed) }
 
-/// Trait for parking the thread and wait for some future event
-///
-/// Trait is intended for use as trait objects in tandemn with [wait_any_new],
-/// but can of course be used for what you like.
-pub trait EventWaiter
-{
-	/// Add the parker to be notified on some future event
+	/// Checks if events sent now are given back: the channel is closed or its
+	/// readers are all dropped
+	fn refuses_events(&self) -> bool
+	{
+		let readers_dropped =
+			self.get_num_readers() == 0 && self.had_readers.load(Ordering::Relaxed);
+		self.is_closed() || readers_dropped
+	}
+
+	/// Checks if no more events can be flushed: the channel is closed or its
+	/// writers are all dropped, and has no un-flushed events
 	///
-	/// Returns an error if not all current events are handled
-	fn add_unparker_new(&self, p: &Parker) -> Result<(), String>;
+	/// A channel that never had a writer is not disconnected, its writers are
+	/// still to come.
+	pub(crate) fn is_disconnected(&self) -> bool
+	{
+		let writers_dropped =
+			!self.has_writers() && self.next_writer_id.load(Ordering::Relaxed) != 0;
+		(self.is_closed() || writers_dropped) && !self.has_new_events()
+	}
 
-	/// Get number of things that can wake the waiter
-	fn get_num_wakers(&self) -> usize;
-}
+	/// Gives [`Disconnected`] if `cursor` has read all events, and no more can
+	/// be flushed
+	pub(crate) fn read_status<C>(&self, cursor: &C) -> Result<(), Disconnected>
+	where
+		C: ReadCursor,
+	{
+		self.flushed_status(cursor.get())
+	}
 
-impl<'a, T> EventWaiter for SyncEventReader<'a, T>
-{
-	/// Add the parker to be notified on the next [SyncEventWriter::send].
-	///
-	/// It is advised to use [wait_any_new] instead, which wraps this function.
-	fn add_unparker_new(&self, p: &Parker) -> Result<(), String>
+	/// Gives [`Disconnected`] if there is no flush after `generation`, and no
+	/// more can be flushed
+	pub(crate) fn flushed_status(&self, generation: u64) -> Result<(), Disconnected>
 	{
-		let _lock = self.channel.write_mutex.lock();
-		if self.channel.has_new_events() {
-			return Err("already new unflushed events".to_string());
+		match !self.has_flushed_since(generation) && self.is_disconnected() {
+			true => Err(Disconnected),
+			false => Ok(()),
 		}
+	}
 
-		unsafe {
-			event_signal::add_waiter(&mut *self.channel.new_event_waiters.get(), p);
-			Ok(())
+	/// Takes a snapshot of the statistics of the channel
+	pub fn stats(&self) -> ChannelStats
+	{
+		let events = self.events.read();
+		let pending = self.shared.lock().events.len()
+			+ self
+				.buffers
+				.lock()
+				.iter()
+				.map(|buffer| unsafe { buffer.events.len() })
+				.sum::<usize>();
+		let reader_lag: Vec<_> = self
+			.readers
+			.lock()
+			.iter()
+			.filter_map(Weak::upgrade)
+			.map(|cursor| cursor.lag(events.generation))
+			.collect();
+		ChannelStats {
+			channel_id: self.channel_id,
+			event_type: type_name::<T>(),
+			sent: events.taken + pending as u64,
+			flushed: events.readable,
+			dropped: self.dropped(),
+			flushes: events.generation,
+			pending,
+			peak_pending: events.peak_taken,
+			writers: self.get_num_writers(),
+			readers: reader_lag.len(),
+			reader_lag,
+			blocked: Duration::from_nanos(self.blocked.load(Ordering::Relaxed)),
 		}
 	}
 
-	fn get_num_wakers(&self) -> usize { self.channel.get_num_writers() }
-}
+	/// Drops the oldest un-flushed event, returns false if there is none
+	///
+	/// The events of all writers are merged into the shared events, so the
+	/// oldest is the first of them.
+	fn drop_oldest(&self) -> bool
+	{
+		let buffers = self.buffers.lock();
+		let mut shared = self.shared.lock();
+		for buffer in buffers.iter() {
+			unsafe { buffer.take_into(&mut shared) };
+		}
+		if shared.events.is_empty() {
+			return false;
+		}
+		shared.remove_first();
+		self.has_shared.store(true, Ordering::Relaxed);
+		self.count_dropped();
+		true
+	}
 
-fn accumulate_wakers(waiters: &[&dyn EventWaiter]) -> usize
-{
-	waiters
-		.iter()
-		.fold(0, |acc, waiter| acc + waiter.get_num_wakers())
-}
+	/// Counts an event dropped because the channel was full
+	pub(crate) fn count_dropped(&self)
+	{
+		self.dropped.fetch_add(1, Ordering::Relaxed);
+		if let Some(bound) = &self.bound {
+			bound.warn_dropped(self.channel_id);
+		}
+	}
 
-/// Wait for any events to be sent to the channels of the passed
-/// [`EventWaiter`]s.
-/// Returns the total number of objects capable of waking the waiters.
-///
-/// The trait object is used to enable iteration over multiple channel types,
-/// perhaps there's a better way, but I don't know about it.
-///
-/// If any channels has unread events, it will return directly, without waiting
-pub fn wait_any_new(readers: &[&dyn EventWaiter]) -> usize
-{
-	let mut wakers = 1;
-	while wakers != 0 {
-		let p = Parker::new();
-		for reader in readers {
-			if reader.add_unparker_new(&p).is_err() {
-				return accumulate_wakers(readers);
+	/// Removes the send buffer of a dropped writer
+	///
+	/// Un-flushed events of the writer are kept, to be read on the next flush.
+	/// Does not lock the events, like
+	/// [`add_writer`](SyncEventChannel::add_writer).
+	pub(crate) fn remove_writer(&self, buffer: &Arc<WriterBuffer<T>>)
+	{
+		{
+			let mut buffers = self.buffers.lock();
+			buffers.retain(|b| !Arc::ptr_eq(b, buffer));
+			let mut shared = self.shared.lock();
+			unsafe { buffer.take_into(&mut shared) };
+			if !shared.events.is_empty() {
+				self.has_shared.store(true, Ordering::Relaxed);
 			}
 		}
-		p.park_timeout(Duration::from_secs(2));
-		wakers = accumulate_wakers(readers);
+
+		if self.writers.fetch_sub(1, Ordering::Relaxed) == 1 {
+			self.new_event_waiters.signal();
+			self.flushed_waiters.signal();
+		}
 	}
-	wakers
-}
 
-/// Like [`wait_any_new`], but with a timeout in ms
-pub fn wait_any_new_timeout(readers: &[&dyn EventWaiter], timeout_ms: u64) -> usize
-{
-	let p = Parker::new();
-	for reader in readers {
-		if reader.add_unparker_new(&p).is_err() {
-			return accumulate_wakers(readers);
+	/// Reads the flushed events that `cursor` has not read
+	pub(crate) fn read_from(&self, cursor: &Cursor) -> SyncEventIterator<'_, T>
+	{
+		self.read_n_from(cursor, usize::MAX)
+	}
+
+	/// Reads at most `n` of the flushed events that `cursor` has not read
+	pub(crate) fn read_n_from(&self, cursor: &Cursor, n: usize) -> SyncEventIterator<'_, T>
+	{
+		let read_lock = self.events.read();
+		let range = cursor.advance(read_lock.generation, read_lock.flushed.events.len(), n);
+		let events = unsafe { detach_slice(&read_lock.flushed.events[range]) };
+		SyncEventIterator {
+			read_lock,
+			iterator: events.iter(),
 		}
 	}
-	p.park_timeout(Duration::from_millis(timeout_ms));
-	accumulate_wakers(readers)
-}
 
-impl<T> SyncEventChannel<T>
-{
-	/// Sends the event to the channel
-	///
-	/// This also wakes any threads waiting for new events via
-	/// [`SyncEventReader::wait_new`].
-	fn send(&self, e: T)
+	/// Gets the flushed events that `cursor` has not read, without marking
+	/// them as read
+	pub(crate) fn peek_from(&self, cursor: &Cursor) -> SyncEventIterator<'_, T>
 	{
-		let _lock = self.write_mutex.lock();
-		self.channel.send(e);
-		unsafe {
-			let waiters = &mut *self.new_event_waiters.get();
-			event_signal::signal_waiters(waiters);
+		let read_lock = self.events.read();
+		let range = cursor.unread(read_lock.generation, read_lock.flushed.events.len());
+		let events = unsafe { detach_slice(&read_lock.flushed.events[range]) };
+		SyncEventIterator {
+			read_lock,
+			iterator: events.iter(),
 		}
 	}
 
-	/// Flushes the channel
+	/// Reads the flushed events that `cursor` has not read, with their
+	/// metadata
+	pub(crate) fn read_meta_from(&self, cursor: &Cursor) -> SyncMetaIterator<'_, T>
+	{
+		let read_lock = self.lock_meta();
+		let range = cursor.advance(
+			read_lock.generation,
+			read_lock.flushed.events.len(),
+			usize::MAX,
+		);
+		meta_iterator(read_lock, range)
+	}
+
+	/// Reads the flushed events sent as `sequence` or later, with their
+	/// metadata, whether `cursor` has read them or not
 	///
-	/// Makes the currently sent un-flushed events readable.
+	/// Marks all flushed events as read.
+	pub(crate) fn read_since_from(
+		&self,
+		cursor: &Cursor,
+		sequence: u64,
+	) -> impl Iterator<Item = (&T, &EventMeta)>
+	{
+		let read_lock = self.lock_meta();
+		let len = read_lock.flushed.events.len();
+		cursor.seek(read_lock.generation, len, len);
+		// events of different writers are not flushed in sequence order
+		meta_iterator(read_lock, 0..len).filter(move |(_, meta)| meta.sequence >= sequence)
+	}
+
+	/// Moves `cursor` to `position` in the flushed events
+	pub(crate) fn seek_cursor(&self, cursor: &Cursor, position: usize)
+	{
+		let read_lock = self.events.read();
+		cursor.seek(
+			read_lock.generation,
+			read_lock.flushed.events.len(),
+			position,
+		);
+	}
+
+	fn lock_meta(&self) -> RwLockReadGuard<'_, Events<T>>
+	{
+		assert!(
+			self.keep_meta,
+			"channel {} does not keep metadata",
+			self.channel_id
+		);
+		self.events.read()
+	}
+
+	/// Takes the flushed events, and their metadata, unless `cursor` has
+	/// already read them
 	///
-	/// This drops all previously flushed events, making them unreadable.
+	/// Only for drainers, the only reader of the channel.
+	pub(crate) fn take_from<C>(&self, cursor: &C) -> (Vec<T>, Vec<EventMeta>)
+	where
+		C: ReadCursor,
+	{
+		let mut events = self.write_events();
+		let generation = events.generation;
+		if cursor.replace(generation) == generation {
+			return (Vec::new(), Vec::new());
+		}
+		let flushed = std::mem::take(&mut events.flushed);
+		(flushed.events, flushed.metas)
+	}
+
+	pub(crate) fn has_unread<C>(&self, cursor: &C) -> bool
+	where
+		C: ReadCursor,
+	{
+		self.has_flushed_since(cursor.get())
+	}
+
+	/// Gets the generation of the last flush
 	///
-	/// This also wakes any threads waiting for a flush via
-	/// [`SyncEventReader::wait_flushed`].
+	/// Also works while the thread is reading, with a flush waiting for the
+	/// lock.
+	fn last_flush(&self) -> u64 { self.events.read_recursive().generation }
+
+	/// Checks if the channel has been flushed after `generation` was read
+	pub(crate) fn has_flushed_since(&self, generation: u64) -> bool
+	{
+		generation != self.events.read().generation
+	}
+
+	pub(crate) fn add_waker_new(&self, waker: &Waker) -> Result<(), String>
+	{
+		self.new_event_waiters
+			.add_unless(waker, || self.has_new_events())
+			.map_err(|_| "already new unflushed events".to_string())
+	}
+
+	/// Adds the waker to be woken on the next flush after `generation`
 	///
-	/// Is is adviced to let one of the readers initiate the flush with
-	/// [`SyncEventReader::flush_channel`],
-	/// as they are controlling consumation of events.
-	pub fn flush(&self)
+	/// Not added if the channel is closed, as closing wakes the waiters.
+	pub(crate) fn add_waker_flushed(&self, waker: &Waker, generation: u64) -> Result<(), ()>
 	{
-		let _lock = self.flush_mutex.write();
-		self.channel.flush();
-		unsafe {
-			event_signal::signal_waiters(&mut *self.flushed_waiters.get());
-		}
+		self.flushed_waiters.add_unless(waker, || {
+			self.has_flushed_since(generation) || self.is_closed()
+		})
 	}
 
-	/// Creates a writer for this channel
-	pub fn get_writer(&self) -> SyncEventWriter<T>
+	/// Adds the waker to be woken on the next flush after `generation`, unless
+	/// the channel is disconnected
+	///
+	/// Un-flushed events left by the last writer are waited for, to be read
+	/// once flushed.
+	pub(crate) fn add_waker_batch(&self, waker: &Waker, generation: u64) -> Result<(), ()>
 	{
-		core_debug!("getting writer for channel {}", self.channel_id);
-		unsafe {
-			let writers = self.writers.get();
-			(*writers).fetch_add(1, Ordering::Relaxed);
+		self.flushed_waiters.add_unless(waker, || {
+			self.has_flushed_since(generation) || self.is_disconnected()
+		})
+	}
+
+	/// See [`SyncEventReader::wait_new`]
+	pub(crate) fn wait_new(&self) -> Result<usize, Disconnected>
+	{
+		loop {
+			let p = Parker::new();
+			let waker = parker_waker(&p);
+			if self.add_waker_new(&waker).is_err() {
+				return Ok(self.get_num_writers());
+			}
+			if self.is_disconnected() {
+				return Err(Disconnected);
+			}
+			park_checked(&p, None, || {
+				self.add_waker_new(&waker).is_ok() && !self.is_disconnected()
+			});
 		}
-		SyncEventWriter { channel: self }
 	}
 
-	/// Creates a reader for this channel
-	pub fn get_reader(&self) -> SyncEventReader<T>
+	/// See [`SyncEventReader::wait_new_timeout`]
+	pub(crate) fn wait_new_timeout(&self, timeout_ms: u64) -> Result<usize, Disconnected>
 	{
-		core_debug!("getting reader for channel {}", self.channel_id);
-		SyncEventReader {
-			read_events: UnsafeCell::new(1), // avoid stupid stuff when read=0
-			channel: self,
+		let p = Parker::new();
+		let waker = parker_waker(&p);
+		if self.add_waker_new(&waker).is_ok() && !self.is_disconnected() {
+			park_checked(&p, Some(Duration::from_millis(timeout_ms)), || {
+				self.add_waker_new(&waker).is_ok() && !self.is_disconnected()
+			});
+		}
+		match self.is_disconnected() {
+			true => Err(Disconnected),
+			false => Ok(self.get_num_writers()),
 		}
 	}
 
-	// expects the write_mutex to already be locked by this thread
-	// only called from reader.wait_new()
-	fn has_new_events(&self) -> bool
+	/// See [`SyncEventReader::wait_flushed`]
+	pub(crate) fn wait_flushed<C>(&self, cursor: &C) -> Result<(), Disconnected>
+	where
+		C: ReadCursor,
 	{
-		unsafe {
-			let buffer = self.channel.readable_buffer.get();
-			match *buffer {
-				// TODO this seems weird, look into it
-				ReadableEventBuffer::A => !(*self.channel.events_b.get()).is_empty(),
-				ReadableEventBuffer::B => !(*self.channel.events_a.get()).is_empty(),
-			}
+		let p = Parker::new();
+		if self
+			.add_waker_flushed(&parker_waker(&p), cursor.get())
+			.is_ok()
+		{
+			p.park();
 		}
+		self.read_status(cursor)
 	}
 
-	fn get_num_writers(&self) -> usize
+	/// See [`SyncEventReader::wait_flushed_timeout`]
+	pub(crate) fn wait_flushed_timeout<C>(
+		&self,
+		cursor: &C,
+		timeout_ms: u64,
+	) -> Result<(), Disconnected>
+	where
+		C: ReadCursor,
 	{
-		unsafe {
-			let writers = self.writers.get();
-			(*writers).load(Ordering::Relaxed)
+		let p = Parker::new();
+		if self
+			.add_waker_flushed(&parker_waker(&p), cursor.get())
+			.is_ok()
+		{
+			p.park_timeout(Duration::from_millis(timeout_ms));
 		}
+		self.read_status(cursor)
+	}
+
+	pub(crate) fn has_new_events(&self) -> bool
+	{
+		!self.shared.lock().events.is_empty()
+			|| self
+				.buffers
+				.lock()
+				.iter()
+				.any(|buffer| unsafe { !buffer.is_empty() })
+	}
+
+	fn take_shared(&self, events: &mut Batch<T>)
+	{
+		let mut shared = self.shared.lock();
+		events.merge(&mut shared);
+		self.has_shared.store(false, Ordering::Relaxed);
 	}
 
-	fn has_writers(&self) -> bool { self.get_num_writers() != 0 }
+	/// Gets the number of live writers
+	pub fn get_num_writers(&self) -> usize { self.writers.load(Ordering::Relaxed) }
+
+	pub(crate) fn has_writers(&self) -> bool { self.get_num_writers() != 0 }
 }
 
 impl<'a, T> SyncEventWriter<'a, T>
@@ -235,25 +1672,65 @@ impl<'a, T> SyncEventWriter<'a, T>
 	///
 	/// This also wakes any threads waiting for new events via
 	/// [`SyncEventReader::wait_new`].
-	pub fn send(&self, event: T) { self.channel.send(event); }
+	///
+	/// If the channel is [bounded](SyncEventChannel::bounded) and full, the
+	/// event is handled by the [`Overflow`] policy of the channel, and given
+	/// back in [`SendError::Full`] with [`Overflow::Error`]. Gives back
+	/// [`SendError::Closed`] if the channel is closed or its readers are all
+	/// dropped, like [`EventWriter::send`](crate::channel::EventWriter::send).
+	/// Events sent before the first reader is created are kept for it.
+	pub fn send(&self, event: T) -> Result<(), SendError<T>>
+	{
+		// the writer is not Sync, so only this thread pushes to the buffer
+		unsafe { self.channel.push(&self.buffer, event) }
+	}
+
+	/// Id of the writer, unique within its channel
+	///
+	/// Recorded as [`EventMeta::writer`] by channels keeping metadata.
+	pub fn id(&self) -> usize { self.buffer.id() }
 }
 
-impl<'a, T> Drop for SyncEventWriter<'a, T>
+impl<T> SyncEventWriter<'static, T>
+where
+	T: Send + Sync + 'static,
 {
-	fn drop(&mut self)
+	/// Sends the event to the channel once `delay` has passed
+	///
+	/// The event is held by the global
+	/// [`EventScheduler`](schedule::EventScheduler), and is sent the first
+	/// time the scheduler is advanced after the delay. The delay is measured
+	/// from the last time the scheduler was advanced. Requires the channel to
+	/// live for the rest of the program, like the resources in the `World`.
+	pub fn send_delayed(&self, event: T, delay: Duration)
 	{
-		unsafe {
-			let writers = self.channel.writers.get();
-			(*writers).fetch_sub(1, Ordering::Relaxed);
-			if (*writers).load(Ordering::Relaxed) == 0 {
-				let _lock = self.channel.write_mutex.lock();
-				event_signal::signal_waiters(&mut *self.channel.new_event_waiters.get());
-				event_signal::signal_waiters(&mut *self.channel.flushed_waiters.get());
-			}
-		}
+		self.schedule(event, Due::At(schedule::scheduler().now() + delay));
+	}
+
+	/// Sends the event to the channel when the scheduler reaches `frame`
+	///
+	/// If the frame has already passed, the event is sent on the next advance
+	/// of the global [`EventScheduler`](schedule::EventScheduler).
+	pub fn send_at_frame(&self, event: T, frame: u64) { self.schedule(event, Due::Frame(frame)); }
+
+	fn schedule(&self, event: T, due: Due)
+	{
+		let channel: &'static SyncEventChannel<T> = self.channel;
+		let writer = self.id();
+		schedule::scheduler().schedule(due, Box::new(move || channel.send(event, writer)));
 	}
 }
 
+impl<'a, T> Drop for SyncEventWriter<'a, T>
+{
+	fn drop(&mut self) { self.channel.remove_writer(&self.buffer); }
+}
+
+impl<'a, T> Drop for SyncEventReader<'a, T>
+{
+	fn drop(&mut self) { self.channel.remove_reader(); }
+}
+
 impl<'a, T> SyncEventReader<'a, T>
 {
 	/// Reads all unread events from this channel
@@ -261,54 +1738,80 @@ impl<'a, T> SyncEventReader<'a, T>
 	/// Giver an `Iterator` over the currently flushed events.
 	///
 	/// Becaus of how this is setup, it reads all flushed events, or none at all
-	/// if the flushed events have been read by this reader.
-	pub fn read(&self) -> impl Iterator<Item = &T>
+	/// if the flushed events have been read by this reader. Use
+	/// [`read_n`](SyncEventReader::read_n) to read part of them, and
+	/// [`peek`](SyncEventReader::peek) to look at them without reading.
+	pub fn read(&self) -> impl Iterator<Item = &T> { self.channel.read_from(&self.cursor) }
+
+	/// Reads all unread events, unless the reader is disconnected
+	///
+	/// Like [`read`](SyncEventReader::read), but gives [`Disconnected`] once
+	/// all events are read and the channel is closed or has no writers, so no
+	/// more events will come.
+	pub fn try_read(&self) -> Result<impl Iterator<Item = &T>, Disconnected>
 	{
-		let read_lock = self.channel.flush_mutex.read();
+		self.channel.read_status(&self.cursor)?;
+		Ok(self.read())
+	}
 
-		if !self.has_unread() {
-			return SyncEventIterator {
-				read_lock,
-				iterator: [].iter(),
-			};
-		}
+	/// Reads all unread events, together with their metadata
+	///
+	/// Like [`read`](SyncEventReader::read), for channels created
+	/// [`with_meta`](SyncEventChannel::with_meta).
+	///
+	/// # Panics
+	///
+	/// If the channel does not keep metadata.
+	pub fn read_with_meta(&self) -> impl Iterator<Item = (&T, &EventMeta)>
+	{
+		self.channel.read_meta_from(&self.cursor)
+	}
 
-		let channel = &self.channel.channel;
-		unsafe {
-			let readable_buffer = channel.readable_buffer.get();
-			let read_events = self.read_events.get();
-			let iterator = match *readable_buffer {
-				ReadableEventBuffer::A => {
-					let start_idx_a = *channel.start_idx_a.get();
-					*read_events = start_idx_a + 1;
-					(*channel.events_a.get()).iter()
-				}
-				ReadableEventBuffer::B => {
-					let start_idx_b = *channel.start_idx_b.get();
-					*read_events = start_idx_b + 1;
-					(*channel.events_b.get()).iter()
-				}
-			};
-			SyncEventIterator {
-				read_lock,
-				iterator,
-			}
-		}
+	/// Gets the unread events, without marking them as read
+	///
+	/// The next read gives the same events, unless the channel is flushed in
+	/// between.
+	pub fn peek(&self) -> impl Iterator<Item = &T> { self.channel.peek_from(&self.cursor) }
+
+	/// Reads at most `n` of the unread events
+	///
+	/// The rest of the flushed events stay unread, for the next read.
+	pub fn read_n(&self, n: usize) -> impl Iterator<Item = &T>
+	{
+		self.channel.read_n_from(&self.cursor, n)
 	}
 
-	// expects write_mutex to already be locked
-	fn has_unread(&self) -> bool
+	/// Moves the reader to `position` in the flushed events
+	///
+	/// The flushed events before `position` are marked as read, and the
+	/// others as unread. Past the end all are read.
+	pub fn seek(&self, position: usize) { self.channel.seek_cursor(&self.cursor, position); }
+
+	/// Marks all flushed events as unread, to read them again
+	///
+	/// Only the currently flushed events can be read again, as a flush drops
+	/// the events flushed before.
+	pub fn rewind(&self) { self.seek(0); }
+
+	/// Reads the events of the current flush sent as `sequence` or later,
+	/// whether read or not, together with their metadata
+	///
+	/// `sequence` is the [`EventMeta::sequence`] of the first wanted event,
+	/// e.g. the one after the last one handled before a failure. The channel
+	/// only keeps the current flush, so this retries within it: when
+	/// `sequence` was sent before the flush, the events from its start are
+	/// given, the earlier ones are gone. A consumer retrying across flushes
+	/// has to keep the events it has not handled itself, e.g. by taking them
+	/// with a [`SyncEventDrainer`](crate::channel::SyncEventDrainer). Marks all
+	/// flushed events as read.
+	///
+	/// # Panics
+	///
+	/// If the channel does not keep metadata, like
+	/// [`read_with_meta`](SyncEventReader::read_with_meta).
+	pub fn read_since(&self, sequence: u64) -> impl Iterator<Item = (&T, &EventMeta)>
 	{
-		let channel = &self.channel.channel;
-		unsafe {
-			let readable_buffer = channel.readable_buffer.get();
-			let read_events = self.read_events.get();
-			let start_idx = match *readable_buffer {
-				ReadableEventBuffer::A => channel.start_idx_a.get(),
-				ReadableEventBuffer::B => channel.start_idx_b.get(),
-			};
-			*read_events <= *start_idx
-		}
+		self.channel.read_since_from(&self.cursor, sequence)
 	}
 
 	/// Initiates a flush on the reader's connected channel
@@ -325,48 +1828,17 @@ impl<'a, T> SyncEventReader<'a, T>
 	///
 	/// If no events are present, the thread will halt and wake when the
 	/// next [`SyncEventWriter::send`] occurs.
-	/// If the last writers is dropped, the reader will be woken, so that this
-	/// case may be handled differently.
-	pub fn wait_new(&self) -> usize
-	{
-		let mut i = 0;
-		loop {
-			let _lock = self.channel.write_mutex.lock();
-			if self.channel.has_new_events() {
-				return self.channel.get_num_writers();
-			}
-
-			if i > 0 && !self.channel_has_writers() {
-				return 0;
-			}
-
-			unsafe {
-				let p = Parker::new();
-				event_signal::add_waiter(&mut *self.channel.new_event_waiters.get(), &p);
-				drop(_lock);
-				p.park_timeout(Duration::from_secs(2));
-			}
-			i += 1;
-		}
-	}
+	/// If the channel is closed or the last writer is dropped, the reader will
+	/// be woken, and gets [`Disconnected`] as no more events will be sent.
+	/// Flushed events may still be unread.
+	pub fn wait_new(&self) -> Result<usize, Disconnected> { self.channel.wait_new() }
 
 	/// Waits for un-flushed events to be present
 	///
 	/// Like [`wait_new`](SyncEventReader::wait_new), with a timeout in ms
-	pub fn wait_new_timeout(&self, timeout_ms: u64) -> usize
+	pub fn wait_new_timeout(&self, timeout_ms: u64) -> Result<usize, Disconnected>
 	{
-		let _lock = self.channel.write_mutex.lock();
-		if self.channel.has_new_events() {
-			return self.channel.get_num_writers();
-		}
-
-		unsafe {
-			let p = Parker::new();
-			event_signal::add_waiter(&mut *self.channel.new_event_waiters.get(), &p);
-			drop(_lock);
-			p.park_timeout(Duration::from_millis(timeout_ms));
-		}
-		self.channel.get_num_writers()
+		self.channel.wait_new_timeout(timeout_ms)
 	}
 
 	/// Waits for flushed un-read events to be present
@@ -376,41 +1848,72 @@ impl<'a, T> SyncEventReader<'a, T>
 	///
 	/// If the reader has read current events, it will halt and wake when the
 	/// next [`SyncEventChannel::flush`] occurs.
-	/// If the last writers is dropped, the reader will be woken, so that this
-	/// case may be handled differently.
+	/// If the channel is closed or the last writer is dropped, the reader will
+	/// be woken, so that this case may be handled differently. It gets
+	/// [`Disconnected`] if all events are read, and there are no un-flushed
+	/// events.
 	///
 	/// Note: This may lead to a deadlock if this thread is responsible for
 	/// flushing, but you already knew that. Also, note that it does not return
 	/// number of writers, unlike [`wait_new`](SyncEventReader::wait_new)
-	pub fn wait_flushed(&self)
+	pub fn wait_flushed(&self) -> Result<(), Disconnected>
 	{
-		let _lock = self.channel.flush_mutex.write();
-		if self.has_unread() {
-			return;
-		}
+		self.channel.wait_flushed(&self.cursor)
+	}
 
-		unsafe {
-			let p = Parker::new();
-			event_signal::add_waiter(&mut *self.channel.flushed_waiters.get(), &p);
-			drop(_lock);
-			p.park();
-		}
+	/// Waits for flushed un-read events to be present
+	///
+	/// Like [`wait_flushed`](SyncEventReader::wait_flushed), with a timeout in
+	/// ms
+	pub fn wait_flushed_timeout(&self, timeout_ms: u64) -> Result<(), Disconnected>
+	{
+		self.channel.wait_flushed_timeout(&self.cursor, timeout_ms)
 	}
 
 	/// Checks if there are any writers connected to reading channel
 	pub fn channel_has_writers(&self) -> bool { self.channel.has_writers() }
 }
 
-struct SyncEventIterator<'a, T>
+pub(crate) struct SyncEventIterator<'a, T, I = Iter<'a, T>>
 {
 	#[allow(dead_code)] // keep lock alive while iterating
-	read_lock: RwLockReadGuard<'a, ()>,
-	iterator: Iter<'a, T>,
+	read_lock: RwLockReadGuard<'a, Events<T>>,
+	iterator: I,
 }
 
-impl<'a, T> Iterator for SyncEventIterator<'a, T>
+pub(crate) type SyncMetaIterator<'a, T> =
+	SyncEventIterator<'a, T, Zip<Iter<'a, T>, Iter<'a, EventMeta>>>;
+
+impl<'a, T, I> Iterator for SyncEventIterator<'a, T, I>
+where
+	I: Iterator,
 {
-	type Item = &'a T;
+	type Item = I::Item;
 
 	fn next(&mut self) -> Option<Self::Item> { self.iterator.next() }
+
+	fn size_hint(&self) -> (usize, Option<usize>) { self.iterator.size_hint() }
+}
+
+impl<'a, T, I> ExactSizeIterator for SyncEventIterator<'a, T, I> where I: ExactSizeIterator {}
+
+/// Detaches the flushed `slice` from the read lock
+///
+/// The flushed events live as long as the channel, and can't be modified while
+/// the lock is held by the iterator.
+unsafe fn detach_slice<'a, U>(slice: &[U]) -> &'a [U] { &*(slice as *const [U]) }
+
+/// Iterates over the `range` of the flushed events and their metadata
+fn meta_iterator<T>(
+	read_lock: RwLockReadGuard<'_, Events<T>>,
+	range: Range<usize>,
+) -> SyncMetaIterator<'_, T>
+{
+	let flushed = &read_lock.flushed;
+	let events = unsafe { detach_slice(&flushed.events[range.clone()]) };
+	let metas = unsafe { detach_slice(&flushed.metas[range]) };
+	SyncEventIterator {
+		read_lock,
+		iterator: events.iter().zip(metas),
+	}
 }
@@ -0,0 +1,237 @@
+use crossbeam::sync::Parker;
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::task::Waker;
+
+use crate::sync_event_channel::{parker_waker, EventWaiter, Waiters};
+
+/// Thread-safe queue where every event is taken by one reader
+///
+/// Unlike the event channels, where every reader reads every event, readers
+/// of a work queue compete for the events. This suits jobs that only one of
+/// several workers should do. There is no flushing, events can be taken as
+/// soon as they are sent.
+///
+/// A taken event is held by [`Work`], which puts the event back first in the
+/// queue if dropped without being [acknowledged](Work::ack), e.g. when the
+/// worker panics. Events are otherwise taken in the order they were sent.
+pub struct WorkQueue<T>
+{
+	queue: Mutex<VecDeque<T>>,
+	waiters: Waiters,
+	writers: AtomicUsize,
+	in_progress: AtomicUsize,
+}
+
+/// Sends events to a [`WorkQueue`]
+///
+/// Created by [`WorkQueue::get_writer`].
+pub struct WorkQueueWriter<'a, T>
+{
+	queue: &'a WorkQueue<T>,
+}
+
+/// Takes events from a [`WorkQueue`]
+///
+/// Created by [`WorkQueue::get_reader`].
+pub struct WorkQueueReader<'a, T>
+{
+	queue: &'a WorkQueue<T>,
+}
+
+/// An event taken from a [`WorkQueue`]
+///
+/// Derefs to the event. Requeued when dropped, unless acknowledged with
+/// [`ack`](Work::ack).
+pub struct Work<'a, T>
+{
+	queue: &'a WorkQueue<T>,
+	event: Option<T>,
+}
+
+impl<T> Default for WorkQueue<T>
+{
+	fn default() -> Self
+	{
+		WorkQueue {
+			queue: Mutex::new(VecDeque::new()),
+			waiters: Waiters::default(),
+			writers: AtomicUsize::new(0),
+			in_progress: AtomicUsize::new(0),
+		}
+	}
+}
+
+impl<T> WorkQueue<T>
+{
+	/// Creates a writer for this queue
+	pub fn get_writer(&self) -> WorkQueueWriter<'_, T>
+	{
+		self.writers.fetch_add(1, Ordering::Relaxed);
+		WorkQueueWriter { queue: self }
+	}
+
+	/// Creates a reader for this queue
+	pub fn get_reader(&self) -> WorkQueueReader<'_, T> { WorkQueueReader { queue: self } }
+
+	/// Number of events waiting to be taken
+	pub fn len(&self) -> usize { self.queue.lock().len() }
+
+	pub fn is_empty(&self) -> bool { self.queue.lock().is_empty() }
+
+	/// Number of taken events that are not yet acknowledged or requeued
+	pub fn in_progress(&self) -> usize { self.in_progress.load(Ordering::Relaxed) }
+
+	/// Checks if there are any writers connected to the queue
+	pub fn has_writers(&self) -> bool { self.get_num_writers() != 0 }
+
+	fn get_num_writers(&self) -> usize { self.writers.load(Ordering::Relaxed) }
+
+	/// Pushes the event, and wakes the waiting readers
+	///
+	/// The waiters are checked after locking the queue, which they lock to
+	/// check for events after being added, so no wakeup is missed.
+	fn push(&self, event: T, front: bool)
+	{
+		{
+			let mut queue = self.queue.lock();
+			match front {
+				true => queue.push_front(event),
+				false => queue.push_back(event),
+			}
+		}
+		self.waiters.signal_if_waiting();
+	}
+
+	fn add_waker(&self, waker: &Waker) -> Result<(), String>
+	{
+		self.waiters
+			.add_unless(waker, || !self.is_empty())
+			.map_err(|_| "already events in queue".to_string())
+	}
+}
+
+impl<'a, T> WorkQueueWriter<'a, T>
+{
+	/// Sends the event to the queue, to be taken by one reader
+	///
+	/// This also wakes the threads waiting for events.
+	pub fn send(&self, event: T) { self.queue.push(event, false); }
+}
+
+impl<'a, T> Clone for WorkQueueWriter<'a, T>
+{
+	fn clone(&self) -> Self { self.queue.get_writer() }
+}
+
+impl<'a, T> Drop for WorkQueueWriter<'a, T>
+{
+	fn drop(&mut self)
+	{
+		if self.queue.writers.fetch_sub(1, Ordering::Relaxed) == 1 {
+			self.queue.waiters.signal();
+		}
+	}
+}
+
+impl<'a, T> WorkQueueReader<'a, T>
+{
+	/// Takes the next event, if there is one
+	pub fn take(&self) -> Option<Work<'a, T>>
+	{
+		let event = self.queue.queue.lock().pop_front()?;
+		self.queue.in_progress.fetch_add(1, Ordering::Relaxed);
+		Some(Work {
+			queue: self.queue,
+			event: Some(event),
+		})
+	}
+
+	/// Waits for an event and takes it
+	///
+	/// Returns `None` if the queue is empty and there are no writers left.
+	/// An event may be taken by another reader after waking, in which case
+	/// this waits again.
+	pub fn wait_take(&self) -> Option<Work<'a, T>>
+	{
+		loop {
+			if let Some(work) = self.take() {
+				return Some(work);
+			}
+			if self.wait_new() == 0 && self.queue.is_empty() {
+				return None;
+			}
+		}
+	}
+
+	/// Waits for events to be in the queue, returns number of active writers
+	///
+	/// Returns directly if there already are events, or if there are no
+	/// writers.
+	pub fn wait_new(&self) -> usize
+	{
+		// the last writer wakes the waiters, check for writers when added
+		let queue = self.queue;
+		let p = Parker::new();
+		if queue
+			.waiters
+			.add_unless(&parker_waker(&p), || {
+				!queue.is_empty() || !queue.has_writers()
+			})
+			.is_ok()
+		{
+			p.park();
+		}
+		queue.get_num_writers()
+	}
+
+	/// Checks if there are any writers connected to the queue
+	pub fn queue_has_writers(&self) -> bool { self.queue.has_writers() }
+}
+
+impl<'a, T> EventWaiter for WorkQueueReader<'a, T>
+{
+	fn add_waker_new(&self, waker: &Waker) -> Result<(), String> { self.queue.add_waker(waker) }
+
+	fn get_num_wakers(&self) -> usize { self.queue.get_num_writers() }
+
+	fn has_new(&self) -> bool { !self.queue.is_empty() }
+}
+
+impl<'a, T> Work<'a, T>
+{
+	/// Acknowledges the event as done, so it's not requeued
+	pub fn ack(mut self) -> T
+	{
+		self.queue.in_progress.fetch_sub(1, Ordering::Relaxed);
+		self.event.take().unwrap()
+	}
+
+	/// Puts the event back first in the queue, for any reader to take
+	pub fn requeue(self) {}
+}
+
+impl<'a, T> Deref for Work<'a, T>
+{
+	type Target = T;
+
+	fn deref(&self) -> &T { self.event.as_ref().unwrap() }
+}
+
+impl<'a, T> DerefMut for Work<'a, T>
+{
+	fn deref_mut(&mut self) -> &mut T { self.event.as_mut().unwrap() }
+}
+
+impl<'a, T> Drop for Work<'a, T>
+{
+	fn drop(&mut self)
+	{
+		if let Some(event) = self.event.take() {
+			self.queue.in_progress.fetch_sub(1, Ordering::Relaxed);
+			self.queue.push(event, true);
+		}
+	}
+}
@@ -0,0 +1,228 @@
+//! Derive macro for the events of the LY engine
+//!
+//! Re-exported by `ly_events`, see `ly_events::registry::Event` for the
+//! attributes.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{
+	parse_macro_input, parse_quote, Attribute, Data, DeriveInput, Error, Expr, Fields, Ident,
+	LitStr, Path, Result, Visibility,
+};
+
+const OVERFLOWS: [&str; 4] = ["Block", "DropOldest", "DropNewest", "Error"];
+
+/// Derives `Event`, configuring the channel of the event from `#[event(...)]`
+/// attributes
+#[proc_macro_derive(Event, attributes(event, serde))]
+pub fn derive_event(input: TokenStream) -> TokenStream
+{
+	let input = parse_macro_input!(input as DeriveInput);
+	expand(&input)
+		.unwrap_or_else(Error::into_compile_error)
+		.into()
+}
+
+/// What the `#[event(...)]` attributes declare
+#[derive(Default)]
+struct Config
+{
+	krate: Option<Path>,
+	capacity: Option<Expr>,
+	overflow: Option<Ident>,
+	coalesce: Option<Path>,
+	meta: bool,
+	/// `Flushes` or `Events`, and the count
+	retention: Option<(Ident, Expr)>,
+	serde: bool,
+}
+
+fn parse_config(attrs: &[Attribute]) -> Result<Config>
+{
+	let mut config = Config::default();
+	for attr in attrs.iter().filter(|attr| attr.path().is_ident("event")) {
+		attr.parse_nested_meta(|meta| {
+			if meta.path.is_ident("crate") {
+				let path: LitStr = meta.value()?.parse()?;
+				config.krate = Some(path.parse()?);
+			}
+			else if meta.path.is_ident("capacity") {
+				config.capacity = Some(meta.value()?.parse()?);
+			}
+			else if meta.path.is_ident("overflow") {
+				let overflow: Ident = meta.value()?.parse()?;
+				if !OVERFLOWS.iter().any(|o| overflow == o) {
+					let expected = OVERFLOWS.join(", ");
+					return Err(Error::new(
+						overflow.span(),
+						format!("expected one of {}", expected),
+					));
+				}
+				config.overflow = Some(overflow);
+			}
+			else if meta.path.is_ident("coalesce") {
+				config.coalesce = Some(meta.value()?.parse()?);
+			}
+			else if meta.path.is_ident("meta") {
+				config.meta = true;
+			}
+			else if meta.path.is_ident("retention") {
+				meta.parse_nested_meta(|retention| {
+					let kind = match retention.path.get_ident() {
+						Some(kind) if kind == "flushes" => format_ident!("Flushes"),
+						Some(kind) if kind == "events" => format_ident!("Events"),
+						_ => return Err(retention.error("expected `flushes` or `events`")),
+					};
+					config.retention = Some((kind, retention.value()?.parse()?));
+					Ok(())
+				})?;
+			}
+			else if meta.path.is_ident("serde") {
+				config.serde = true;
+			}
+			else {
+				return Err(meta.error("unknown event attribute"));
+			}
+			Ok(())
+		})?;
+	}
+	if let (Some(overflow), None) = (&config.overflow, &config.capacity) {
+		return Err(Error::new(overflow.span(), "overflow needs a capacity"));
+	}
+	Ok(config)
+}
+
+fn expand(input: &DeriveInput) -> Result<TokenStream2>
+{
+	let config = parse_config(&input.attrs)?;
+	let krate = config
+		.krate
+		.clone()
+		.unwrap_or_else(|| parse_quote!(::ly_events));
+	let name = &input.ident;
+	let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+	let mut channel = match &config.capacity {
+		Some(capacity) => {
+			let overflow = config
+				.overflow
+				.clone()
+				.unwrap_or_else(|| format_ident!("DropOldest"));
+			quote!(#krate::channel::SyncEventChannel::bounded(
+				#capacity,
+				#krate::channel::Overflow::#overflow
+			))
+		}
+		None => quote!(#krate::channel::SyncEventChannel::default()),
+	};
+	if config.meta {
+		channel = quote!(#channel.with_meta());
+	}
+	if let Some(rule) = &config.coalesce {
+		channel = quote!({
+			let channel = #channel;
+			channel.set_coalesce(#rule());
+			channel
+		});
+	}
+	let retention = config.retention.as_ref().map(|(kind, count)| {
+		quote!(
+			const RETENTION: #krate::channel::Retention = #krate::channel::Retention::#kind(#count);
+		)
+	});
+	let serde = match config.serde {
+		true => expand_serde(input, &krate)?,
+		false => TokenStream2::new(),
+	};
+
+	Ok(quote! {
+		impl #impl_generics #krate::registry::Event for #name #ty_generics #where_clause
+		{
+			#retention
+
+			fn channel() -> #krate::channel::SyncEventChannel<Self> { #channel }
+		}
+
+		#serde
+	})
+}
+
+/// Implements `Serialize` and `Deserialize`, through serde's derive of a
+/// copy of the type as a remote definition
+///
+/// A derive cannot add derives to the type it is on, so the copy is derived,
+/// with the `#[serde(...)]` attributes of the type.
+fn expand_serde(input: &DeriveInput, krate: &Path) -> Result<TokenStream2>
+{
+	if !input.generics.params.is_empty() {
+		return Err(Error::new_spanned(
+			&input.generics,
+			"serde is not supported for generic events",
+		));
+	}
+	let name = &input.ident;
+	let remote = format_ident!("__{}Serde", name);
+
+	let mut def = input.clone();
+	def.ident = remote.clone();
+	def.vis = Visibility::Inherited;
+	def.attrs.retain(is_serde);
+	match &mut def.data {
+		Data::Struct(data) => strip_fields(&mut data.fields),
+		Data::Enum(data) => {
+			for variant in &mut data.variants {
+				variant.attrs.retain(is_serde);
+				strip_fields(&mut variant.fields);
+			}
+		}
+		Data::Union(_) => {
+			return Err(Error::new_spanned(
+				name,
+				"serde is not supported for union events",
+			))
+		}
+	}
+
+	let remote_name = LitStr::new(&name.to_string(), name.span());
+	let serde_crate = LitStr::new(&quote!(#krate::__private::serde).to_string(), name.span());
+	Ok(quote! {
+		const _: () = {
+			use #krate::__private::serde;
+
+			#[derive(serde::Serialize, serde::Deserialize)]
+			#[serde(remote = #remote_name, crate = #serde_crate)]
+			#[allow(dead_code)]
+			#def
+
+			impl serde::Serialize for #name
+			{
+				fn serialize<S>(&self, serializer: S) -> ::core::result::Result<S::Ok, S::Error>
+				where
+					S: serde::Serializer,
+				{
+					#remote::serialize(self, serializer)
+				}
+			}
+
+			impl<'de> serde::Deserialize<'de> for #name
+			{
+				fn deserialize<D>(deserializer: D) -> ::core::result::Result<Self, D::Error>
+				where
+					D: serde::Deserializer<'de>,
+				{
+					#remote::deserialize(deserializer)
+				}
+			}
+		};
+	})
+}
+
+fn is_serde(attr: &Attribute) -> bool { attr.path().is_ident("serde") }
+
+fn strip_fields(fields: &mut Fields)
+{
+	for field in fields.iter_mut() {
+		field.attrs.retain(is_serde);
+	}
+}
@@ -14,6 +14,12 @@
 //! - strip_info
 //! - strip_warning
 //!
+//! On top of that, the least severe level that is logged can be lowered at
+//! runtime with [`set_log_level`].
+//!
+//! Listeners added with [`add_log_listener`] get every printed log,
+//! which is how e.g. a console can show them.
+//!
 //! Logs will indicate if they blocked the sender side.
 //! Can be dissallowed with the feature `dissallow_blocking`,
 //! in which case blocking events will panic.
@@ -33,9 +39,10 @@
 
 pub use colored::Colorize;
 use crossbeam::channel;
-use parking_lot::{Condvar, Mutex};
+use parking_lot::{const_rwlock, Condvar, Mutex, RwLock};
 use std::fmt;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::thread;
 use thread_local::ThreadLocal;
@@ -50,10 +57,13 @@ pub mod prelude
 pub mod core_prelude
 {
 	pub use super::{
-		core_debug, core_error, core_info, core_trace, core_warning, log_die, log_init,
+		add_log_listener, core_debug, core_error, core_info, core_trace, core_warning, log_die,
+		log_init, set_log_level,
 	};
 }
 
+/// Severity of a log, ordered from most to least severe
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum LogLevel
 {
 	Error,
@@ -63,6 +73,62 @@ pub enum LogLevel
 	Trace,
 }
 
+impl LogLevel
+{
+	fn from_usize(level: usize) -> Self
+	{
+		match level {
+			0 => LogLevel::Error,
+			1 => LogLevel::Warning,
+			2 => LogLevel::Info,
+			3 => LogLevel::Debug,
+			_ => LogLevel::Trace,
+		}
+	}
+}
+
+impl fmt::Display for LogLevel
+{
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+	{
+		let name = match self {
+			LogLevel::Error => "error",
+			LogLevel::Warning => "warning",
+			LogLevel::Info => "info",
+			LogLevel::Debug => "debug",
+			LogLevel::Trace => "trace",
+		};
+		write!(f, "{}", name)
+	}
+}
+
+impl FromStr for LogLevel
+{
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err>
+	{
+		match s.to_lowercase().as_str() {
+			"error" => Ok(LogLevel::Error),
+			"warning" | "warn" => Ok(LogLevel::Warning),
+			"info" => Ok(LogLevel::Info),
+			"debug" => Ok(LogLevel::Debug),
+			"trace" => Ok(LogLevel::Trace),
+			_ => Err(format!("unknown log level {}", s)),
+		}
+	}
+}
+
+static MAX_LEVEL: AtomicUsize = AtomicUsize::new(LogLevel::Trace as usize);
+
+/// Sets the least severe level that is logged
+///
+/// Levels stripped at compile time can not be enabled again.
+pub fn set_log_level(level: LogLevel) { MAX_LEVEL.store(level as usize, Ordering::Relaxed); }
+
+/// Gets the least severe level that is logged
+pub fn log_level() -> LogLevel { LogLevel::from_usize(MAX_LEVEL.load(Ordering::Relaxed)) }
+
 struct LogEvent
 {
 	level: LogLevel,
@@ -73,6 +139,50 @@ struct LogEvent
 	message: String,
 }
 
+/// A log as seen by listeners
+pub struct LogRecord<'a>
+{
+	pub level: LogLevel,
+	pub in_core: bool,
+	pub file: &'static str,
+	pub line: u32,
+	pub message: &'a str,
+}
+
+type LogListener = Box<dyn Fn(&LogRecord) + Send + Sync>;
+
+static LISTENERS: RwLock<Vec<LogListener>> = const_rwlock(Vec::new());
+
+/// Adds a listener called with every printed log
+///
+/// Listeners are called from the logging thread, so they should be quick,
+/// and must not log themselves.
+pub fn add_log_listener<F>(listener: F)
+where
+	F: Fn(&LogRecord) + Send + Sync + 'static,
+{
+	LISTENERS.write().push(Box::new(listener));
+}
+
+fn notify_listeners(event: &LogEvent)
+{
+	let listeners = LISTENERS.read();
+	if listeners.is_empty() {
+		return;
+	}
+
+	let record = LogRecord {
+		level: event.level,
+		in_core: event.in_core,
+		file: event.file,
+		line: event.line,
+		message: &event.message,
+	};
+	for listener in listeners.iter() {
+		listener(&record);
+	}
+}
+
 enum LogEnum
 {
 	Msg(LogEvent),
@@ -143,6 +253,7 @@ fn init_channel() -> (LogSender, CondPair)
 			for line in rx {
 				match line {
 					LogEnum::Msg(event) => {
+						notify_listeners(&event);
 						print_log_event(event);
 					}
 					LogEnum::Kill(msg) => {
@@ -193,6 +304,10 @@ pub fn __private_log(
 	args: fmt::Arguments,
 )
 {
+	if level as usize > MAX_LEVEL.load(Ordering::Relaxed) {
+		return;
+	}
+
 	let event = LogEvent {
 		level,
 		in_core,
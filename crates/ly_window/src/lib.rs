@@ -1,14 +1,17 @@
 #![feature(trait_alias)]
 
+mod recording;
 mod winit_converters;
 use std::error::Error;
 
 use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
+pub use recording::*;
 use winit_converters as converters;
 
-use ly_app::{App, AppRunner};
-use ly_events::channel::SyncEventChannel;
-use ly_events::types::{ButtonEvent, MouseEvent, WindowEvent};
+use ly_app::{App, AppInfo, AppRunner, Requirements};
+use ly_config::Config;
+use ly_events::registry::Events;
+use ly_events::types::WindowEvent;
 use ly_log::core_prelude::*;
 use winit::event;
 use winit::event_loop::{ControlFlow, EventLoop, EventLoopWindowTarget};
@@ -30,15 +33,31 @@ pub struct LyWindow
 	window: Window,
 }
 
+/// Title used when none is configured
+pub const DEFAULT_TITLE: &str = "Initial Window";
+
 /// Create a window to render to.
 /// Errors when winit is not able to create a window,
 /// reasons such as permission, memory, incompatability.
 pub fn create_window() -> Result<LyWindow, Box<dyn Error>>
+{
+	create_window_with_title(DEFAULT_TITLE)
+}
+
+/// Create a window configured by `config`
+///
+/// Uses `window.title` for the title. Errors like [`create_window`].
+pub fn create_window_from_config(config: &Config) -> Result<LyWindow, Box<dyn Error>>
+{
+	create_window_with_title(&config.get_or("window.title", DEFAULT_TITLE.to_string()))
+}
+
+fn create_window_with_title(title: &str) -> Result<LyWindow, Box<dyn Error>>
 {
 	// window
 	let event_loop = EventLoop::new();
 	let window = winit::window::WindowBuilder::new()
-		.with_title("Initial Window")
+		.with_title(title)
 		//.with_inner_size(winit::dpi::LogicalSize::new(800.0, 600.0))
 		//.with_min_inner_size(winit::dpi::PhysicalSize::new(100.0, 100.0))
 		.build(&event_loop)?;
@@ -56,8 +75,30 @@ impl LyWindow
 		self.event_loop.run_return(event_handler);
 	}
 
+	/// Consumes window to set the runner of `app`, declaring
+	/// [`forwarding_requirements`]
+	///
+	/// Uses the runner of [`get_app_runner`](LyWindow::get_app_runner).
+	pub fn set_app_runner(self, app: &mut App)
+	{
+		app.set_runner_requiring(self.get_app_runner(), forwarding_requirements());
+	}
+
+	/// Like [`set_app_runner`](LyWindow::set_app_runner), recording the
+	/// forwarded events with `recorder`
+	pub fn set_recording_app_runner(self, app: &mut App, recorder: InputRecorder)
+	{
+		app.set_runner_requiring(
+			self.get_recording_app_runner(recorder),
+			forwarding_requirements(),
+		);
+	}
+
 	/// Consumes window to generate a runner for [`App`](ly_app::App)
 	/// Uses event_loop generated by [`get_sync_forwarding_event_loop`].
+	///
+	/// Prefer [`set_app_runner`](LyWindow::set_app_runner), which also has
+	/// missing resources reported before the app starts.
 	pub fn get_app_runner(self) -> Box<AppRunner>
 	{
 		let closure = move |app: App| match get_sync_forwarding_event_loop(app) {
@@ -72,6 +113,24 @@ impl LyWindow
 		Box::new(closure)
 	}
 
+	/// Like [`get_app_runner`](LyWindow::get_app_runner), recording the
+	/// forwarded events with `recorder`
+	///
+	/// Uses event_loop generated by [`get_recording_event_loop`].
+	pub fn get_recording_app_runner(self, recorder: InputRecorder) -> Box<AppRunner>
+	{
+		let closure = move |app: App| match get_recording_event_loop(app, recorder) {
+			Ok(event_handler) => self.run(event_handler),
+			Err(e) => {
+				core_error!(
+					"winit event loop does not have required resources: \n\t{}",
+					e
+				)
+			}
+		};
+		Box::new(closure)
+	}
+
 	/// Get the RawWindowHandle of the underlying window
 	pub fn get_raw_handle(&self) -> RawWindowHandle { self.window.raw_window_handle() }
 
@@ -93,25 +152,42 @@ pub fn get_empty_event_loop() -> Box<dyn EventHandler>
 	})
 }
 
-/// Gets event loop that forwards events to channels in the resources
-/// Errors if there are no `SyncEventChannel`s for window, mouse, and button
-/// events
+/// The resources required by [`get_sync_forwarding_event_loop`], and the
+/// replay runner
+pub fn forwarding_requirements() -> Requirements
+{
+	Requirements::new("winit forwarding event loop").resource::<Events>()
+}
+
+/// Gets event loop that forwards events to channels in the [`Events`]
+/// resource, adding the channels for window, mouse, and button events
+/// Errors if there is no [`Events`] resource, or if one of the channels is a
+/// resource of its own
 pub fn get_sync_forwarding_event_loop<'a>(
+	app: App,
+) -> Result<Box<dyn EventHandler + 'a>, Box<dyn Error>>
+{
+	forwarding_event_loop(app, None)
+}
+
+/// Like [`get_sync_forwarding_event_loop`], also recording all forwarded
+/// events and frame timings with `recorder`
+///
+/// The recording can be replayed with [`get_replay_runner`].
+pub fn get_recording_event_loop<'a>(
+	app: App,
+	recorder: InputRecorder,
+) -> Result<Box<dyn EventHandler + 'a>, Box<dyn Error>>
+{
+	forwarding_event_loop(app, Some(recorder))
+}
+
+fn forwarding_event_loop<'a>(
 	mut app: App,
+	recorder: Option<InputRecorder>,
 ) -> Result<Box<dyn EventHandler + 'a>, Box<dyn Error>>
 {
-	let writer_window = app
-		.world
-		.get_resource::<SyncEventChannel<WindowEvent>>()?
-		.get_writer();
-	let writer_button = app
-		.world
-		.get_resource::<SyncEventChannel<ButtonEvent>>()?
-		.get_writer();
-	let writer_mouse = app
-		.world
-		.get_resource::<SyncEventChannel<MouseEvent>>()?
-		.get_writer();
+	let mut forwarder = Forwarder::new(&app.world, recorder)?;
 
 	Ok(Box::new(
 		move |event, _, control_flow: &mut ControlFlow| match event {
@@ -122,20 +198,20 @@ pub fn get_sync_forwarding_event_loop<'a>(
 			} => match event {
 				event::WindowEvent::CloseRequested => {
 					core_info!("closing window");
-					writer_window.send(WindowEvent::WindowClose);
+					forwarder.window(WindowEvent::WindowClose);
 					*control_flow = ControlFlow::Exit;
 				}
 				event::WindowEvent::MouseInput { button, state, .. } => {
-					writer_button.send(converters::convert_mouse_button(button, state));
+					forwarder.button(converters::convert_mouse_button(button, state));
 				}
 				event::WindowEvent::CursorMoved { position, .. } => {
-					writer_mouse.send(converters::convert_cursor_move(position));
+					forwarder.mouse(converters::convert_cursor_move(position));
 				}
 				event::WindowEvent::KeyboardInput { input, .. } => {
-					writer_button.send(converters::convert_keyboard_input(input));
+					forwarder.button(converters::convert_keyboard_input(input));
 				}
 				event::WindowEvent::MouseWheel { delta, .. } => {
-					writer_button.send(converters::convert_mouse_scroll(delta));
+					forwarder.button(converters::convert_mouse_scroll(delta));
 				}
 				_ => (),
 			},
@@ -143,10 +219,19 @@ pub fn get_sync_forwarding_event_loop<'a>(
 				event: event::DeviceEvent::MouseMotion { delta },
 				device_id: _winit_device_id,
 			} => {
-				writer_mouse.send(converters::convert_mouse_move(delta));
+				forwarder.mouse(converters::convert_mouse_move(delta));
 			}
 			event::Event::MainEventsCleared => {}
-			_ => app.update(),
+			_ => {
+				app.update();
+				forwarder.frame(&app.world);
+				if let Ok(info) = app.world.get_resource::<AppInfo>() {
+					if info.exit_requested() {
+						core_info!("exit requested, closing window");
+						*control_flow = ControlFlow::Exit;
+					}
+				}
+			}
 		},
 	))
 }
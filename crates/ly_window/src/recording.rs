@@ -0,0 +1,405 @@
+//! Recording and replay of the events forwarded by the window
+
+use ly_app::{App, AppInfo, AppRunner, Time, World};
+use ly_events::channel::{OwnedSyncEventWriter, SyncEventChannel};
+use ly_events::registry::Events;
+use ly_events::types::{ButtonEvent, MouseEvent, SplitWriter, WindowEvent};
+use ly_log::core_prelude::*;
+use serde::{Deserialize, Serialize};
+use std::any::type_name;
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, ErrorKind, Read, Write};
+use std::path::Path;
+use std::time::Duration;
+
+const MAGIC: &[u8; 5] = b"LYREC";
+const VERSION: u16 = 1;
+
+/// An entry in a recording
+///
+/// Events are recorded in the order they were forwarded, and each app update
+/// ends a frame. Replaying the entries in order gives the same updates.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RecordEntry
+{
+	Button(ButtonEvent),
+	Mouse(MouseEvent),
+	Window(WindowEvent),
+	/// The app was updated, with the frame clock advancing by `delta_nanos`
+	Frame
+	{
+		delta_nanos: u64,
+	},
+}
+
+/// Writes forwarded events and frame timings to a file
+pub struct InputRecorder
+{
+	writer: BufWriter<File>,
+}
+
+impl InputRecorder
+{
+	/// Creates the recording file, replacing any existing file
+	pub fn create<P>(path: P) -> Result<Self, Box<dyn Error>>
+	where
+		P: AsRef<Path>,
+	{
+		let mut writer = BufWriter::new(File::create(path)?);
+		writer.write_all(MAGIC)?;
+		writer.write_all(&VERSION.to_le_bytes())?;
+		Ok(InputRecorder { writer })
+	}
+
+	/// Appends an entry to the recording
+	pub fn record(&mut self, entry: &RecordEntry) -> Result<(), Box<dyn Error>>
+	{
+		bincode::serialize_into(&mut self.writer, entry)?;
+		Ok(())
+	}
+
+	/// Writes buffered entries to the file
+	pub fn flush(&mut self) -> Result<(), Box<dyn Error>>
+	{
+		self.writer.flush()?;
+		Ok(())
+	}
+}
+
+/// Reads the entries of a recording made by [`InputRecorder`]
+pub struct InputReplay
+{
+	reader: BufReader<File>,
+}
+
+impl InputReplay
+{
+	/// Opens a recording, checking that it is a recording of this version
+	pub fn open<P>(path: P) -> Result<Self, Box<dyn Error>>
+	where
+		P: AsRef<Path>,
+	{
+		let mut reader = BufReader::new(File::open(path)?);
+		let mut magic = [0; 5];
+		reader.read_exact(&mut magic)?;
+		if &magic != MAGIC {
+			return Err("not an input recording".into());
+		}
+		let mut version = [0; 2];
+		reader.read_exact(&mut version)?;
+		let version = u16::from_le_bytes(version);
+		if version != VERSION {
+			return Err(format!("recording has version {}, expected {}", version, VERSION).into());
+		}
+		Ok(InputReplay { reader })
+	}
+}
+
+impl Iterator for InputReplay
+{
+	type Item = Result<RecordEntry, Box<dyn Error>>;
+
+	fn next(&mut self) -> Option<Self::Item>
+	{
+		match bincode::deserialize_from(&mut self.reader) {
+			Ok(entry) => Some(Ok(entry)),
+			Err(e) => match *e {
+				bincode::ErrorKind::Io(ref io) if io.kind() == ErrorKind::UnexpectedEof => None,
+				_ => Some(Err(e)),
+			},
+		}
+	}
+}
+
+/// Asks for the forwarded events to also be sent as fine-grained events
+///
+/// When this resource is in the world, the window and the replay runner also
+/// send the grouped events to the channels of the fine-grained events, like
+/// [`KeyPressed`](ly_events::types::KeyPressed), through a [`SplitWriter`].
+/// These channels keep metadata, which has a cost on every send, so they are
+/// only fed when asked for.
+#[derive(Debug, Default)]
+pub struct SplitEvents;
+
+/// Sends events to the channels in the world, recording them if asked to
+///
+/// Events given back by a full or closed channel are dropped.
+pub(crate) struct Forwarder
+{
+	window: OwnedSyncEventWriter<WindowEvent>,
+	button: OwnedSyncEventWriter<ButtonEvent>,
+	mouse: OwnedSyncEventWriter<MouseEvent>,
+	split: Option<SplitWriter>,
+	recorder: Option<InputRecorder>,
+}
+
+impl Forwarder
+{
+	/// Adds the channels for window, mouse, and button events, and their
+	/// fine-grained events if there is a [`SplitEvents`] resource, to the
+	/// [`Events`] in the world, if not already added
+	///
+	/// Errors if there is no [`Events`] resource, or if one of the channels is
+	/// a resource of its own, which would never get the events
+	pub(crate) fn new(
+		world: &World,
+		recorder: Option<InputRecorder>,
+	) -> Result<Self, Box<dyn Error>>
+	{
+		let events = world.get_resource::<Events>()?;
+		Ok(Forwarder {
+			window: writer::<WindowEvent>(world, events)?,
+			button: writer::<ButtonEvent>(world, events)?,
+			mouse: writer::<MouseEvent>(world, events)?,
+			split: world
+				.get_resource::<SplitEvents>()
+				.ok()
+				.map(|_| SplitWriter::new(events)),
+			recorder,
+		})
+	}
+
+	pub(crate) fn window(&mut self, event: WindowEvent)
+	{
+		self.record(|| RecordEntry::Window(event.clone()));
+		if let Some(split) = &self.split {
+			split.window(&event);
+		}
+		let _ = self.window.send(event);
+	}
+
+	pub(crate) fn button(&mut self, event: ButtonEvent)
+	{
+		self.record(|| RecordEntry::Button(event.clone()));
+		if let Some(split) = &self.split {
+			split.button(&event);
+		}
+		let _ = self.button.send(event);
+	}
+
+	pub(crate) fn mouse(&mut self, event: MouseEvent)
+	{
+		self.record(|| RecordEntry::Mouse(event.clone()));
+		if let Some(split) = &self.split {
+			split.mouse(&event);
+		}
+		let _ = self.mouse.send(event);
+	}
+
+	/// Records the end of a frame, after the app has been updated
+	pub(crate) fn frame(&mut self, world: &World)
+	{
+		if self.recorder.is_none() {
+			return;
+		}
+		let delta = world
+			.get_resource::<Time>()
+			.map(|time| time.delta())
+			.unwrap_or_default();
+		self.record(|| RecordEntry::Frame {
+			delta_nanos: delta.as_nanos() as u64,
+		});
+	}
+
+	fn record<F>(&mut self, entry: F)
+	where
+		F: FnOnce() -> RecordEntry,
+	{
+		if let Some(recorder) = &mut self.recorder {
+			if let Err(e) = recorder.record(&entry()) {
+				core_error!("stopping recording, could not write: {}", e);
+				self.recorder = None;
+			}
+		}
+	}
+}
+
+impl Drop for Forwarder
+{
+	fn drop(&mut self)
+	{
+		if let Some(recorder) = &mut self.recorder {
+			if let Err(e) = recorder.flush() {
+				core_error!("could not flush recording: {}", e);
+			}
+		}
+	}
+}
+
+/// Gets a writer to the channel of `T` in `events`
+///
+/// Channels used to be resources of their own, these are rejected instead of
+/// silently not getting any events.
+fn writer<T>(world: &World, events: &Events) -> Result<OwnedSyncEventWriter<T>, Box<dyn Error>>
+where
+	T: Send + Sync + 'static,
+{
+	if world.get_resource::<SyncEventChannel<T>>().is_ok() {
+		return Err(format!(
+			"{} is a resource, but events are forwarded to the channels in {}, add the channel \
+			 with App::add_event instead",
+			type_name::<SyncEventChannel<T>>(),
+			type_name::<Events>()
+		)
+		.into());
+	}
+	Ok(events.add::<T>().get_owned_writer())
+}
+
+/// Sets the runner of `app` to replay a recording, declaring
+/// [`forwarding_requirements`](crate::forwarding_requirements)
+///
+/// Uses the runner of [`get_replay_runner`].
+pub fn set_replay_runner(app: &mut App, replay: InputReplay)
+{
+	app.set_runner_requiring(get_replay_runner(replay), crate::forwarding_requirements());
+}
+
+/// Generates a runner for [`App`] replaying a recording
+///
+/// Events are sent to the same channels as the forwarding event loop, and
+/// the app is updated with the recorded frame deltas. No window is needed.
+/// Stops at the end of the recording, or when exit is requested.
+pub fn get_replay_runner(replay: InputReplay) -> Box<AppRunner>
+{
+	let closure = move |mut app: App| {
+		let mut forwarder = match Forwarder::new(&app.world, None) {
+			Ok(forwarder) => forwarder,
+			Err(e) => {
+				core_error!("replay does not have required resources: \n\t{}", e);
+				return;
+			}
+		};
+
+		for entry in replay {
+			match entry {
+				Ok(RecordEntry::Button(event)) => forwarder.button(event),
+				Ok(RecordEntry::Mouse(event)) => forwarder.mouse(event),
+				Ok(RecordEntry::Window(event)) => forwarder.window(event),
+				Ok(RecordEntry::Frame { delta_nanos }) => {
+					app.update_with_delta(Duration::from_nanos(delta_nanos));
+					if let Ok(info) = app.world.get_resource::<AppInfo>() {
+						if info.exit_requested() {
+							core_info!("exit requested, stopping replay");
+							return;
+						}
+					}
+				}
+				Err(e) => {
+					core_error!("stopping replay, could not read recording: {}", e);
+					return;
+				}
+			}
+		}
+		core_info!("replay finished");
+	};
+	Box::new(closure)
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+	use ly_input::MouseButton;
+	use std::path::PathBuf;
+
+	fn temp_path(name: &str) -> PathBuf
+	{
+		std::env::temp_dir().join(format!("ly_{}_{}.rec", name, std::process::id()))
+	}
+
+	#[test]
+	fn recording_001()
+	{
+		let entries = vec![
+			RecordEntry::Button(ButtonEvent::MousePressed(MouseButton::Left)),
+			RecordEntry::Mouse(MouseEvent::CursorMove(1.5, -2.0)),
+			RecordEntry::Frame {
+				delta_nanos: 16_000_000,
+			},
+			RecordEntry::Window(WindowEvent::WindowResized(800, 600)),
+			RecordEntry::Frame { delta_nanos: 0 },
+		];
+		let path = temp_path("roundtrip");
+		let mut recorder = InputRecorder::create(&path).unwrap();
+		for entry in entries.iter() {
+			recorder.record(entry).unwrap();
+		}
+		recorder.flush().unwrap();
+
+		let replayed: Vec<_> = InputReplay::open(&path)
+			.unwrap()
+			.map(|entry| entry.unwrap())
+			.collect();
+		std::fs::remove_file(&path).unwrap();
+		assert_eq!(replayed, entries);
+	}
+
+	#[test]
+	/// test rejecting files that are not recordings of this version
+	fn recording_002()
+	{
+		let path = temp_path("invalid");
+		std::fs::write(&path, b"NOTREC\x01\x00").unwrap();
+		let error = InputReplay::open(&path).err().unwrap();
+		assert_eq!(error.to_string(), "not an input recording");
+
+		let mut file = MAGIC.to_vec();
+		file.extend((VERSION + 1).to_le_bytes());
+		std::fs::write(&path, file).unwrap();
+		let error = InputReplay::open(&path).err().unwrap();
+		assert_eq!(
+			error.to_string(),
+			format!(
+				"recording has version {}, expected {}",
+				VERSION + 1,
+				VERSION
+			)
+		);
+
+		std::fs::write(&path, b"LY").unwrap();
+		assert!(InputReplay::open(&path).is_err(), "truncated header");
+		std::fs::remove_file(&path).unwrap();
+	}
+
+	#[test]
+	/// test rejecting channels that are resources of their own
+	fn forwarding_001()
+	{
+		let world = World::new();
+		world.create_resource::<Events>().unwrap();
+		world
+			.create_resource::<SyncEventChannel<WindowEvent>>()
+			.unwrap();
+		let error = Forwarder::new(&world, None).err().unwrap();
+		assert!(error.to_string().contains("App::add_event"), "{}", error);
+	}
+
+	#[test]
+	/// test sending the fine-grained events only when asked for
+	fn forwarding_002()
+	{
+		use ly_events::types::CloseRequested;
+
+		// resources are global, so the forwarders are built without the world
+		let events = Events::default();
+		let forwarder = |split: bool| Forwarder {
+			window: events.add().get_owned_writer(),
+			button: events.add().get_owned_writer(),
+			mouse: events.add().get_owned_writer(),
+			split: split.then(|| SplitWriter::new(&events)),
+			recorder: None,
+		};
+		let reader = events.add::<WindowEvent>().get_owned_reader().unwrap();
+		forwarder(false).window(WindowEvent::WindowClose);
+		assert!(!events.contains::<CloseRequested>());
+
+		let mut split_forwarder = forwarder(true);
+		let split = events.get_reader::<CloseRequested>().unwrap();
+		split_forwarder.window(WindowEvent::WindowClose);
+		events.flush();
+		assert_eq!(reader.read().count(), 2);
+		assert_eq!(split.read().count(), 1);
+	}
+}
@@ -0,0 +1,76 @@
+//! Chat between processes through an event bridge
+//!
+//! Start the server with `cargo run --example event_bridge --features serde
+//! -- listen`, and any number of clients with `... -- connect`. Lines typed
+//! in any process are printed by all of them.
+
+use rustly::events::bridge::{BridgeClient, EventBridge};
+use rustly::events::channel::SyncEventChannel;
+use std::io::BufRead;
+use std::sync::Arc;
+use std::time::Duration;
+use std::{env, io, thread};
+
+fn main()
+{
+	let mode = env::args().nth(1).unwrap_or_default();
+	let path = env::args().nth(2).unwrap_or_else(|| {
+		env::temp_dir()
+			.join("ly_event_bridge")
+			.display()
+			.to_string()
+	});
+
+	match mode.as_str() {
+		"listen" => listen(&path),
+		"connect" => connect(&path),
+		_ => eprintln!("usage: event_bridge listen|connect [socket path]"),
+	}
+}
+
+fn listen(path: &str)
+{
+	let channel = Arc::new(SyncEventChannel::<String>::default());
+	let _bridge = EventBridge::listen(&channel, path).unwrap();
+	println!("listening on {}", path);
+
+	let writer = channel.get_owned_writer();
+	thread::spawn(move || {
+		for line in io::stdin().lock().lines() {
+			writer.send(format!("server: {}", line.unwrap())).unwrap();
+		}
+	});
+
+	// the bridge does not flush, the process owning the channel does
+	let reader = channel.get_reader().expect("the channel was just created");
+	loop {
+		thread::sleep(Duration::from_millis(10));
+		channel.flush();
+		for line in reader.read() {
+			println!("{}", line);
+		}
+	}
+}
+
+fn connect(path: &str)
+{
+	let client = Arc::new(BridgeClient::<String>::connect(path));
+	let sender = Arc::clone(&client);
+	thread::spawn(move || {
+		for line in io::stdin().lock().lines() {
+			let line = format!("client {}: {}", std::process::id(), line.unwrap());
+			if let Err(e) = sender.send(&line) {
+				eprintln!("{}", e);
+			}
+		}
+	});
+
+	let reader = client.channel().get_reader().expect("the bridge channel is never drained");
+	loop {
+		thread::sleep(Duration::from_millis(10));
+		reader.flush_channel();
+		for line in reader.read() {
+			println!("{}", line);
+		}
+	}
+}
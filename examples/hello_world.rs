@@ -1,15 +1,15 @@
 use std::sync::atomic::{AtomicUsize, Ordering};
 
-use ly_app::{AppInfo, AppState, World};
-use ly_events::channel::wait_any_new;
-use ly_events::channel::wait_any_new_timeout;
+use ly_app::{AppInfo, AppState, Requirements, World};
 use rustly::app::App;
-use rustly::events::channel::EventWaiter;
-use rustly::events::channel::SyncEventChannel;
+use rustly::config::{Config, ConfigPlugin};
+use rustly::console::{Console, ConsolePlugin};
+use rustly::events::channel::{Select, SelectError};
+use rustly::events::registry::Events;
 use rustly::events::types::{ButtonEvent, MouseEvent, WindowEvent};
 use rustly::log::*;
 use rustly::window;
-use std::thread;
+use std::time::Duration;
 
 #[derive(Debug)]
 struct MyEvent {}
@@ -17,23 +17,35 @@ struct MyEvent {}
 fn main()
 {
 	let mut app = App::new();
-	let window = window::create_window().unwrap();
+	app.add_plugin(ConfigPlugin);
+	app.add_plugin(ConsolePlugin { stdin: true });
+	let config = app.world.get_resource::<Config>().unwrap();
+	let window = window::create_window_from_config(config).unwrap();
 
-	app.world
-		.create_resource::<SyncEventChannel<ButtonEvent>>()
-		.unwrap();
-	app.world
-		.create_resource::<SyncEventChannel<MouseEvent>>()
-		.unwrap();
-	app.world
-		.create_resource::<SyncEventChannel<WindowEvent>>()
-		.unwrap();
+	app.add_event::<ButtonEvent>();
+	app.add_event::<MouseEvent>();
+	app.add_event::<WindowEvent>();
 	app.world.create_resource::<AtomicUsize>().unwrap();
+	app.world
+		.get_resource::<Console>()
+		.unwrap()
+		.register_resource::<AtomicUsize>("updates");
 
-	let runner = window.get_app_runner();
+	// record with `-s input.record=<file>`, replay with `-s input.replay=<file>`
+	if let Some(path) = config.get::<String>("input.replay") {
+		window::set_replay_runner(&mut app, window::InputReplay::open(path).unwrap());
+	}
+	else if let Some(path) = config.get::<String>("input.record") {
+		window.set_recording_app_runner(&mut app, window::InputRecorder::create(path).unwrap());
+	}
+	else {
+		window.set_app_runner(&mut app);
+	}
 	app.add_process(thing_i_want_to_do);
-	app.add_system(basic_system);
-	app.set_runner(runner);
+	app.add_system_requiring(
+		basic_system,
+		Requirements::new("basic_system").resource::<AtomicUsize>(),
+	);
 	app.run();
 }
 
@@ -46,35 +58,37 @@ fn basic_system(world: &World)
 
 fn thing_i_want_to_do(world: &World)
 {
-	let reader_m = world
-		.get_resource::<SyncEventChannel<MouseEvent>>()
-		.unwrap()
-		.get_reader();
-	let reader_b = world
-		.get_resource::<SyncEventChannel<ButtonEvent>>()
-		.unwrap()
-		.get_reader();
+	let events = world.get_resource::<Events>().unwrap();
+	let reader_m = events.get_reader::<MouseEvent>().unwrap();
+	let reader_b = events.get_reader::<ButtonEvent>().unwrap();
 
-	let arr: [&dyn EventWaiter; 2] = [&reader_b, &reader_m];
+	let mut select = Select::new();
+	let button = select.add(&reader_b);
+	let mouse = select.add(&reader_m);
 
 	loop {
-		// need this for some reason, or it will drop events
-		// TODO why?
-		// thread::sleep(Duration::from_millis(1));
-		// wait_new solves it here, but not root cause
-		//reader_b.wait_new();
-
 		debug!("waiting...");
-		//wait_any_new_timeout(&arr, 500);
-		wait_any_new(&arr);
+		let selected = select.select_timeout(Duration::from_millis(500));
 		if let AppState::Stopped = world.get_resource::<AppInfo>().unwrap().state() {
 			info!("Application quit, breaking read loop!");
 			break;
 		}
-		//wait_any_new(&[&reader_b as &dyn EventWaiter]);
-		debug!("got new...");
 
-		reader_b.flush_channel();
+		// the app flushes the channels every update, wait for it to read
+		let flushed = match selected {
+			Ok(i) if i == button => reader_b.wait_flushed(),
+			Ok(i) if i == mouse => reader_m.wait_flushed(),
+			Ok(_) => unreachable!("only two readers are selected"),
+			Err(SelectError::Timeout) => continue,
+			Err(SelectError::Disconnected) => {
+				warning!("the window no longer forwards events");
+				break;
+			}
+		};
+		if flushed.is_err() {
+			continue;
+		}
+
 		for event in reader_b.read() {
 			if let ButtonEvent::MousePressed(ly_input::MouseButton::Left) = event {
 				let count = world.get_resource::<AtomicUsize>().unwrap();
@@ -82,19 +96,8 @@ fn thing_i_want_to_do(world: &World)
 			}
 			info!("recieved {:?}", event);
 		}
-
-		reader_m.flush_channel();
 		for event in reader_m.read() {
 			info!("recieved {:?}", event);
 		}
-
-		if !reader_b.channel_has_writers() {
-			warning!("button no longer has readers");
-			break;
-		}
-		if !reader_m.channel_has_writers() {
-			warning!("mouse no longer has readers");
-			break;
-		}
 	}
 }
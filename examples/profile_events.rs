@@ -15,14 +15,14 @@ const NUM_EVENTS: usize = 10000000;
 fn run() -> usize
 {
 	let channel = Arc::new(events::channel::SyncEventChannel::<MyEvent>::default());
-	let reader = channel.get_reader();
+	let reader = channel.get_reader().unwrap();
 	let writer = channel.get_writer();
 	let mut total: usize = 0;
 
 	let time = Instant::now();
 	for i in 0..NUM_EVENTS {
 		let event = MyEvent { num: i };
-		writer.send(event);
+		let _ = writer.send(event);
 
 		if i % READ_BATCH == 0 {
 			channel.flush();
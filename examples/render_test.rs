@@ -1,5 +1,4 @@
 use rustly::app::App;
-use rustly::events::channel::SyncEventChannel;
 use rustly::events::types::*;
 use rustly::log::*;
 use rustly::renderer;
@@ -7,18 +6,12 @@ use rustly::window;
 
 pub fn main()
 {
-	let app = App::new();
+	let mut app = App::new();
 	let window = window::create_window().unwrap();
 
-	app.world
-		.create_resource::<SyncEventChannel<ButtonEvent>>()
-		.unwrap();
-	app.world
-		.create_resource::<SyncEventChannel<MouseEvent>>()
-		.unwrap();
-	app.world
-		.create_resource::<SyncEventChannel<WindowEvent>>()
-		.unwrap();
+	app.add_event::<ButtonEvent>();
+	app.add_event::<MouseEvent>();
+	app.add_event::<WindowEvent>();
 
 	match renderer::LyRenderer::new(window.get_handle()) {
 		Ok(_) => info!("All is good!"),
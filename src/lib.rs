@@ -38,6 +38,25 @@ pub mod app
 	pub use ly_app::*;
 }
 
+/// Configuration for LY engine clients
+///
+/// Contains the [`Config`](config::Config) loaded from file, environment and
+/// arguments, and the registry of runtime variables.
+///
+/// crate doc: [ly_config]
+pub mod config
+{
+	pub use ly_config::*;
+}
+
+/// Developer console for LY engine clients
+///
+/// crate doc: [ly_console]
+pub mod console
+{
+	pub use ly_console::*;
+}
+
 /// Event system for LY engine clients
 ///
 /// The crate provides functionality to send event via "channels"